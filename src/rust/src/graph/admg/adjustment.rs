@@ -2,8 +2,33 @@
 //! Adjustment criteria (Generalized Adjustment Criterion) for ADMGs.
 
 use super::Admg;
-use crate::graph::alg::bitset;
+use crate::edges::Mark;
+use crate::graph::alg::list_sep::{self, ListMinSep, ListSep};
+use crate::graph::alg::{bitset, csr};
 use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Lazy, polynomial-delay iterator over valid adjustment sets; see
+/// [`Admg::adjustment_sets`]. Each set is sorted.
+pub struct AdjustmentSets {
+    inner: AdjustmentSetsInner,
+}
+
+enum AdjustmentSetsInner {
+    All(ListSep<Admg>),
+    Minimal(ListMinSep),
+}
+
+impl Iterator for AdjustmentSets {
+    type Item = Vec<u32>;
+
+    fn next(&mut self) -> Option<Vec<u32>> {
+        match &mut self.inner {
+            AdjustmentSetsInner::All(it) => it.next(),
+            AdjustmentSetsInner::Minimal(it) => it.next(),
+        }
+    }
+}
 
 impl Admg {
     /// Descendants mask including seeds.
@@ -22,7 +47,7 @@ impl Admg {
     /// Adjusting for any node in this set can:
     /// 1. Block the causal effect (if on the path itself)
     /// 2. Open spurious paths via collider bias (if descendant of a node on the path)
-    fn forbidden_set(&self, xs: &[u32], ys: &[u32]) -> Vec<bool> {
+    pub(super) fn forbidden_set(&self, xs: &[u32], ys: &[u32]) -> Vec<bool> {
         let n = self.n() as usize;
 
         // Step 1: Compute De(X) ∪ X (nodes reachable from X, including X)
@@ -195,6 +220,69 @@ impl Admg {
         valid_sets
     }
 
+    /// Proper backdoor graph as an ADMG: every directed edge `x → v` with
+    /// `x ∈ xs`, `v ∉ xs` and `v ∈ An(ys) ∪ ys` is removed.
+    pub(super) fn proper_backdoor_admg(&self, xs: &[u32], ys: &[u32]) -> Result<Admg, String> {
+        let x = bitset::mask_from(xs, self.n());
+        let an_y = self.ancestors_mask(ys);
+        let core = csr::filter_edges(self.core_ref(), |u, k, c| {
+            let v = c.col_index[k];
+            let first = |a: u32, b: u32| x[a as usize] && !x[b as usize] && an_y[b as usize];
+            match c.marks(k) {
+                (Mark::Tail, Mark::Arrow) => !first(u, v),
+                (Mark::Arrow, Mark::Tail) => !first(v, u),
+                _ => true,
+            }
+        })?;
+        Admg::new(Arc::new(core))
+    }
+
+    /// Lazily enumerate the valid adjustment sets for `Xs → Ys` with
+    /// polynomial delay, so callers can stop early.
+    ///
+    /// Valid sets are the m-separators of `Xs` and `Ys` in the proper
+    /// backdoor graph that avoid `forb(Xs, Ys) ∪ Ys`, as in
+    /// [`is_valid_adjustment_set`](Self::is_valid_adjustment_set). With
+    /// `minimal = false` every valid set is listed (LISTSEP); with
+    /// `minimal = true` only the inclusion-minimal ones, as the minimal
+    /// separators of the augmented graph of `An(Xs ∪ Ys)` (van der Zander,
+    /// Liśkiewicz & Textor, 2019).
+    pub fn adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+    ) -> Result<AdjustmentSets, String> {
+        let n = self.n();
+        if let Some(v) = xs.iter().chain(ys).find(|&&v| v >= n) {
+            return Err(format!("Index {} is out of bounds (n = {})", v, n));
+        }
+        if xs.is_empty() || ys.is_empty() {
+            return Err("adjustment requires non-empty X and Y".into());
+        }
+        if xs.iter().any(|x| ys.contains(x)) {
+            return Err("X and Y must be disjoint".into());
+        }
+
+        let forbidden = self.forbidden_set(xs, ys);
+        let allowed: Vec<bool> = (0..n)
+            .map(|v| !forbidden[v as usize] && !ys.contains(&v))
+            .collect();
+        let pbd = self.proper_backdoor_admg(xs, ys)?;
+        let inner = if minimal {
+            let mut seeds = xs.to_vec();
+            seeds.extend_from_slice(ys);
+            let mask = pbd.ancestors_mask(&seeds);
+            let adj = list_sep::augmented_adj(&pbd, &mask);
+            let allowed: Vec<bool> = (0..n as usize).map(|v| allowed[v] && mask[v]).collect();
+            AdjustmentSetsInner::Minimal(ListMinSep::new(&adj, xs, ys, &allowed))
+        } else {
+            let restrict = bitset::collect_from_mask(&allowed);
+            AdjustmentSetsInner::All(ListSep::new(pbd, xs, ys, &[], &restrict))
+        };
+        Ok(AdjustmentSets { inner })
+    }
+
     /// Enumerate all k-subsets of `u`, collecting those satisfying `pred`.
     fn k_subsets<F>(
         u: &[u32],
@@ -246,6 +334,70 @@ impl Admg {
 #[cfg(test)]
 mod tests {
     use super::Admg;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use std::sync::Arc;
+
+    fn admg(n: u32, directed: &[(u32, u32)], bidirected: &[(u32, u32)]) -> Admg {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let b = reg.code_of("<->").unwrap();
+        let mut gb = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v) in directed {
+            gb.add_edge(u, v, d).unwrap();
+        }
+        for &(u, v) in bidirected {
+            gb.add_edge(u, v, b).unwrap();
+        }
+        Admg::new(Arc::new(gb.finalize().unwrap())).unwrap()
+    }
+
+    /// The lazy enumerators agree with brute-force subset enumeration.
+    #[test]
+    fn adjustment_sets_iterator_matches_subset_enumeration() {
+        let graphs = [
+            // Two backdoor paths 0 <- 2 -> 1, 0 <- 3 -> 4 -> 1, and a mediator 5.
+            admg(
+                6,
+                &[(2, 0), (2, 1), (3, 0), (3, 4), (4, 1), (0, 5), (5, 1)],
+                &[],
+            ),
+            // Confounded treatment: 0 <-> 2 -> 1, 3 -> 0, 3 <-> 1.
+            admg(4, &[(0, 1), (2, 1), (3, 0)], &[(0, 2), (3, 1)]),
+            // Chain of bidirected edges into the outcome.
+            admg(5, &[(0, 1), (2, 0), (3, 1)], &[(2, 4), (4, 3)]),
+        ];
+        for g in &graphs {
+            let all_n = g.n();
+            let mut expected = g.all_adjustment_sets(&[0], &[1], false, all_n);
+            expected.sort();
+            let mut got: Vec<_> = g.adjustment_sets(&[0], &[1], false).unwrap().collect();
+            got.sort();
+            assert_eq!(got, expected);
+
+            let mut expected = g.all_adjustment_sets(&[0], &[1], true, all_n);
+            expected.sort();
+            let mut got: Vec<_> = g.adjustment_sets(&[0], &[1], true).unwrap().collect();
+            got.sort();
+            assert_eq!(got, expected);
+        }
+    }
+
+    #[test]
+    fn adjustment_sets_iterator_stops_early_and_validates_input() {
+        let g = admg(6, &[(2, 0), (2, 1), (3, 0), (3, 4), (4, 1)], &[]);
+        let first: Vec<_> = g
+            .adjustment_sets(&[0], &[1], true)
+            .unwrap()
+            .take(1)
+            .collect();
+        assert_eq!(first.len(), 1);
+        assert!(g.is_valid_adjustment_set(&[0], &[1], &first[0]));
+        assert!(g.adjustment_sets(&[0], &[0], true).is_err());
+        assert!(g.adjustment_sets(&[0], &[9], false).is_err());
+        assert!(g.adjustment_sets(&[], &[1], false).is_err());
+    }
 
     #[test]
     fn prune_minimal_skips_supersets_of_existing_sets() {
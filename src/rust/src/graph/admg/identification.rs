@@ -0,0 +1,568 @@
+// SPDX-License-Identifier: MIT
+//! Causal effect identification for ADMGs (Shpitser & Pearl ID algorithm).
+//!
+//! `P(y | do(x))` is either expressed as a functional of the observational
+//! distribution over the ADMG's nodes, or shown to be non-identifiable by a
+//! hedge.
+//!
+//! # References
+//!
+//! I. Shpitser and J. Pearl (2006). Identification of joint interventional
+//! distributions in recursive semi-Markovian causal models. In *Proceedings of
+//! the Twenty-First National Conference on Artificial Intelligence (AAAI-06)*,
+//! pp. 1219–1226.
+
+use super::Admg;
+use crate::graph::alg::{bitset, topological_sort};
+use std::fmt;
+
+/// A functional of the observational distribution.
+///
+/// Variables are node indices of the ADMG the expression was derived from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// `P(vars | given)`; `given` may be empty.
+    Prob { vars: Vec<u32>, given: Vec<u32> },
+    /// `Σ_{over} body`.
+    Sum { over: Vec<u32>, body: Box<Expr> },
+    /// Product of two or more factors.
+    Product(Vec<Expr>),
+    /// `num / den`, from conditioning a term that is not a plain probability.
+    Ratio { num: Box<Expr>, den: Box<Expr> },
+}
+
+/// Witness of non-identifiability: two C-forests `F' ⊂ F` with `F ∩ X ≠ ∅`
+/// and `F' ∩ X = ∅`, both rooted in the outcome's ancestors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hedge {
+    /// Nodes of the larger C-forest `F`.
+    pub f: Vec<u32>,
+    /// Nodes of the smaller C-forest `F'`.
+    pub f_prime: Vec<u32>,
+}
+
+/// Outcome of [`Admg::identify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identification {
+    Identified(Expr),
+    NotIdentifiable(Hedge),
+}
+
+impl Expr {
+    /// `Σ_{over} body`, absorbing the sum into `body` where possible.
+    fn sum(over: Vec<u32>, body: Expr) -> Expr {
+        if over.is_empty() {
+            return body;
+        }
+        match body {
+            Expr::Prob { vars, given } if over.iter().all(|v| vars.contains(v)) => Expr::Prob {
+                vars: vars.into_iter().filter(|v| !over.contains(v)).collect(),
+                given,
+            },
+            Expr::Sum { over: inner, body } => {
+                let mut all = inner;
+                all.extend(over);
+                all.sort_unstable();
+                all.dedup();
+                Expr::Sum { over: all, body }
+            }
+            body => Expr::Sum {
+                over,
+                body: Box::new(body),
+            },
+        }
+    }
+
+    /// Product of `factors`, flattening nested products.
+    fn product(factors: Vec<Expr>) -> Expr {
+        let mut flat = Vec::with_capacity(factors.len());
+        for f in factors {
+            match f {
+                Expr::Product(inner) => flat.extend(inner),
+                f => flat.push(f),
+            }
+        }
+        if flat.len() == 1 {
+            flat.pop().expect("one factor")
+        } else {
+            Expr::Product(flat)
+        }
+    }
+
+    /// Render with `name` mapping node indices to labels.
+    pub fn render<F: Fn(u32) -> String>(&self, name: &F) -> String {
+        let list = |vs: &[u32]| vs.iter().map(|&v| name(v)).collect::<Vec<_>>().join(", ");
+        match self {
+            Expr::Prob { vars, given } if given.is_empty() => format!("P({})", list(vars)),
+            Expr::Prob { vars, given } => format!("P({} | {})", list(vars), list(given)),
+            Expr::Sum { over, body } => format!("sum_{{{}}} [{}]", list(over), body.render(name)),
+            Expr::Product(fs) => fs
+                .iter()
+                .map(|f| f.render(name))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Expr::Ratio { num, den } => format!("({}) / ({})", num.render(name), den.render(name)),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&|v| format!("v{}", v)))
+    }
+}
+
+/// Recursion state shared across calls: the graph and a fixed topological order.
+struct IdContext<'a> {
+    g: &'a Admg,
+    pos: Vec<usize>,
+}
+
+impl IdContext<'_> {
+    /// Nodes of `mask`, sorted by topological position.
+    fn ordered(&self, mask: &[bool]) -> Vec<u32> {
+        let mut vs = bitset::collect_from_mask(mask);
+        vs.sort_by_key(|&v| self.pos[v as usize]);
+        vs
+    }
+
+    /// Ancestors of `seeds` (inclusive) in `G[mask]`, not climbing above `cut` nodes.
+    fn ancestors(&self, seeds: &[u32], mask: &[bool], cut: &[bool]) -> Vec<bool> {
+        let mut an = vec![false; mask.len()];
+        let mut st: Vec<u32> = seeds.to_vec();
+        while let Some(v) = st.pop() {
+            if an[v as usize] || !mask[v as usize] {
+                continue;
+            }
+            an[v as usize] = true;
+            if !cut[v as usize] {
+                st.extend_from_slice(self.g.parents_of(v));
+            }
+        }
+        an
+    }
+
+    /// Districts (C-components) of `G[mask]`, each as a mask.
+    fn districts(&self, mask: &[bool]) -> Vec<Vec<bool>> {
+        let mut seen = vec![false; mask.len()];
+        let mut out = Vec::new();
+        for s in bitset::collect_from_mask(mask) {
+            if seen[s as usize] {
+                continue;
+            }
+            let mut d = vec![false; mask.len()];
+            let mut st = vec![s];
+            seen[s as usize] = true;
+            while let Some(v) = st.pop() {
+                d[v as usize] = true;
+                for &w in self.g.spouses_of(v) {
+                    if mask[w as usize] && !seen[w as usize] {
+                        seen[w as usize] = true;
+                        st.push(w);
+                    }
+                }
+            }
+            out.push(d);
+        }
+        out
+    }
+
+    /// `Σ_{V \ keep} p` for a distribution `p` over `v`.
+    fn marginal(&self, p: &Expr, v: &[bool], keep: &[bool]) -> Expr {
+        let over = bitset::collect_from_mask(&minus(v, keep));
+        Expr::sum(over, p.clone())
+    }
+
+    /// `p(x | pre)` for a distribution `p` over `v`.
+    fn conditional(&self, p: &Expr, v: &[bool], x: u32, pre: &[u32]) -> Expr {
+        if let Expr::Prob { given, .. } = p {
+            let mut given = given.clone();
+            given.extend_from_slice(pre);
+            given.sort_unstable();
+            return Expr::Prob {
+                vars: vec![x],
+                given,
+            };
+        }
+        let mut keep = bitset::mask_from(pre, self.g.n());
+        let den = self.marginal(p, v, &keep);
+        keep[x as usize] = true;
+        let num = self.marginal(p, v, &keep);
+        if pre.is_empty() {
+            num
+        } else {
+            Expr::Ratio {
+                num: Box::new(num),
+                den: Box::new(den),
+            }
+        }
+    }
+
+    /// `Π_{vi ∈ s} p(vi | v_π^(i-1))` over the topological order of `v`.
+    fn factorize(&self, p: &Expr, v: &[bool], s: &[bool]) -> Expr {
+        let order = self.ordered(v);
+        let factors = order
+            .iter()
+            .enumerate()
+            .filter(|(_, &vi)| s[vi as usize])
+            .map(|(i, &vi)| self.conditional(p, v, vi, &order[..i]))
+            .collect();
+        Expr::product(factors)
+    }
+
+    /// ID(y, x, P, G[v]); `y`, `x` are masks over the full node set.
+    fn id(&self, y: &[bool], x: &[bool], p: &Expr, v: &[bool]) -> Result<Expr, Hedge> {
+        let n = v.len();
+        let ys = bitset::collect_from_mask(y);
+        let none = vec![false; n];
+
+        // Line 1: no intervention.
+        if !x.iter().any(|&b| b) {
+            return Ok(self.marginal(p, v, y));
+        }
+
+        // Line 2: restrict to ancestors of y.
+        let an_y = self.ancestors(&ys, v, &none);
+        if an_y != v {
+            let p2 = self.marginal(p, v, &an_y);
+            return self.id(y, &and(x, &an_y), &p2, &an_y);
+        }
+
+        // Line 3: intervene on nodes without effect on y once x is fixed.
+        let an_y_cut = self.ancestors(&ys, v, x);
+        let w = minus(&minus(v, x), &an_y_cut);
+        if w.iter().any(|&b| b) {
+            return self.id(y, &or(x, &w), p, v);
+        }
+
+        // Line 4: factorize over the districts of G[V \ X].
+        let v_minus_x = minus(v, x);
+        let cs = self.districts(&v_minus_x);
+        if cs.len() > 1 {
+            let factors = cs
+                .iter()
+                .map(|s| self.id(s, &minus(v, s), p, v))
+                .collect::<Result<Vec<_>, _>>()?;
+            let over = bitset::collect_from_mask(&minus(&v_minus_x, y));
+            return Ok(Expr::sum(over, Expr::product(factors)));
+        }
+        let s = &cs[0];
+
+        let cg = self.districts(v);
+        // Line 5: G is a single district, so (G, G ∩ S) is a hedge.
+        if cg.len() == 1 {
+            return Err(Hedge {
+                f: bitset::collect_from_mask(v),
+                f_prime: bitset::collect_from_mask(s),
+            });
+        }
+
+        // Line 6: S is itself a district of G.
+        if cg.iter().any(|c| c == s) {
+            let over = bitset::collect_from_mask(&minus(s, y));
+            return Ok(Expr::sum(over, self.factorize(p, v, s)));
+        }
+
+        // Line 7: recurse into the district S' ⊃ S.
+        let sp = cg
+            .into_iter()
+            .find(|c| s.iter().zip(c).all(|(&a, &b)| !a || b))
+            .expect("every district of G[V \\ X] lies in a district of G");
+        let p2 = self.factorize(p, v, &sp);
+        self.id(y, &and(x, &sp), &p2, &sp)
+    }
+}
+
+fn and(a: &[bool], b: &[bool]) -> Vec<bool> {
+    a.iter().zip(b).map(|(&x, &y)| x && y).collect()
+}
+
+fn or(a: &[bool], b: &[bool]) -> Vec<bool> {
+    a.iter().zip(b).map(|(&x, &y)| x || y).collect()
+}
+
+fn minus(a: &[bool], b: &[bool]) -> Vec<bool> {
+    a.iter().zip(b).map(|(&x, &y)| x && !y).collect()
+}
+
+impl Admg {
+    /// Identify `P(ys | do(xs))` with the ID algorithm of Shpitser & Pearl (2006).
+    ///
+    /// Returns [`Identification::Identified`] with an expression over the
+    /// observational distribution of all nodes, or
+    /// [`Identification::NotIdentifiable`] with a hedge witnessing that no such
+    /// expression exists. The algorithm is sound and complete.
+    ///
+    /// # Errors
+    /// Returns an error if an index is out of bounds, `ys` is empty, or `xs`
+    /// and `ys` overlap.
+    pub fn identify(&self, xs: &[u32], ys: &[u32]) -> Result<Identification, String> {
+        let n = self.n();
+        for &v in xs.iter().chain(ys) {
+            if v >= n {
+                return Err(format!("Index {} is out of bounds (n = {})", v, n));
+            }
+        }
+        if ys.is_empty() {
+            return Err("identify requires at least one outcome".into());
+        }
+        let x = bitset::mask_from(xs, n);
+        let y = bitset::mask_from(ys, n);
+        if let Some(v) = ys.iter().find(|&&v| x[v as usize]) {
+            return Err(format!("Node {} is both a treatment and an outcome", v));
+        }
+
+        let order = topological_sort(self.core_ref());
+        let mut pos = vec![0usize; n as usize];
+        for (i, &v) in order.iter().enumerate() {
+            pos[v as usize] = i;
+        }
+        let ctx = IdContext { g: self, pos };
+
+        let all = vec![true; n as usize];
+        let p = Expr::Prob {
+            vars: ctx.ordered(&all),
+            given: Vec::new(),
+        };
+        Ok(match ctx.id(&y, &x, &p, &all) {
+            Ok(e) => Identification::Identified(e),
+            Err(h) => Identification::NotIdentifiable(h),
+        })
+    }
+
+    /// Identify the conditional effect `P(ys | do(xs), zs)` with the IDC
+    /// algorithm of Shpitser & Pearl (2006).
+    ///
+    /// Each `z ∈ zs` that rule 2 of the do-calculus lets us treat as an action
+    /// is moved into the intervention set; the remaining query is answered as
+    /// `P'(ys, zs | do(xs)) / Σ_{ys} P'(ys, zs | do(xs))` via [`identify`](Self::identify).
+    /// A hedge for the joint effect is returned when it is not identifiable.
+    ///
+    /// # Errors
+    /// Returns an error if an index is out of bounds, `ys` is empty, or the
+    /// three sets overlap.
+    ///
+    /// # References
+    ///
+    /// I. Shpitser and J. Pearl (2006). Identification of conditional
+    /// interventional distributions. In *Proceedings of the Twenty-Second
+    /// Conference on Uncertainty in Artificial Intelligence (UAI-06)*,
+    /// pp. 437–444.
+    pub fn identify_conditional(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+    ) -> Result<Identification, String> {
+        let n = self.n();
+        let mut seen = vec![false; n as usize];
+        for &v in xs.iter().chain(ys).chain(zs) {
+            if v >= n {
+                return Err(format!("Index {} is out of bounds (n = {})", v, n));
+            }
+            if seen[v as usize] {
+                return Err(format!("Node {} appears in more than one of X, Y, Z", v));
+            }
+            seen[v as usize] = true;
+        }
+        if ys.is_empty() {
+            return Err("identify_conditional requires at least one outcome".into());
+        }
+
+        let mut x = xs.to_vec();
+        let mut z = zs.to_vec();
+        'search: loop {
+            for (i, &w) in z.iter().enumerate() {
+                let rest: Vec<u32> = z.iter().copied().filter(|&v| v != w).collect();
+                if self.do_calculus_rules(&x, ys, &[w], &rest)?.rule2 {
+                    x.push(w);
+                    z.remove(i);
+                    continue 'search;
+                }
+            }
+            break;
+        }
+        x.sort_unstable();
+        z.sort_unstable();
+
+        if z.is_empty() {
+            return self.identify(&x, ys);
+        }
+        let mut yz = ys.to_vec();
+        yz.extend_from_slice(&z);
+        Ok(match self.identify(&x, &yz)? {
+            Identification::Identified(p) => {
+                let mut over = ys.to_vec();
+                over.sort_unstable();
+                let den = Expr::sum(over, p.clone());
+                Identification::Identified(Expr::Ratio {
+                    num: Box::new(p),
+                    den: Box::new(den),
+                })
+            }
+            hedge => hedge,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use std::sync::Arc;
+
+    fn build(n: u32, edges: &[(u32, u32, &str)]) -> Admg {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, false, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Admg::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    fn prob(vars: &[u32], given: &[u32]) -> Expr {
+        Expr::Prob {
+            vars: vars.to_vec(),
+            given: given.to_vec(),
+        }
+    }
+
+    #[test]
+    fn identify_backdoor_gives_adjustment_formula() {
+        // 0:Z --> 1:X --> 2:Y, Z --> Y
+        let g = build(3, &[(0, 1, "-->"), (1, 2, "-->"), (0, 2, "-->")]);
+        let Identification::Identified(e) = g.identify(&[1], &[2]).unwrap() else {
+            panic!("backdoor effect is identifiable");
+        };
+        assert_eq!(
+            e,
+            Expr::Sum {
+                over: vec![0],
+                body: Box::new(Expr::Product(vec![prob(&[0], &[]), prob(&[2], &[0, 1])])),
+            }
+        );
+        assert_eq!(e.to_string(), "sum_{v0} [P(v0) P(v2 | v0, v1)]");
+    }
+
+    #[test]
+    fn identify_front_door() {
+        // 0:X --> 1:M --> 2:Y, X <-> Y
+        let g = build(3, &[(0, 1, "-->"), (1, 2, "-->"), (0, 2, "<->")]);
+        let Identification::Identified(e) = g.identify(&[0], &[2]).unwrap() else {
+            panic!("front-door effect is identifiable");
+        };
+        let names = ["X", "M", "Y"];
+        assert_eq!(
+            e.render(&|v| names[v as usize].to_string()),
+            "sum_{M} [P(M | X) sum_{X} [P(X) P(Y | X, M)]]"
+        );
+    }
+
+    #[test]
+    fn identify_bow_arc_returns_hedge() {
+        // 0:X --> 1:Y, X <-> Y
+        let g = build(2, &[(0, 1, "-->"), (0, 1, "<->")]);
+        assert_eq!(
+            g.identify(&[0], &[1]).unwrap(),
+            Identification::NotIdentifiable(Hedge {
+                f: vec![0, 1],
+                f_prime: vec![1],
+            })
+        );
+    }
+
+    #[test]
+    fn identify_confounded_mediator_is_not_identifiable() {
+        // 0:X --> 1:M --> 2:Y, X <-> M, M <-> Y
+        let g = build(
+            3,
+            &[(0, 1, "-->"), (1, 2, "-->"), (0, 1, "<->"), (1, 2, "<->")],
+        );
+        let Identification::NotIdentifiable(h) = g.identify(&[0], &[2]).unwrap() else {
+            panic!("expected a hedge");
+        };
+        assert!(h.f.contains(&0));
+        assert!(!h.f_prime.contains(&0));
+        assert!(h.f_prime.iter().all(|v| h.f.contains(v)));
+    }
+
+    #[test]
+    fn identify_napkin() {
+        // 0:W --> 1:Z --> 2:X --> 3:Y, W <-> X, W <-> Y
+        let g = build(
+            4,
+            &[
+                (0, 1, "-->"),
+                (1, 2, "-->"),
+                (2, 3, "-->"),
+                (0, 2, "<->"),
+                (0, 3, "<->"),
+            ],
+        );
+        let Identification::Identified(e) = g.identify(&[2], &[3]).unwrap() else {
+            panic!("napkin effect is identifiable");
+        };
+        // sum_w P(x, y | z, w) P(w) / sum_w P(x | z, w) P(w)
+        let names = ["W", "Z", "X", "Y"];
+        assert_eq!(
+            e.render(&|v| names[v as usize].to_string()),
+            "(sum_{W} [P(W) P(X | W, Z) P(Y | W, Z, X)]) / \
+             (sum_{W, Y} [P(W) P(X | W, Z) P(Y | W, Z, X)])"
+        );
+    }
+
+    #[test]
+    fn identify_without_treatment_is_marginal() {
+        let g = build(3, &[(0, 1, "-->"), (1, 2, "-->")]);
+        assert_eq!(
+            g.identify(&[], &[2]).unwrap(),
+            Identification::Identified(prob(&[2], &[]))
+        );
+    }
+
+    #[test]
+    fn identify_rejects_bad_input() {
+        let g = build(2, &[(0, 1, "-->")]);
+        assert!(g.identify(&[0], &[]).is_err());
+        assert!(g.identify(&[0], &[0]).is_err());
+        assert!(g.identify(&[2], &[1]).is_err());
+    }
+
+    #[test]
+    fn identify_conditional_moves_observations_into_actions() {
+        // 0:X --> 1:Z --> 2:Y, X <-> Y: in P(y | do(x), z), z acts like do(z)
+        let g = build(3, &[(0, 1, "-->"), (1, 2, "-->"), (0, 2, "<->")]);
+        let cond = g.identify_conditional(&[0], &[2], &[1]).unwrap();
+        assert_eq!(cond, g.identify(&[0, 1], &[2]).unwrap());
+        assert!(matches!(cond, Identification::Identified(_)));
+    }
+
+    #[test]
+    fn identify_conditional_ratio_of_joint_effect() {
+        // 0:X --> 1:Y --> 2:Z: P(y | do(x), z) = P(y, z | do(x)) / P(z | do(x))
+        let g = build(3, &[(0, 1, "-->"), (1, 2, "-->")]);
+        let Identification::Identified(e) = g.identify_conditional(&[0], &[1], &[2]).unwrap()
+        else {
+            panic!("identifiable in a DAG");
+        };
+        assert!(matches!(e, Expr::Ratio { .. }));
+        assert_eq!(
+            e.to_string(),
+            "(P(v1 | v0) P(v2 | v0, v1)) / (sum_{v1} [P(v1 | v0) P(v2 | v0, v1)])"
+        );
+    }
+
+    #[test]
+    fn identify_conditional_reports_hedge_and_bad_input() {
+        // 0:X --> 1:Y, X <-> Y, 1 --> 2:Z
+        let g = build(3, &[(0, 1, "-->"), (0, 1, "<->"), (1, 2, "-->")]);
+        assert!(matches!(
+            g.identify_conditional(&[0], &[1], &[2]).unwrap(),
+            Identification::NotIdentifiable(_)
+        ));
+        assert!(g.identify_conditional(&[0], &[1], &[1]).is_err());
+        assert!(g.identify_conditional(&[0], &[], &[2]).is_err());
+    }
+}
@@ -9,7 +9,14 @@
 
 mod adjustment;
 mod districts;
+mod front_door;
+mod identification;
+mod instruments;
 mod msep;
+mod transforms;
+
+pub use adjustment::AdjustmentSets;
+pub use identification::{Expr, Hedge, Identification};
 
 use super::error::AdmgError;
 use super::packed::{PackedBuckets, PackedBucketsBuilder};
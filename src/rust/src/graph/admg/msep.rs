@@ -3,8 +3,12 @@
 
 use super::Admg;
 use crate::graph::alg::bitset;
+use crate::graph::alg::do_calculus::{self, DoCalculusRules};
+use crate::graph::alg::implied::{self, ConditionalIndependence};
 use crate::graph::alg::min_msep::{self, MixedGraph};
+use crate::graph::alg::open_paths::{self, OpenPath};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 impl MixedGraph for Admg {
     fn n(&self) -> u32 {
@@ -72,10 +76,11 @@ impl Admg {
     /// Build a moral adjacency for m-separation in an ADMG.
     ///
     /// This extends the standard moralization to handle bidirected edges:
-    /// 1. Connect parents with children (undirected)
-    /// 2. Marry every pair of arrowhead endpoints at a common node
-    ///    (i.e. every pair in `pa(v) ∪ sp(v)`)
-    /// 3. Add bidirected edges as undirected edges
+    /// within the masked subgraph, every district `D` (a component of the
+    /// bidirected edges) is joined into a clique with its parents `pa(D)`.
+    /// Two nodes are then adjacent iff some path between them has only
+    /// colliders as interior nodes (Richardson, 2003), which also covers
+    /// parent-child edges, married parents and bidirected edges.
     fn moral_adj_admg(&self, mask: &[bool]) -> Vec<Vec<u32>> {
         self.moral_adj_admg_filtered(mask, &HashSet::new())
     }
@@ -90,53 +95,44 @@ impl Admg {
     ) -> Vec<Vec<u32>> {
         let n = self.n() as usize;
         let mut adj = vec![Vec::<u32>::new(); n];
+        let mut seen = vec![false; n];
 
-        for v in 0..n as u32 {
-            if !mask[v as usize] {
+        for root in 0..n as u32 {
+            if !mask[root as usize] || seen[root as usize] {
                 continue;
             }
-
-            // Effective directed parents (after deleting edges in `removed_directed`).
-            let pa: Vec<u32> = self
-                .parents_of(v)
-                .iter()
-                .copied()
-                .filter(|&u| !removed_directed.contains(&(u, v)))
-                .collect();
-
-            // Connect with parents (as in standard moralization)
-            for &p in &pa {
-                if mask[p as usize] {
-                    adj[v as usize].push(p);
-                    adj[p as usize].push(v);
+            // District of `root` within the mask.
+            seen[root as usize] = true;
+            let mut district = vec![root];
+            let mut k = 0;
+            while k < district.len() {
+                let v = district[k];
+                k += 1;
+                for &s in self.spouses_of(v) {
+                    if mask[s as usize] && !seen[s as usize] {
+                        seen[s as usize] = true;
+                        district.push(s);
+                    }
                 }
             }
 
-            // Add bidirected edges as undirected (spouses connect in moral graph)
-            for &s in self.spouses_of(v) {
-                if mask[s as usize] {
-                    adj[v as usize].push(s);
-                    // Note: the reverse will be added when we process node s
-                }
+            // Join the district and its effective directed parents (after
+            // deleting edges in `removed_directed`).
+            let mut clique = district.clone();
+            for &v in &district {
+                clique.extend(
+                    self.parents_of(v)
+                        .iter()
+                        .copied()
+                        .filter(|&u| mask[u as usize] && !removed_directed.contains(&(u, v))),
+                );
             }
-
-            // Marry every pair of arrowhead endpoints at v: pa(v) ∪ sp(v).
-            // Both directed parents and spouses contribute an arrowhead at v,
-            // so the full moralization rule must form a clique on this union.
-            let mut heads: Vec<u32> = pa
-                .iter()
-                .copied()
-                .chain(self.spouses_of(v).iter().copied())
-                .filter(|u| mask[*u as usize])
-                .collect();
-            heads.sort_unstable();
-            heads.dedup();
-            for i in 0..heads.len() {
-                for j in i + 1..heads.len() {
-                    let hi = heads[i] as usize;
-                    let hj = heads[j] as usize;
-                    adj[hi].push(heads[j]);
-                    adj[hj].push(heads[i]);
+            clique.sort_unstable();
+            clique.dedup();
+            for (i, &a) in clique.iter().enumerate() {
+                for &b in &clique[i + 1..] {
+                    adj[a as usize].push(b);
+                    adj[b as usize].push(a);
                 }
             }
         }
@@ -232,6 +228,27 @@ impl Admg {
         !Self::reachable_in_moral(&adj, &mask, xs, &blocked, ys)
     }
 
+    /// A shortest m-connecting path between `xs` and `ys` given `z`, or
+    /// `None` if they are m-separated.
+    pub fn m_connecting_path(&self, xs: &[u32], ys: &[u32], z: &[u32]) -> Option<OpenPath> {
+        if self.m_separated(xs, ys, z) {
+            return None;
+        }
+        open_paths::shortest_open_path(self, xs, ys, z)
+    }
+
+    /// Every m-connecting path between `xs` and `ys` given `z` with at most
+    /// `max_len` edges, shortest first.
+    pub fn m_connecting_paths(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+        max_len: usize,
+    ) -> Vec<OpenPath> {
+        open_paths::open_paths(self, xs, ys, z, max_len)
+    }
+
     /// M-separation in the proper backdoor graph for `(xs, ys)`.
     ///
     /// The proper backdoor graph is `G` with the first edge of every proper
@@ -291,6 +308,35 @@ impl Admg {
     ) -> Result<Option<Vec<u32>>, String> {
         min_msep::find_min_msep(self, xs, ys, include, restrict)
     }
+
+    /// One independence `u ⊥ v | Z` per separable non-adjacent pair `u < v`,
+    /// with `Z` a minimal m-separator, ordered by `(u, v)`.
+    pub fn implied_independencies(&self) -> Vec<ConditionalIndependence> {
+        implied::pairwise_basis(self)
+    }
+
+    /// Check which of Pearl's do-calculus rules apply to the query
+    /// `(xs, ys, zs, ws)`, by m-separation in the mutilated ADMGs.
+    ///
+    /// See [`do_calculus`](crate::graph::alg::do_calculus) for the rules.
+    /// The four sets must be disjoint, and `ys` and `zs` non-empty.
+    pub fn do_calculus_rules(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+        ws: &[u32],
+    ) -> Result<DoCalculusRules, String> {
+        do_calculus::check_rules(
+            self.core_ref(),
+            |v| self.parents_of(v),
+            xs,
+            ys,
+            zs,
+            ws,
+            |g, ys, zs, cond| Ok(Admg::new(Arc::new(g))?.m_separated(ys, zs, cond)),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -298,7 +344,6 @@ mod tests {
     use super::*;
     use crate::edges::EdgeRegistry;
     use crate::graph::builder::GraphBuilder;
-    use std::sync::Arc;
 
     fn two_parents_one_child() -> Admg {
         let mut reg = EdgeRegistry::new();
@@ -426,4 +471,59 @@ mod tests {
         assert_eq!(adj2[2], vec![1]);
         assert!(adj2[1].contains(&2));
     }
+
+    #[test]
+    fn do_calculus_rules_front_door_admg() {
+        // 0:X --> 1:M --> 2:Y, X <-> Y
+        let g = build_admg(3, &[(0, 1), (1, 2)], &[(0, 2)]);
+        // P(m | do(x)) = P(m | x): rule 2
+        assert!(g.do_calculus_rules(&[], &[1], &[0], &[]).unwrap().rule2);
+        // P(y | do(m)) = P(y | m) fails: backdoor M <- X <-> Y is open
+        assert!(!g.do_calculus_rules(&[], &[2], &[1], &[]).unwrap().rule2);
+        // P(y | do(m), x) = P(y | m, x): rule 2 once X is observed
+        assert!(g.do_calculus_rules(&[], &[2], &[1], &[0]).unwrap().rule2);
+        // P(x | do(m)) = P(x): rule 3
+        assert!(g.do_calculus_rules(&[], &[0], &[1], &[]).unwrap().rule3);
+        // P(y | do(x), do(m)) = P(y | do(m)): rule 3 with X = {m}, Z = {x}
+        assert!(g.do_calculus_rules(&[1], &[2], &[0], &[]).unwrap().rule3);
+    }
+
+    #[test]
+    fn implied_independencies_one_minimal_separator_per_pair() {
+        // 0 -> 1 -> 2 <-> 3
+        let g = build_admg(4, &[(0, 1), (1, 2)], &[(2, 3)]);
+        let ci: Vec<(u32, u32, Vec<u32>)> = g
+            .implied_independencies()
+            .into_iter()
+            .map(|c| (c.x[0], c.y[0], c.z))
+            .collect();
+        assert_eq!(ci, vec![(0, 2, vec![1]), (0, 3, vec![]), (1, 3, vec![])]);
+
+        // 0 -> 1 <-> 2 with 1 -> 3 -> 2: an inducing path leaves (0, 2)
+        // inseparable, so only (0, 3) is listed.
+        let g = build_admg(4, &[(0, 1), (1, 3), (3, 2)], &[(1, 2)]);
+        let ci = g.implied_independencies();
+        assert_eq!(ci.len(), 1);
+        assert_eq!(
+            (ci[0].x.clone(), ci[0].y.clone(), ci[0].z.clone()),
+            (vec![0], vec![3], vec![1])
+        );
+    }
+
+    #[test]
+    fn m_separation_through_a_chain_of_colliders() {
+        use crate::graph::alg::open_paths::PathEdge::{Backward, Bidirected, Forward};
+
+        // 0 -> 2 <-> 3 <- 1: conditioning on both colliders opens the path.
+        let g = build_admg(4, &[(0, 2), (1, 3)], &[(2, 3)]);
+        assert!(g.m_separated(&[0], &[1], &[2]));
+        assert!(!g.m_separated(&[0], &[1], &[2, 3]));
+
+        let p = g.m_connecting_path(&[0], &[1], &[2, 3]).unwrap();
+        assert_eq!(p.nodes, vec![0, 2, 3, 1]);
+        assert_eq!(p.edges, vec![Forward, Bidirected, Backward]);
+        assert_eq!(p.opened_colliders, vec![(2, 2), (3, 3)]);
+        assert_eq!(g.m_connecting_path(&[0], &[1], &[2]), None);
+        assert_eq!(g.m_connecting_paths(&[0], &[1], &[2, 3], 3), vec![p]);
+    }
 }
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT
+//! Graph transformations for ADMGs.
+
+use super::Admg;
+use crate::edges::Mark;
+use crate::graph::ag::Ag;
+use crate::graph::alg::inducing;
+
+impl Admg {
+    /// Whether `a` and `b` share an edge of any kind.
+    fn adjacent(&self, a: u32, b: u32) -> bool {
+        self.parents_of(a).binary_search(&b).is_ok()
+            || self.children_of(a).binary_search(&b).is_ok()
+            || self.spouses_of(a).binary_search(&b).is_ok()
+    }
+
+    /// Convert an ADMG to the MAG encoding the same m-separation model.
+    ///
+    /// Two nodes are adjacent in the MAG iff they are adjacent in the ADMG or
+    /// joined by an inducing path. Each adjacency becomes `a --> b` when `a` is
+    /// an ancestor of `b` and `a <-> b` when neither is an ancestor of the
+    /// other, so parallel `-->`/`<->` pairs collapse to the directed edge and
+    /// bidirected edges implied by ancestral relations become directed.
+    ///
+    /// # References
+    ///
+    /// T. Richardson and P. Spirtes (2002). Ancestral graph Markov models.
+    /// *The Annals of Statistics*, 30(4), 962–1030.
+    pub fn to_mag(&self) -> Result<Ag, String> {
+        let n = self.n();
+        let an: Vec<Vec<bool>> = (0..n).map(|v| self.ancestors_mask(&[v])).collect();
+
+        let mut edges = Vec::new();
+        for u in 0..n {
+            for v in (u + 1)..n {
+                if !self.adjacent(u, v) && !inducing::has_inducing_path(self, u, v) {
+                    continue;
+                }
+                let (at_u, at_v) = if an[v as usize][u as usize] {
+                    (Mark::Tail, Mark::Arrow)
+                } else if an[u as usize][v as usize] {
+                    (Mark::Arrow, Mark::Tail)
+                } else {
+                    (Mark::Arrow, Mark::Arrow)
+                };
+                edges.push((u, v, at_u, at_v));
+            }
+        }
+
+        Ag::from_marked_edges(&self.core_ref().registry, n, edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use std::sync::Arc;
+
+    fn build(n: u32, edges: &[(u32, u32, &str)], simple: bool) -> Admg {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, simple, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Admg::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn to_mag_collapses_parallel_edges_to_directed() {
+        // 0 --> 1 and 0 <-> 1
+        let mag = build(2, &[(0, 1, "-->"), (0, 1, "<->")], false)
+            .to_mag()
+            .unwrap();
+        assert_eq!(mag.children_of(0), &[1]);
+        assert!(mag.spouses_of(0).is_empty());
+    }
+
+    #[test]
+    fn to_mag_orients_bidirected_edge_between_ancestor_pair() {
+        // 0 --> 1 --> 2, 0 <-> 2: 0 is an ancestor of 2
+        let mag = build(3, &[(0, 1, "-->"), (1, 2, "-->"), (0, 2, "<->")], true)
+            .to_mag()
+            .unwrap();
+        assert_eq!(mag.parents_of(2), &[0, 1]);
+        assert!(mag.spouses_of(2).is_empty());
+    }
+
+    #[test]
+    fn to_mag_adds_edge_for_inducing_path() {
+        // 0 <-> 1 <-> 2 with 1 --> 2: 1 is a collider and an ancestor of 2
+        let mag = build(3, &[(0, 1, "<->"), (1, 2, "<->"), (1, 2, "-->")], false)
+            .to_mag()
+            .unwrap();
+        assert_eq!(mag.spouses_of(0), &[1, 2]);
+        assert_eq!(mag.children_of(1), &[2]);
+        assert!(mag.is_mag());
+    }
+
+    #[test]
+    fn to_mag_keeps_non_inducing_collider_open() {
+        // 0 --> 1 <-- 2: not adjacent in the MAG
+        let mag = build(3, &[(0, 1, "-->"), (2, 1, "-->")], true)
+            .to_mag()
+            .unwrap();
+        assert!(!mag.adjacent(0, 2));
+        assert_eq!(mag.parents_of(1), &[0, 2]);
+    }
+
+    #[test]
+    fn to_mag_matches_dag_to_mag_after_latent_projection() {
+        // DAG 0 --> 1 <-- 2:L --> 3, 1 --> 3; projecting L gives 1 <-> 3 and 1 --> 3
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+        for (u, v) in [(0, 1), (2, 1), (2, 3), (1, 3)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let dag = crate::graph::dag::Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+
+        let via_admg = dag.latent_project(&[2]).unwrap().to_mag().unwrap();
+        let direct = dag.to_mag(&[2], &[]).unwrap();
+        for v in 0..3 {
+            assert_eq!(via_admg.parents_of(v), direct.parents_of(v));
+            assert_eq!(via_admg.spouses_of(v), direct.spouses_of(v));
+            assert_eq!(via_admg.children_of(v), direct.children_of(v));
+        }
+        assert_eq!(direct.children_of(0), &[1, 2]);
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT
+//! Generalized adjustment criterion for maximal ancestral graphs.
+
+use super::Ag;
+use crate::edges::Mark;
+use crate::graph::alg::gac::{self, Amenability, GacSets, MarkedGraph, Semantics};
+use crate::graph::alg::{bitset, csr};
+use std::sync::Arc;
+
+impl Ag {
+    /// Amenability, forbidden and causal sets of `(xs, ys)`; errors unless
+    /// this graph is a MAG.
+    fn gac_sets(&self, xs: &[u32], ys: &[u32]) -> Result<Amenability<GacSets>, String> {
+        if !self.is_mag() {
+            return Err("adjustment criterion requires a maximal ancestral graph".into());
+        }
+        gac::analyse(
+            &MarkedGraph::new(self.core_ref(), Semantics::Ancestral),
+            xs,
+            ys,
+        )
+    }
+
+    /// Proper backdoor graph: drop every directed edge `x --> w` out of `Xs`
+    /// that starts a proper causal path to `Ys`.
+    pub(crate) fn proper_backdoor_mag(&self, xs: &[u32], ys: &[u32]) -> Result<Ag, String> {
+        let x = bitset::mask_from(xs, self.n());
+        let mut to_y = vec![false; self.n() as usize];
+        let mut st = ys.to_vec();
+        while let Some(v) = st.pop() {
+            if to_y[v as usize] || x[v as usize] {
+                continue;
+            }
+            to_y[v as usize] = true;
+            st.extend_from_slice(self.parents_of(v));
+        }
+        let core = csr::filter_edges(self.core_ref(), |u, k, c| {
+            let v = c.col_index[k];
+            let first = |a: u32, b: u32, at_a: Mark, at_b: Mark| {
+                x[a as usize]
+                    && !x[b as usize]
+                    && to_y[b as usize]
+                    && at_a == Mark::Tail
+                    && at_b == Mark::Arrow
+            };
+            let (mu, mv) = c.marks(k);
+            !(first(u, v, mu, mv) || first(v, u, mv, mu))
+        })?;
+        Ag::new(Arc::new(core))
+    }
+
+    /// Generalized adjustment criterion: whether `z` is a valid adjustment
+    /// set for `Xs → Ys` in this MAG (Perković et al., 2018).
+    pub fn is_valid_adjustment_set(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Amenability<bool>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let pbd = self.proper_backdoor_mag(xs, ys)?;
+            gac::is_valid(&sets, ys, z, |z| pbd.m_separated(xs, ys, z))
+        })
+    }
+
+    /// Enumerate valid adjustment sets for `Xs → Ys` up to size `max_size`.
+    /// If `minimal` is true, return only inclusion-minimal sets.
+    pub fn all_adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+        max_size: u32,
+    ) -> Result<Amenability<Vec<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let pbd = self.proper_backdoor_mag(xs, ys)?;
+            Ok(gac::all_sets(&sets, ys, minimal, max_size, |z| {
+                pbd.m_separated(xs, ys, z)
+            }))
+        })
+    }
+
+    /// The canonical adjustment set `PossAn(X ∪ Y) \ (X ∪ Y ∪ Forb)`, or
+    /// `None` if no valid adjustment set exists.
+    pub fn adjustment_set_canonical(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Amenability<Option<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let g = MarkedGraph::new(self.core_ref(), Semantics::Ancestral);
+            let z = gac::canonical(&g, &sets, xs, ys);
+            let pbd = self.proper_backdoor_mag(xs, ys)?;
+            Ok(pbd.m_separated(xs, ys, &z).then_some(z))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+
+    fn ag(n: u32, edges: &[(u32, u32, &str)]) -> Ag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Ag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn mag_adjustment_with_visible_treatment_edge() {
+        // 3 --> 0 --> 1 --> 2, 0 --> 2, 4 <-> 0, 4 --> 2.
+        // 0 --> 1 and 0 --> 2 are visible through 3, which is not adjacent to 1 or 2.
+        let g = ag(
+            5,
+            &[
+                (3, 0, "-->"),
+                (0, 1, "-->"),
+                (1, 2, "-->"),
+                (0, 2, "-->"),
+                (4, 0, "<->"),
+                (4, 2, "-->"),
+            ],
+        );
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[2], &[4]).unwrap(),
+            Amenability::Amenable(true)
+        );
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[2], &[]).unwrap(),
+            Amenability::Amenable(false)
+        );
+        // The mediator is forbidden.
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[2], &[1, 4]).unwrap(),
+            Amenability::Amenable(false)
+        );
+        assert_eq!(
+            g.all_adjustment_sets(&[0], &[2], true, 3).unwrap(),
+            Amenability::Amenable(vec![vec![4]])
+        );
+        assert_eq!(
+            g.adjustment_set_canonical(&[0], &[2]).unwrap(),
+            Amenability::Amenable(Some(vec![3, 4]))
+        );
+    }
+
+    #[test]
+    fn mag_invisible_edge_is_not_amenable() {
+        let g = ag(2, &[(0, 1, "-->")]);
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[1], &[]).unwrap(),
+            Amenability::NotAmenable { x: 0, first: 1 }
+        );
+    }
+}
@@ -11,7 +11,9 @@
 //! 2. If there is an arrowhead at v from u, then v is not an anterior of u
 //! 3. If v is an endpoint of an undirected edge, v has no edge with arrowhead at v
 
+mod adjustment;
 mod msep;
+mod transforms;
 
 use super::error::AgError;
 use super::packed::{PackedBuckets, PackedBucketsBuilder};
@@ -19,6 +21,7 @@ use super::CaugiGraph;
 use crate::edges::EdgeClass;
 use crate::graph::alg::bitset;
 use crate::graph::alg::directed_part_is_acyclic;
+use crate::graph::alg::inducing;
 use crate::graph::alg::traversal;
 use std::sync::Arc;
 
@@ -380,21 +383,18 @@ impl Ag {
     /// 1. Violating the ancestral graph constraints, OR
     /// 2. Changing the encoded independence model
     ///
-    /// For each non-adjacent pair (u, v), if they are not m-separated given any
-    /// subset of the remaining nodes, then an edge could be added, making the
-    /// graph non-maximal.
-    ///
-    /// Note: This is a computationally expensive check (exponential in the worst case).
+    /// An ancestral graph is maximal iff no two non-adjacent nodes are joined by
+    /// an inducing path (Richardson & Spirtes, 2002, Theorem 4.3), so this runs
+    /// one collider-path search per non-adjacent pair: polynomial in the size
+    /// of the graph.
     pub fn is_mag(&self) -> bool {
         let n = self.n();
-        if n <= 1 {
-            return true;
-        }
-
-        // For each non-adjacent pair, check if there exists any separating set
         for u in 0..n {
+            if !inducing::has_arrowhead_neighbor(self, u) {
+                continue;
+            }
             for v in (u + 1)..n {
-                if !self.adjacent(u, v) && !self.has_separating_set(u, v) {
+                if !self.adjacent(u, v) && self.has_inducing_path(u, v) {
                     return false;
                 }
             }
@@ -402,38 +402,10 @@ impl Ag {
         true
     }
 
-    /// Determine if there exists any conditioning set that m-separates `u` and `v`.
-    fn has_separating_set(&self, u: u32, v: u32) -> bool {
-        let mut candidates = Vec::with_capacity(self.n() as usize - 2);
-        for w in 0..self.n() {
-            if w != u && w != v {
-                candidates.push(w);
-            }
-        }
-        let mut cur = Vec::new();
-        self.search_separator(u, v, &candidates, 0, &mut cur)
-    }
-
-    /// Recursively search for any conditioning set that m-separates `u` and `v`.
-    fn search_separator(
-        &self,
-        u: u32,
-        v: u32,
-        candidates: &[u32],
-        start: usize,
-        cur: &mut Vec<u32>,
-    ) -> bool {
-        if self.m_separated(&[u], &[v], cur) {
-            return true;
-        }
-        for i in start..candidates.len() {
-            cur.push(candidates[i]);
-            if self.search_separator(u, v, candidates, i + 1, cur) {
-                return true;
-            }
-            cur.pop();
-        }
-        false
+    /// Whether there is an inducing path between `u` and `v` through at least
+    /// one collider; see [`inducing::has_inducing_path`].
+    pub(crate) fn has_inducing_path(&self, u: u32, v: u32) -> bool {
+        inducing::has_inducing_path(self, u, v)
     }
 }
 
@@ -808,30 +780,56 @@ mod tests {
         assert_eq!(ag.district_of(0), vec![0, 1, 2]);
     }
 
+    /// Richardson & Spirtes (2002), Figure 5: 0 <-> 1 <-> 2 <-> 3 with
+    /// 1 --> 3 and 2 --> 0, so 0 and 3 are joined by an inducing path.
+    fn non_maximal_ag(reg: &EdgeRegistry, dir: u8, bid: u8, inducing: bool) -> Ag {
+        let mut b = GraphBuilder::new_with_registry(4, true, reg);
+        b.add_edge(0, 1, bid).unwrap();
+        b.add_edge(1, 2, bid).unwrap();
+        b.add_edge(2, 3, bid).unwrap();
+        b.add_edge(1, 3, dir).unwrap();
+        if inducing {
+            b.add_edge(2, 0, dir).unwrap();
+        }
+        Ag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
     #[test]
-    fn ag_separator_search_recursion_true_and_false() {
-        let (reg, dir, _bid, _und) = setup();
+    fn ag_inducing_path_requires_ancestral_colliders() {
+        let (reg, dir, bid, _und) = setup();
+
+        let ag = non_maximal_ag(&reg, dir, bid, true);
+        assert!(ag.has_inducing_path(0, 3));
+        assert!(ag.has_inducing_path(3, 0));
+        assert!(!ag.is_mag());
+
+        // Without 2 --> 0, the collider 2 is not an ancestor of {0, 3}.
+        let ag = non_maximal_ag(&reg, dir, bid, false);
+        assert!(!ag.has_inducing_path(0, 3));
+        assert!(ag.is_mag());
+
+        // A chain has no colliders at all.
         let mut b = GraphBuilder::new_with_registry(3, true, &reg);
-        // Chain: 0 -> 1 -> 2
         b.add_edge(0, 1, dir).unwrap();
         b.add_edge(1, 2, dir).unwrap();
         let ag = Ag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert!(!ag.has_inducing_path(0, 2));
+    }
 
-        // Recursive search should find separator {1}.
-        let mut cur = Vec::new();
-        assert!(ag.search_separator(0, 2, &[1], 0, &mut cur));
-
-        // Adjacent pair with no candidates cannot be separated by this search.
-        let mut cur2 = Vec::new();
-        assert!(!ag.search_separator(0, 1, &[], 0, &mut cur2));
+    #[test]
+    fn ag_to_maximal_adds_bidirected_edge_for_inducing_path() {
+        let (reg, dir, bid, _und) = setup();
+        let ag = non_maximal_ag(&reg, dir, bid, true);
+        let mag = ag.to_maximal().unwrap();
+        assert!(mag.is_mag());
+        assert_eq!(mag.spouses_of(0), &[1, 3]);
+        assert_eq!(mag.spouses_of(3), &[0, 2]);
+        assert_eq!(mag.parents_of(3), &[1]);
+        assert_eq!(mag.parents_of(0), &[2]);
 
-        // Backtracking branch: try an irrelevant candidate first and pop it.
-        let mut b2 = GraphBuilder::new_with_registry(4, true, &reg);
-        b2.add_edge(0, 1, dir).unwrap();
-        b2.add_edge(1, 2, dir).unwrap();
-        let ag2 = Ag::new(Arc::new(b2.finalize().unwrap())).unwrap();
-        let mut cur3 = Vec::new();
-        assert!(!ag2.search_separator(0, 2, &[3], 0, &mut cur3));
+        // A MAG is its own maximal completion.
+        let mag2 = non_maximal_ag(&reg, dir, bid, false).to_maximal().unwrap();
+        assert!(mag2.spouses_of(0).len() == 1 && !mag2.adjacent(0, 3));
     }
 
     #[test]
@@ -930,6 +928,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ag_is_mag_matches_separator_search_on_small_graphs() {
+        let (reg, dir, bid, und) = setup();
+        let pairs: [(u32, u32); 6] = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+
+        // Exhaustive reference: a non-adjacent pair must be separated by some subset.
+        let separable = |ag: &Ag, u: u32, v: u32| {
+            let rest: Vec<u32> = (0..4).filter(|&w| w != u && w != v).collect();
+            (0..(1u32 << rest.len())).any(|bits| {
+                let z: Vec<u32> = (0..rest.len())
+                    .filter(|&i| bits & (1 << i) != 0)
+                    .map(|i| rest[i])
+                    .collect();
+                ag.m_separated(&[u], &[v], &z)
+            })
+        };
+
+        let states = 5usize;
+        for idx in 0..states.pow(pairs.len() as u32) {
+            let mut code = idx;
+            let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+            for &(a, c) in &pairs {
+                match code % states {
+                    1 => b.add_edge(a, c, dir).unwrap(),
+                    2 => b.add_edge(c, a, dir).unwrap(),
+                    3 => b.add_edge(a, c, bid).unwrap(),
+                    4 => b.add_edge(a, c, und).unwrap(),
+                    _ => {}
+                }
+                code /= states;
+            }
+            let Ok(ag) = Ag::new(Arc::new(b.finalize().unwrap())) else {
+                continue;
+            };
+            let expected = pairs
+                .iter()
+                .all(|&(u, v)| ag.adjacent(u, v) || separable(&ag, u, v));
+            assert_eq!(ag.is_mag(), expected, "graph index {}", idx);
+            if !expected {
+                assert!(ag.to_maximal().unwrap().is_mag());
+            }
+        }
+    }
+
     // Note: Anterior constraint validation tests would require carefully
     // constructed graphs that violate the constraint. Since our construction
     // validates this, we test via try_new error cases.
@@ -14,7 +14,9 @@
 
 use super::Ag;
 use crate::graph::alg::bitset;
+use crate::graph::alg::implied::{self, ConditionalIndependence};
 use crate::graph::alg::min_msep::{self, MixedGraph};
+use crate::graph::alg::open_paths::{self, OpenPath};
 
 impl MixedGraph for Ag {
     fn n(&self) -> u32 {
@@ -248,6 +250,27 @@ impl Ag {
         !Self::reachable_in_augmented(&adj, &mask, xs, &blocked, ys)
     }
 
+    /// A shortest m-connecting path between `xs` and `ys` given `z`, or
+    /// `None` if they are m-separated.
+    pub fn m_connecting_path(&self, xs: &[u32], ys: &[u32], z: &[u32]) -> Option<OpenPath> {
+        if self.m_separated(xs, ys, z) {
+            return None;
+        }
+        open_paths::shortest_open_path(self, xs, ys, z)
+    }
+
+    /// Every m-connecting path between `xs` and `ys` given `z` with at most
+    /// `max_len` edges, shortest first.
+    pub fn m_connecting_paths(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+        max_len: usize,
+    ) -> Vec<OpenPath> {
+        open_paths::open_paths(self, xs, ys, z, max_len)
+    }
+
     /// Computes a minimal m-separator for `xs` and `ys` in the ancestral graph.
     ///
     /// See [`crate::graph::alg::min_msep::find_min_msep`] for the algorithm
@@ -261,6 +284,12 @@ impl Ag {
     ) -> Result<Option<Vec<u32>>, String> {
         min_msep::find_min_msep(self, xs, ys, include, restrict)
     }
+
+    /// One independence `u ⊥ v | Z` per separable non-adjacent pair `u < v`,
+    /// with `Z` a minimal m-separator, ordered by `(u, v)`.
+    pub fn implied_independencies(&self) -> Vec<ConditionalIndependence> {
+        implied::pairwise_basis(self)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MIT
+//! Graph transformations for ancestral graphs.
+
+use super::Ag;
+use crate::edges::Mark;
+use crate::graph::alg::csr;
+use crate::graph::alg::fci::{self, MarkMatrix};
+use crate::graph::builder::GraphBuilder;
+use crate::graph::pag::Pag;
+use crate::graph::RegistrySnapshot;
+use std::sync::Arc;
+
+impl Ag {
+    /// Build an AG on `n` nodes from `(u, v, mark at u, mark at v)` edges,
+    /// picking edge codes from `registry`.
+    pub(crate) fn from_marked_edges<I>(
+        registry: &RegistrySnapshot,
+        n: u32,
+        edges: I,
+    ) -> Result<Ag, String>
+    where
+        I: IntoIterator<Item = (u32, u32, Mark, Mark)>,
+    {
+        let mut b = GraphBuilder::new_from_snapshot(n, true, Arc::new(registry.clone()));
+        for (u, v, at_u, at_v) in edges {
+            let (code, reversed) = csr::code_for_marks(registry, at_u, at_v)?;
+            if reversed {
+                b.add_edge(v, u, code)?;
+            } else {
+                b.add_edge(u, v, code)?;
+            }
+        }
+        Ag::new(Arc::new(b.finalize()?))
+    }
+
+    /// Whether the edge between `u` and `v` has an arrowhead at `v`.
+    #[inline]
+    fn has_arrowhead_at(&self, u: u32, v: u32) -> bool {
+        self.parents_of(v).binary_search(&u).is_ok() || self.spouses_of(v).binary_search(&u).is_ok()
+    }
+
+    /// Complete an ancestral graph to the unique MAG that contains it and
+    /// encodes the same independence model.
+    ///
+    /// Every non-adjacent pair joined by an inducing path receives a `<->`
+    /// edge; all pairs are found on the input graph before any edge is added
+    /// (Richardson & Spirtes, 2002, Theorem 5.1). A MAG is returned unchanged.
+    ///
+    /// # References
+    ///
+    /// T. Richardson and P. Spirtes (2002). Ancestral graph Markov models.
+    /// *The Annals of Statistics*, 30(4), 962–1030.
+    pub fn to_maximal(&self) -> Result<Ag, String> {
+        let n = self.n();
+        let core = self.core_ref();
+        let mut edges: Vec<(u32, u32, Mark, Mark)> = (0..n)
+            .flat_map(|a| {
+                core.row_range(a).filter_map(move |k| {
+                    let b = core.col_index[k];
+                    (a < b).then(|| (a, b, core.my_mark(k), core.nbr_mark(k)))
+                })
+            })
+            .collect();
+        for u in 0..n {
+            for v in (u + 1)..n {
+                if !self.adjacent(u, v) && self.has_inducing_path(u, v) {
+                    edges.push((u, v, Mark::Arrow, Mark::Arrow));
+                }
+            }
+        }
+        Ag::from_marked_edges(&core.registry, n, edges)
+    }
+
+    /// Convert a MAG to its PAG: the essential graph of the MAG's Markov
+    /// equivalence class (skeleton, unshielded colliders, then FCI rules R1–R10).
+    ///
+    /// Discriminated colliders are resolved by R4 against the MAG itself: the
+    /// middle node of a discriminating path is a non-collider exactly when it
+    /// lies in every (equivalently, in a minimal) m-separator of the path's
+    /// endpoints. The input is assumed to be maximal; see [`is_mag`](Self::is_mag).
+    ///
+    /// # References
+    ///
+    /// J. Zhang (2008). On the completeness of orientation rules for causal
+    /// discovery in the presence of latent confounders and selection bias.
+    /// *Artificial Intelligence*, 172(16–17), 1873–1896.
+    pub fn to_pag(&self) -> Result<Pag, String> {
+        let n = self.n();
+
+        // Endpoint `v` of `u *-* v` keeps its arrowhead iff it is part of an
+        // unshielded collider `u *-> v <-* w`.
+        let collider_end = |u: u32, v: u32| {
+            self.has_arrowhead_at(u, v)
+                && self
+                    .neighbors_of(v)
+                    .iter()
+                    .any(|&w| w != u && self.has_arrowhead_at(w, v) && !self.adjacent(u, w))
+        };
+        let mark = |u: u32, v: u32| {
+            if collider_end(u, v) {
+                Mark::Arrow
+            } else {
+                Mark::Circle
+            }
+        };
+
+        let mut edges = Vec::new();
+        for u in 0..n {
+            for &v in self.neighbors_of(u) {
+                if u < v {
+                    edges.push((u, v, mark(v, u), mark(u, v)));
+                }
+            }
+        }
+        let mut g = MarkMatrix::from_edges(n as usize, edges);
+
+        fci::apply_fci_closure(&mut g, |theta, gamma, beta| {
+            let restrict: Vec<u32> = (0..n).filter(|&v| v != theta && v != gamma).collect();
+            self.minimal_m_separator(&[theta], &[gamma], &[], &restrict)
+                .ok()
+                .flatten()
+                .is_some_and(|z| z.contains(&beta))
+        });
+
+        Pag::from_mark_matrix(&self.core_ref().registry, &g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+
+    fn build(n: u32, edges: &[(u32, u32, &str)]) -> Ag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Ag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn to_pag_chain_is_all_circles() {
+        let pag = build(3, &[(0, 1, "-->"), (1, 2, "-->")]).to_pag().unwrap();
+        assert_eq!(pag.circle_neighbors_of(1), &[0, 2]);
+        assert_eq!(pag.marks_between(0, 1), Some((Mark::Circle, Mark::Circle)));
+    }
+
+    #[test]
+    fn to_pag_collider_then_r1() {
+        // 0 --> 2 <-- 1, 2 --> 3  =>  0 o-> 2 <-o 1, 2 --> 3
+        let pag = build(4, &[(0, 2, "-->"), (1, 2, "-->"), (2, 3, "-->")])
+            .to_pag()
+            .unwrap();
+        assert_eq!(pag.marks_between(0, 2), Some((Mark::Circle, Mark::Arrow)));
+        assert_eq!(pag.marks_between(1, 2), Some((Mark::Circle, Mark::Arrow)));
+        assert_eq!(pag.children_of(2), &[3]);
+    }
+
+    #[test]
+    fn to_pag_discriminated_collider() {
+        // 0 --> 1 <-> 2 <-> 3, 1 --> 3: 2 is a collider on the discriminating path
+        let pag = build(
+            4,
+            &[(0, 1, "-->"), (1, 2, "<->"), (2, 3, "<->"), (1, 3, "-->")],
+        )
+        .to_pag()
+        .unwrap();
+        assert_eq!(pag.marks_between(0, 1), Some((Mark::Circle, Mark::Arrow)));
+        assert_eq!(pag.children_of(1), &[3]);
+        assert_eq!(pag.spouses_of(2), &[1, 3]);
+    }
+
+    #[test]
+    fn to_pag_discriminated_non_collider() {
+        // 0 --> 1 <-> 2 --> 3, 1 --> 3: 2 is a non-collider on the discriminating path
+        let pag = build(
+            4,
+            &[(0, 1, "-->"), (1, 2, "<->"), (2, 3, "-->"), (1, 3, "-->")],
+        )
+        .to_pag()
+        .unwrap();
+        assert_eq!(pag.children_of(2), &[3]);
+        assert_eq!(pag.marks_between(2, 1), Some((Mark::Circle, Mark::Arrow)));
+        assert_eq!(pag.children_of(1), &[3]);
+    }
+
+    #[test]
+    fn to_pag_undirected_part_stays_undirected() {
+        // Selection: 0 --- 1, 1 --> 2 <-- 3
+        let pag = build(4, &[(0, 1, "---"), (1, 2, "-->"), (3, 2, "-->")])
+            .to_pag()
+            .unwrap();
+        assert_eq!(pag.marks_between(1, 2), Some((Mark::Circle, Mark::Arrow)));
+        assert_eq!(pag.marks_between(0, 1), Some((Mark::Circle, Mark::Circle)));
+    }
+}
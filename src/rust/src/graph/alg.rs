@@ -2,12 +2,23 @@
 //! Graph algorithms.
 
 pub mod acyclic;
+pub mod bignum;
 pub mod bitset;
+pub mod clique_picking;
 pub mod csr;
+pub mod do_calculus;
+pub mod fci;
+pub mod gac;
+pub mod implied;
+pub mod inducing;
+pub mod linalg;
+pub mod list_sep;
 pub mod meek;
 pub mod min_msep;
 pub mod moral;
+pub mod open_paths;
 pub mod reachability;
+pub mod rng;
 pub mod subsets;
 pub mod topo_sort;
 pub mod traversal;
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: MIT
+//! Minimal arbitrary-precision unsigned integers for exact counting.
+//!
+//! Only what the counting algorithms need: addition, subtraction,
+//! multiplication, uniform sampling below a bound, conversion to `u128`, and
+//! decimal formatting. Limbs are
+//! base `2^32`, least significant first, without trailing zeros.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+pub(crate) struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub(crate) fn zero() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn one() -> Self {
+        Self::from_u128(1)
+    }
+
+    pub(crate) fn from_u128(mut v: u128) -> Self {
+        let mut limbs = Vec::new();
+        while v > 0 {
+            limbs.push(v as u32);
+            v >>= 32;
+        }
+        Self { limbs }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn trim(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    pub(crate) fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0u128, |acc, &l| (acc << 32) | l as u128),
+        )
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+        for i in 0..len {
+            let a = *self.limbs.get(i).unwrap_or(&0) as u64;
+            let b = *other.limbs.get(i).unwrap_or(&0) as u64;
+            let s = a + b + carry;
+            limbs.push(s as u32);
+            carry = s >> 32;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        Self { limbs }
+    }
+
+    /// `self - other`; panics if `other > self`.
+    pub(crate) fn sub(&self, other: &Self) -> Self {
+        assert!(*self >= *other, "BigUint subtraction underflow");
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0i64;
+        for i in 0..self.limbs.len() {
+            let mut d = self.limbs[i] as i64 - *other.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if d < 0 {
+                d += 1 << 32;
+                borrow = 1;
+            }
+            limbs.push(d as u32);
+        }
+        Self { limbs }.trim()
+    }
+
+    pub(crate) fn mul(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Self::zero();
+        }
+        let mut acc = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let t = acc[i + j] + a as u64 * b as u64 + carry;
+                acc[i + j] = t & 0xffff_ffff;
+                carry = t >> 32;
+            }
+            let mut k = i + other.limbs.len();
+            while carry > 0 {
+                let t = acc[k] + carry;
+                acc[k] = t & 0xffff_ffff;
+                carry = t >> 32;
+                k += 1;
+            }
+        }
+        Self {
+            limbs: acc.into_iter().map(|l| l as u32).collect(),
+        }
+        .trim()
+    }
+
+    /// Uniform value in `0..self` drawn from the 32-bit source `next`, by
+    /// rejection on the bit length of `self`; `self` must be positive.
+    pub(crate) fn random_below(&self, mut next: impl FnMut() -> u32) -> Self {
+        assert!(!self.is_zero(), "BigUint::random_below called on zero");
+        let top = *self.limbs.last().unwrap();
+        let mask = u32::MAX >> top.leading_zeros();
+        loop {
+            let mut limbs: Vec<u32> = (0..self.limbs.len()).map(|_| next()).collect();
+            *limbs.last_mut().unwrap() &= mask;
+            let r = Self { limbs }.trim();
+            if r < *self {
+                return r;
+            }
+        }
+    }
+
+    /// Divide in place by a small divisor, returning the remainder.
+    fn div_rem_small(&mut self, d: u32) -> u32 {
+        let mut rem = 0u64;
+        for l in self.limbs.iter_mut().rev() {
+            let cur = (rem << 32) | *l as u64;
+            *l = (cur / d as u64) as u32;
+            rem = cur % d as u64;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        rem as u32
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(v) = self.to_u128() {
+            return write!(f, "{}", v);
+        }
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_zero() {
+            chunks.push(rest.div_rem_small(1_000_000_000));
+        }
+        let mut s = chunks.pop().unwrap().to_string();
+        for c in chunks.iter().rev() {
+            s.push_str(&format!("{:09}", c));
+        }
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factorial(n: u32) -> BigUint {
+        (1..=n).fold(BigUint::one(), |acc, k| {
+            acc.mul(&BigUint::from_u128(k as u128))
+        })
+    }
+
+    #[test]
+    fn bignum_arithmetic_and_formatting() {
+        let a = BigUint::from_u128(u128::MAX);
+        let b = a.add(&BigUint::one());
+        assert_eq!(b.to_u128(), None);
+        assert_eq!(b.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(b.sub(&BigUint::one()), a);
+        assert_eq!(
+            factorial(40).to_string(),
+            "815915283247897734345611269596115894272000000000"
+        );
+        assert_eq!(factorial(20).to_u128(), Some(2_432_902_008_176_640_000));
+        assert!(BigUint::zero().is_zero());
+        assert!(factorial(5) < factorial(6));
+
+        let mut state = 7u32;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        let bound = factorial(30);
+        for _ in 0..50 {
+            assert!(bound.random_below(&mut next) < bound);
+        }
+        let three = BigUint::from_u128(3);
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            seen[three.random_below(&mut next).to_u128().unwrap() as usize] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+}
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MIT
 //! CSR graph manipulation utilities.
 
-use crate::edges::EdgeClass;
+use crate::edges::{EdgeClass, Mark};
 use crate::graph::CaugiGraph;
 
 /// Build an undirected graph core from adjacency lists.
@@ -60,6 +60,38 @@ pub fn undirected_code(registry: &crate::graph::RegistrySnapshot) -> Result<u8,
     Err("No undirected edge spec in registry".into())
 }
 
+/// Find an edge code whose endpoint marks are `(at_u, at_v)` for an edge `u *-* v`.
+///
+/// Returns the code and whether the edge has to be stored reversed (`v` at the
+/// tail position). Built-in glyphs are preferred over user-registered ones.
+pub fn code_for_marks(
+    registry: &crate::graph::RegistrySnapshot,
+    at_u: Mark,
+    at_v: Mark,
+) -> Result<(u8, bool), String> {
+    const BUILTINS: [&str; 6] = ["-->", "---", "<->", "o-o", "--o", "o->"];
+    let mut best: Option<(u8, bool, bool)> = None;
+    for (i, s) in registry.specs.iter().enumerate() {
+        let reversed = if (s.tail, s.head) == (at_u, at_v) {
+            false
+        } else if (s.tail, s.head) == (at_v, at_u) {
+            true
+        } else {
+            continue;
+        };
+        let builtin = BUILTINS.contains(&s.glyph.as_str());
+        let better = match best {
+            None => true,
+            Some((_, _, b)) => builtin && !b,
+        };
+        if better {
+            best = Some((i as u8, reversed, builtin));
+        }
+    }
+    best.map(|(code, reversed, _)| (code, reversed))
+        .ok_or_else(|| format!("No edge spec with marks ({}, {}) in registry", at_u, at_v))
+}
+
 /// Filter edges from a graph based on a predicate.
 ///
 /// # Arguments
@@ -237,4 +269,32 @@ mod tests {
         let err = undirected_code(&snap2).unwrap_err();
         assert!(err.contains("No undirected edge spec in registry"));
     }
+
+    #[test]
+    fn code_for_marks_prefers_builtins_and_reverses() {
+        let mut reg = EdgeRegistry::new();
+        reg.register(EdgeSpec {
+            glyph: "==>".into(),
+            tail: Mark::Tail,
+            head: Mark::Arrow,
+            symmetric: false,
+            class: EdgeClass::Directed,
+        })
+        .unwrap();
+        reg.register_builtins().unwrap();
+        let core = GraphBuilder::new_with_registry(1, true, &reg)
+            .finalize()
+            .unwrap();
+        let snap = &core.registry;
+
+        let (code, reversed) = code_for_marks(snap, Mark::Tail, Mark::Arrow).unwrap();
+        assert_eq!(code, reg.code_of("-->").unwrap());
+        assert!(!reversed);
+
+        let (code, reversed) = code_for_marks(snap, Mark::Arrow, Mark::Circle).unwrap();
+        assert_eq!(code, reg.code_of("o->").unwrap());
+        assert!(reversed);
+
+        assert!(code_for_marks(snap, Mark::Other, Mark::Tail).is_err());
+    }
 }
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT
+//! Pearl's do-calculus rule checks via separation in mutilated graphs.
+//!
+//! For disjoint node sets `X`, `Y`, `Z`, `W` the three rules are
+//!
+//! 1. `P(y | do(x), z, w) = P(y | do(x), w)` if `Y ⊥ Z | X, W` in `G_{X̄}`;
+//! 2. `P(y | do(x), do(z), w) = P(y | do(x), z, w)` if `Y ⊥ Z | X, W` in `G_{X̄ Z̲}`;
+//! 3. `P(y | do(x), do(z), w) = P(y | do(x), w)` if `Y ⊥ Z | X, W` in
+//!    `G_{X̄ Z(W)‾}`, where `Z(W) = Z \ An(W)` in `G_{X̄}`.
+//!
+//! `G_{X̄}` removes every edge with an arrowhead at `X` (incoming directed and
+//! all bidirected edges), `G_{Z̲}` removes directed edges out of `Z`.
+//!
+//! # References
+//!
+//! J. Pearl (1995). Causal diagrams for empirical research. *Biometrika*,
+//! 82(4), 669–688.
+
+use super::{bitset, csr};
+use crate::edges::Mark;
+use crate::graph::CaugiGraph;
+
+/// Which do-calculus rules license a rewrite of a `(X, Y, Z, W)` query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoCalculusRules {
+    /// Insertion/deletion of the observation `z`.
+    pub rule1: bool,
+    /// Exchange of the action `do(z)` with the observation `z`.
+    pub rule2: bool,
+    /// Insertion/deletion of the action `do(z)`.
+    pub rule3: bool,
+}
+
+/// Copy of `core` without edges that have an arrowhead at a `bar` node or
+/// leave an `underline` node as a directed edge.
+pub(crate) fn mutilate(
+    core: &CaugiGraph,
+    bar: &[bool],
+    underline: &[bool],
+) -> Result<CaugiGraph, String> {
+    csr::filter_edges(core, |u, k, c| {
+        let v = c.col_index[k];
+        let (mu, mv) = c.marks(k);
+        let into = |a: u32, m: Mark| m == Mark::Arrow && bar[a as usize];
+        let out_of = |a: u32, ma: Mark, mb: Mark| {
+            ma == Mark::Tail && mb == Mark::Arrow && underline[a as usize]
+        };
+        !(into(u, mu) || into(v, mv) || out_of(u, mu, mv) || out_of(v, mv, mu))
+    })
+}
+
+/// Check all three rules for the query `(xs, ys, zs, ws)`.
+///
+/// `parents_of` gives the directed parents in `core`, and `separated(g, ys,
+/// zs, cond)` decides separation in a mutilated copy `g` of `core`.
+pub(crate) fn check_rules<'a, P, S>(
+    core: &CaugiGraph,
+    parents_of: P,
+    xs: &[u32],
+    ys: &[u32],
+    zs: &[u32],
+    ws: &[u32],
+    separated: S,
+) -> Result<DoCalculusRules, String>
+where
+    P: Fn(u32) -> &'a [u32],
+    S: Fn(CaugiGraph, &[u32], &[u32], &[u32]) -> Result<bool, String>,
+{
+    let n = core.n();
+    let mut seen = vec![false; n as usize];
+    for (set, name) in [(xs, "X"), (ys, "Y"), (zs, "Z"), (ws, "W")] {
+        for &v in set {
+            if v >= n {
+                return Err(format!("Index {} is out of bounds (n = {})", v, n));
+            }
+            if seen[v as usize] {
+                return Err(format!(
+                    "Node {} appears more than once across X, Y, Z, W (in {})",
+                    v, name
+                ));
+            }
+            seen[v as usize] = true;
+        }
+    }
+    if ys.is_empty() || zs.is_empty() {
+        return Err("do-calculus rules require non-empty Y and Z".into());
+    }
+
+    let x = bitset::mask_from(xs, n);
+    let z = bitset::mask_from(zs, n);
+    let none = vec![false; n as usize];
+    let mut cond = xs.to_vec();
+    cond.extend_from_slice(ws);
+
+    let rule1 = separated(mutilate(core, &x, &none)?, ys, zs, &cond)?;
+    let rule2 = separated(mutilate(core, &x, &z)?, ys, zs, &cond)?;
+
+    // Z(W): nodes of Z that are not ancestors of W once edges into X are cut.
+    let mut an_w = vec![false; n as usize];
+    let mut st = ws.to_vec();
+    while let Some(v) = st.pop() {
+        if an_w[v as usize] {
+            continue;
+        }
+        an_w[v as usize] = true;
+        if !x[v as usize] {
+            st.extend_from_slice(parents_of(v));
+        }
+    }
+    let mut bar = x.clone();
+    for &v in zs {
+        bar[v as usize] |= !an_w[v as usize];
+    }
+    let rule3 = separated(mutilate(core, &bar, &none)?, ys, zs, &cond)?;
+
+    Ok(DoCalculusRules {
+        rule1,
+        rule2,
+        rule3,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+
+    #[test]
+    fn mutilate_removes_arrowheads_into_bar_and_arrows_out_of_underline() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let bi = reg.code_of("<->").unwrap();
+        // 0 --> 1 --> 2, 0 <-> 2, 3 --> 1
+        let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+        b.add_edge(0, 1, d).unwrap();
+        b.add_edge(1, 2, d).unwrap();
+        b.add_edge(0, 2, bi).unwrap();
+        b.add_edge(3, 1, d).unwrap();
+        let core = b.finalize().unwrap();
+
+        // bar {1}: drop 0 --> 1 and 3 --> 1, keep 1 --> 2 and 0 <-> 2
+        let g = mutilate(&core, &[false, true, false, false], &[false; 4]).unwrap();
+        assert_eq!(g.row_range(1).len(), 1);
+        assert_eq!(g.row_range(0).len(), 1);
+
+        // bar {2}: drop 1 --> 2 and 0 <-> 2; underline {0}: drop 0 --> 1
+        let g = mutilate(
+            &core,
+            &[false, false, true, false],
+            &[true, false, false, false],
+        )
+        .unwrap();
+        assert_eq!(g.row_range(0).len(), 0);
+        assert_eq!(g.row_range(2).len(), 0);
+        assert_eq!(g.row_range(1).len(), 1);
+    }
+}
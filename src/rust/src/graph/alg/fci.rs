@@ -0,0 +1,658 @@
+// SPDX-License-Identifier: MIT
+//! FCI orientation rules (Zhang, 2008) on a dense endpoint-mark matrix.
+//!
+//! The closure takes a PAG skeleton whose unshielded colliders are already
+//! oriented and applies rules R1–R10 until no endpoint changes. R4 needs
+//! separating-set information, which is supplied by the caller.
+
+use crate::edges::Mark;
+use crate::graph::CaugiGraph;
+
+/// Dense endpoint-mark matrix of a graph with a fixed skeleton.
+///
+/// `at(a, b)` is the mark at `b` on the edge `a *-* b`, or `None` if `a` and
+/// `b` are not adjacent.
+#[derive(Debug, Clone)]
+pub(crate) struct MarkMatrix {
+    n: usize,
+    marks: Vec<Option<Mark>>,
+    adj: Vec<Vec<u32>>,
+}
+
+impl MarkMatrix {
+    /// Build from `(a, b, mark at a, mark at b)` edges; each pair is listed once.
+    pub(crate) fn from_edges<I>(n: usize, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (u32, u32, Mark, Mark)>,
+    {
+        let mut marks = vec![None; n * n];
+        let mut adj = vec![Vec::new(); n];
+        for (a, b, ma, mb) in edges {
+            let (ai, bi) = (a as usize, b as usize);
+            if marks[ai * n + bi].is_none() {
+                adj[ai].push(b);
+                adj[bi].push(a);
+            }
+            marks[bi * n + ai] = Some(ma);
+            marks[ai * n + bi] = Some(mb);
+        }
+        for row in &mut adj {
+            row.sort_unstable();
+        }
+        Self { n, marks, adj }
+    }
+
+    /// Build from the endpoint marks of `core`.
+    pub(crate) fn from_core(core: &CaugiGraph) -> Self {
+        let edges = (0..core.n()).flat_map(|a| {
+            core.row_range(a).filter_map(move |k| {
+                let b = core.col_index[k];
+                (a < b).then(|| (a, b, core.my_mark(k), core.nbr_mark(k)))
+            })
+        });
+        Self::from_edges(core.n() as usize, edges)
+    }
+
+    #[inline]
+    pub(crate) fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Mark at `b` on the edge between `a` and `b`.
+    #[inline]
+    pub(crate) fn at(&self, a: u32, b: u32) -> Option<Mark> {
+        self.marks[a as usize * self.n + b as usize]
+    }
+
+    #[inline]
+    pub(crate) fn is(&self, a: u32, b: u32, m: Mark) -> bool {
+        self.at(a, b) == Some(m)
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, a: u32, b: u32, m: Mark) {
+        debug_assert!(self.adjacent(a, b));
+        self.marks[a as usize * self.n + b as usize] = Some(m);
+    }
+
+    #[inline]
+    pub(crate) fn adjacent(&self, a: u32, b: u32) -> bool {
+        self.at(a, b).is_some()
+    }
+
+    /// Sorted neighbors of `a`.
+    #[inline]
+    pub(crate) fn neighbors(&self, a: u32) -> &[u32] {
+        &self.adj[a as usize]
+    }
+
+    /// `a --> b`.
+    #[inline]
+    pub(crate) fn is_directed(&self, a: u32, b: u32) -> bool {
+        self.is(b, a, Mark::Tail) && self.is(a, b, Mark::Arrow)
+    }
+
+    /// The edge `a *-* b` is potentially directed from `a` to `b`: no
+    /// arrowhead at `a` and no tail at `b`.
+    #[inline]
+    fn is_potentially_directed(&self, a: u32, b: u32) -> bool {
+        match (self.at(b, a), self.at(a, b)) {
+            (Some(ma), Some(mb)) => ma != Mark::Arrow && mb != Mark::Tail,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    fn is_circle_circle(&self, a: u32, b: u32) -> bool {
+        self.is(b, a, Mark::Circle) && self.is(a, b, Mark::Circle)
+    }
+
+    /// Search for an uncovered path `start, first, ..., target` whose consecutive
+    /// edges all satisfy `step`. The path never visits nodes in `avoid`, and
+    /// `accept` gets the final say on a complete path.
+    fn uncovered_path<S, A>(
+        &self,
+        start: u32,
+        first: u32,
+        target: u32,
+        avoid: &[u32],
+        step: S,
+        accept: A,
+    ) -> Option<Vec<u32>>
+    where
+        S: Fn(u32, u32) -> bool,
+        A: Fn(&[u32]) -> bool,
+    {
+        if !step(start, first) || avoid.contains(&first) {
+            return None;
+        }
+        let mut on_path = vec![false; self.n];
+        on_path[start as usize] = true;
+        on_path[first as usize] = true;
+        for &v in avoid {
+            on_path[v as usize] = true;
+        }
+        let mut path = vec![start, first];
+        if self.extend_uncovered(target, &mut path, &mut on_path, &step, &accept) {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    fn extend_uncovered<S, A>(
+        &self,
+        target: u32,
+        path: &mut Vec<u32>,
+        on_path: &mut [bool],
+        step: &S,
+        accept: &A,
+    ) -> bool
+    where
+        S: Fn(u32, u32) -> bool,
+        A: Fn(&[u32]) -> bool,
+    {
+        let cur = path[path.len() - 1];
+        if cur == target {
+            return accept(path);
+        }
+        let prev = path[path.len() - 2];
+        for &next in self.neighbors(cur) {
+            if on_path[next as usize] || self.adjacent(prev, next) || !step(cur, next) {
+                continue;
+            }
+            on_path[next as usize] = true;
+            path.push(next);
+            if self.extend_uncovered(target, path, on_path, step, accept) {
+                return true;
+            }
+            path.pop();
+            on_path[next as usize] = false;
+        }
+        false
+    }
+}
+
+/// Apply FCI rules R1–R10 to a fixed point.
+///
+/// `in_sepset(theta, gamma, beta)` reports whether `beta` belongs to the
+/// separating set found for the non-adjacent pair `(theta, gamma)`; it is only
+/// consulted by R4 on discriminating paths.
+pub(crate) fn apply_fci_closure<F>(g: &mut MarkMatrix, in_sepset: F)
+where
+    F: Fn(u32, u32, u32) -> bool,
+{
+    loop {
+        let mut changed = false;
+        changed |= rule1(g);
+        changed |= rule2(g);
+        changed |= rule3(g);
+        changed |= rule4(g, &in_sepset);
+        if changed {
+            continue;
+        }
+        changed |= rule5(g);
+        changed |= rule6(g);
+        changed |= rule7(g);
+        changed |= rule8(g);
+        changed |= rule9(g);
+        changed |= rule10(g);
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// R1: α *→ β o-* γ, α and γ non-adjacent  =>  β → γ.
+fn rule1(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for b in 0..g.n() as u32 {
+        let nb = g.neighbors(b).to_vec();
+        for &c in &nb {
+            if !g.is(c, b, Mark::Circle) {
+                continue;
+            }
+            let fires = nb
+                .iter()
+                .any(|&a| a != c && g.is(a, b, Mark::Arrow) && !g.adjacent(a, c));
+            if fires {
+                g.set(c, b, Mark::Tail);
+                g.set(b, c, Mark::Arrow);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// R2: α → β *→ γ or α *→ β → γ, and α *-o γ  =>  α *→ γ.
+fn rule2(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for a in 0..g.n() as u32 {
+        let na = g.neighbors(a).to_vec();
+        for &c in &na {
+            if !g.is(a, c, Mark::Circle) {
+                continue;
+            }
+            let fires = na.iter().any(|&b| {
+                b != c
+                    && g.adjacent(b, c)
+                    && ((g.is_directed(a, b) && g.is(b, c, Mark::Arrow))
+                        || (g.is(a, b, Mark::Arrow) && g.is_directed(b, c)))
+            });
+            if fires {
+                g.set(a, c, Mark::Arrow);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// R3: α *→ β ←* γ, α *-o θ o-* γ, α and γ non-adjacent, θ *-o β  =>  θ *→ β.
+fn rule3(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for b in 0..g.n() as u32 {
+        let nb = g.neighbors(b).to_vec();
+        for &t in &nb {
+            if !g.is(t, b, Mark::Circle) {
+                continue;
+            }
+            let into_b: Vec<u32> = nb
+                .iter()
+                .copied()
+                .filter(|&x| x != t && g.is(x, b, Mark::Arrow) && g.is(x, t, Mark::Circle))
+                .collect();
+            let fires = into_b
+                .iter()
+                .enumerate()
+                .any(|(i, &a)| into_b[i + 1..].iter().any(|&c| !g.adjacent(a, c)));
+            if fires {
+                g.set(t, b, Mark::Arrow);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Find a discriminating path `θ, ..., α, β, γ` for `β`, where `α` is given.
+///
+/// Every vertex between `θ` and `β` must be a collider on the path and a
+/// parent of `γ`, and `θ` must not be adjacent to `γ`. Returns `θ`.
+fn discriminating_path_start(g: &MarkMatrix, a: u32, b: u32, c: u32) -> Option<u32> {
+    let mut seen = vec![false; g.n()];
+    seen[a as usize] = true;
+    seen[b as usize] = true;
+    seen[c as usize] = true;
+    let mut queue = std::collections::VecDeque::from([a]);
+    while let Some(v) = queue.pop_front() {
+        for &w in g.neighbors(v) {
+            if seen[w as usize] || !g.is(w, v, Mark::Arrow) {
+                continue;
+            }
+            if !g.adjacent(w, c) {
+                return Some(w);
+            }
+            if g.is(v, w, Mark::Arrow) && g.is_directed(w, c) {
+                seen[w as usize] = true;
+                queue.push_back(w);
+            }
+        }
+    }
+    None
+}
+
+/// R4: discriminating path `θ, ..., α, β, γ` for `β` with `β o-* γ`. If `β` is
+/// in the separating set of `(θ, γ)` then `β → γ`, otherwise `α ↔ β ↔ γ`.
+fn rule4<F>(g: &mut MarkMatrix, in_sepset: &F) -> bool
+where
+    F: Fn(u32, u32, u32) -> bool,
+{
+    let mut changed = false;
+    for b in 0..g.n() as u32 {
+        let nb = g.neighbors(b).to_vec();
+        for &c in &nb {
+            if !g.is(c, b, Mark::Circle) {
+                continue;
+            }
+            for &a in &nb {
+                if a == c || !g.is(b, a, Mark::Arrow) || !g.is_directed(a, c) {
+                    continue;
+                }
+                if let Some(theta) = discriminating_path_start(g, a, b, c) {
+                    if in_sepset(theta, c, b) {
+                        g.set(c, b, Mark::Tail);
+                        g.set(b, c, Mark::Arrow);
+                    } else {
+                        g.set(a, b, Mark::Arrow);
+                        g.set(c, b, Mark::Arrow);
+                        g.set(b, c, Mark::Arrow);
+                    }
+                    changed = true;
+                    break;
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// R5: α o-o β with an uncovered circle path `α, γ, ..., θ, β` such that
+/// α, θ and β, γ are non-adjacent  =>  α — β and every edge on the path is `—`.
+fn rule5(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for a in 0..g.n() as u32 {
+        let na = g.neighbors(a).to_vec();
+        for &b in &na {
+            if b < a || !g.is_circle_circle(a, b) {
+                continue;
+            }
+            let mut found = None;
+            for &c in &na {
+                if c == b || g.adjacent(c, b) {
+                    continue;
+                }
+                found = g.uncovered_path(
+                    a,
+                    c,
+                    b,
+                    &[],
+                    |u, v| g.is_circle_circle(u, v),
+                    |p| !g.adjacent(a, p[p.len() - 2]),
+                );
+                if found.is_some() {
+                    break;
+                }
+            }
+            if let Some(path) = found {
+                g.set(a, b, Mark::Tail);
+                g.set(b, a, Mark::Tail);
+                for w in path.windows(2) {
+                    g.set(w[0], w[1], Mark::Tail);
+                    g.set(w[1], w[0], Mark::Tail);
+                }
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// R6: α — β o-* γ  =>  β —* γ.
+fn rule6(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for b in 0..g.n() as u32 {
+        let nb = g.neighbors(b).to_vec();
+        let has_undirected = nb
+            .iter()
+            .any(|&a| g.is(a, b, Mark::Tail) && g.is(b, a, Mark::Tail));
+        if !has_undirected {
+            continue;
+        }
+        for &c in &nb {
+            if g.is(c, b, Mark::Circle) {
+                g.set(c, b, Mark::Tail);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// R7: α —o β o-* γ, α and γ non-adjacent  =>  β —* γ.
+fn rule7(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for b in 0..g.n() as u32 {
+        let nb = g.neighbors(b).to_vec();
+        for &c in &nb {
+            if !g.is(c, b, Mark::Circle) {
+                continue;
+            }
+            let fires = nb.iter().any(|&a| {
+                a != c && g.is(b, a, Mark::Tail) && g.is(a, b, Mark::Circle) && !g.adjacent(a, c)
+            });
+            if fires {
+                g.set(c, b, Mark::Tail);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// `α o→ γ`.
+#[inline]
+fn is_circle_arrow(g: &MarkMatrix, a: u32, c: u32) -> bool {
+    g.is(c, a, Mark::Circle) && g.is(a, c, Mark::Arrow)
+}
+
+/// R8: α → β → γ or α —o β → γ, and α o→ γ  =>  α → γ.
+fn rule8(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for a in 0..g.n() as u32 {
+        let na = g.neighbors(a).to_vec();
+        for &c in &na {
+            if !is_circle_arrow(g, a, c) {
+                continue;
+            }
+            let fires = na.iter().any(|&b| {
+                b != c
+                    && g.is(b, a, Mark::Tail)
+                    && (g.is(a, b, Mark::Arrow) || g.is(a, b, Mark::Circle))
+                    && g.is_directed(b, c)
+            });
+            if fires {
+                g.set(c, a, Mark::Tail);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// R9: α o→ γ with an uncovered potentially directed path `α, β, θ, ..., γ`
+/// such that γ and β are non-adjacent  =>  α → γ.
+fn rule9(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for a in 0..g.n() as u32 {
+        let na = g.neighbors(a).to_vec();
+        for &c in &na {
+            if !is_circle_arrow(g, a, c) {
+                continue;
+            }
+            let fires = na.iter().any(|&b| {
+                b != c
+                    && !g.adjacent(b, c)
+                    && g.uncovered_path(
+                        a,
+                        b,
+                        c,
+                        &[],
+                        |u, v| g.is_potentially_directed(u, v),
+                        |_| true,
+                    )
+                    .is_some()
+            });
+            if fires {
+                g.set(c, a, Mark::Tail);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// R10: α o→ γ, β → γ ← θ, uncovered potentially directed paths p1 from α to
+/// β and p2 from α to θ whose second vertices μ and ω are distinct and
+/// non-adjacent  =>  α → γ.
+fn rule10(g: &mut MarkMatrix) -> bool {
+    let mut changed = false;
+    for a in 0..g.n() as u32 {
+        let na = g.neighbors(a).to_vec();
+        for &c in &na {
+            if !is_circle_arrow(g, a, c) {
+                continue;
+            }
+            let parents: Vec<u32> = g
+                .neighbors(c)
+                .iter()
+                .copied()
+                .filter(|&p| p != a && g.is_directed(p, c))
+                .collect();
+            if parents.len() < 2 {
+                continue;
+            }
+            // First vertices μ of uncovered potentially directed paths α → μ ⇝ p.
+            let reach = |p: u32| -> Vec<u32> {
+                na.iter()
+                    .copied()
+                    .filter(|&m| {
+                        m != c
+                            && g.uncovered_path(
+                                a,
+                                m,
+                                p,
+                                &[c],
+                                |u, v| g.is_potentially_directed(u, v),
+                                |_| true,
+                            )
+                            .is_some()
+                    })
+                    .collect()
+            };
+            let firsts: Vec<Vec<u32>> = parents.iter().map(|&p| reach(p)).collect();
+            let fires = (0..parents.len()).any(|i| {
+                ((i + 1)..parents.len()).any(|j| {
+                    firsts[i]
+                        .iter()
+                        .any(|&m| firsts[j].iter().any(|&w| m != w && !g.adjacent(m, w)))
+                })
+            });
+            if fires {
+                g.set(c, a, Mark::Tail);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Mark::{Arrow as A, Circle as C, Tail as T};
+
+    fn closed(n: usize, edges: &[(u32, u32, Mark, Mark)]) -> MarkMatrix {
+        let mut g = MarkMatrix::from_edges(n, edges.iter().copied());
+        apply_fci_closure(&mut g, |_, _, _| false);
+        g
+    }
+
+    #[test]
+    fn mark_matrix_roundtrip() {
+        let g = MarkMatrix::from_edges(3, [(0, 1, C, A), (1, 2, T, T)]);
+        assert_eq!(g.at(0, 1), Some(A));
+        assert_eq!(g.at(1, 0), Some(C));
+        assert_eq!(g.at(1, 2), Some(T));
+        assert_eq!(g.at(0, 2), None);
+        assert_eq!(g.neighbors(1), &[0, 2]);
+        assert!(g.is_potentially_directed(0, 1));
+        assert!(!g.is_potentially_directed(1, 0));
+    }
+
+    #[test]
+    fn r1_orients_away_from_collider() {
+        // 0 *-> 1 o-o 2, 0 !~ 2  =>  1 --> 2
+        let g = closed(3, &[(0, 1, C, A), (1, 2, C, C)]);
+        assert_eq!((g.at(2, 1), g.at(1, 2)), (Some(T), Some(A)));
+        assert_eq!(g.at(1, 0), Some(C));
+    }
+
+    #[test]
+    fn r2_adds_arrowhead_along_directed_path() {
+        // 0 --> 1 <-> 2, 0 o-o 2  =>  0 o-> 2
+        let g = closed(3, &[(0, 1, T, A), (1, 2, A, A), (0, 2, C, C)]);
+        assert_eq!(g.at(0, 2), Some(A));
+        assert_eq!(g.at(2, 0), Some(C));
+    }
+
+    #[test]
+    fn r3_orients_into_collider() {
+        // 0 *-> 1 <-* 2, 0 o-o 3 o-o 2, 3 o-o 1, 0 !~ 2  =>  3 *-> 1
+        let g = closed(
+            4,
+            &[
+                (0, 1, C, A),
+                (2, 1, C, A),
+                (0, 3, C, C),
+                (3, 2, C, C),
+                (3, 1, C, C),
+            ],
+        );
+        assert_eq!(g.at(3, 1), Some(A));
+    }
+
+    #[test]
+    fn r4_uses_separating_sets() {
+        // θ=0 *-> α=1 <-> β=2 ... β o-o γ=3, α --> γ, 0 !~ 3.
+        let edges = [(0, 1, C, A), (1, 2, A, C), (1, 3, T, A), (2, 3, C, C)];
+        let mut g = MarkMatrix::from_edges(4, edges);
+        apply_fci_closure(&mut g, |_, _, _| true);
+        assert_eq!((g.at(3, 2), g.at(2, 3)), (Some(T), Some(A)));
+
+        let mut g = MarkMatrix::from_edges(4, edges);
+        apply_fci_closure(&mut g, |_, _, _| false);
+        assert_eq!(g.at(1, 2), Some(A));
+        assert_eq!((g.at(3, 2), g.at(2, 3)), (Some(A), Some(A)));
+    }
+
+    #[test]
+    fn r5_orients_uncovered_circle_cycle_as_undirected() {
+        // 0 o-o 1 o-o 2 o-o 3 o-o 0 (chordless 4-cycle)
+        let g = closed(4, &[(0, 1, C, C), (1, 2, C, C), (2, 3, C, C), (3, 0, C, C)]);
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            assert_eq!((g.at(a, b), g.at(b, a)), (Some(T), Some(T)));
+        }
+    }
+
+    #[test]
+    fn r6_and_r7_propagate_tails() {
+        // R6: 0 --- 1 o-> 2  =>  1 --> 2
+        let g = closed(3, &[(0, 1, T, T), (1, 2, C, A)]);
+        assert_eq!(g.at(2, 1), Some(T));
+        // R7: 0 --o 1 o-o 2, 0 !~ 2  =>  1 --o 2 (then R6/R7 cascade stops at circles)
+        let g = closed(3, &[(0, 1, T, C), (1, 2, C, C)]);
+        assert_eq!(g.at(2, 1), Some(T));
+    }
+
+    #[test]
+    fn r8_removes_circle_on_transitive_edge() {
+        // 0 --> 1 --> 2, 0 o-> 2  =>  0 --> 2
+        let g = closed(3, &[(0, 1, T, A), (1, 2, T, A), (0, 2, C, A)]);
+        assert_eq!(g.at(2, 0), Some(T));
+    }
+
+    #[test]
+    fn r9_uses_uncovered_potentially_directed_path() {
+        // 0 o-> 3, 0 o-o 1 o-o 2 --> 3, with 1 !~ 3 and 0 !~ 2
+        let g = closed(4, &[(0, 3, C, A), (0, 1, C, C), (1, 2, C, C), (2, 3, T, A)]);
+        assert_eq!(g.at(3, 0), Some(T));
+    }
+
+    #[test]
+    fn r10_uses_two_parents_of_target() {
+        // 0 o-> 3, 1 --> 3 <-- 2, 0 o-o 1, 0 o-o 2, 1 !~ 2
+        let g = closed(
+            4,
+            &[
+                (0, 3, C, A),
+                (1, 3, T, A),
+                (2, 3, T, A),
+                (0, 1, C, C),
+                (0, 2, C, C),
+            ],
+        );
+        assert_eq!(g.at(3, 0), Some(T));
+    }
+}
@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: MIT
+//! Generalized adjustment criterion (GAC) on graphs that represent classes.
+//!
+//! For a CPDAG, MPDAG, MAG or PAG `G`, a set `Z` is a valid adjustment set
+//! for `(X, Y)` iff
+//!
+//! 1. `G` is *amenable* relative to `(X, Y)`: every proper possibly directed
+//!    path from `X` to `Y` starts with a (visible) directed edge out of `X`;
+//! 2. `Z ∩ Forb(X, Y, G) = ∅`, where `Forb` holds `X` and every possible
+//!    descendant of a node `W ∉ X` on a proper possibly causal path to `Y`;
+//! 3. every proper definite-status non-causal path from `X` to `Y` is blocked
+//!    by `Z`.
+//!
+//! Conditions 1 and 2 only depend on endpoint marks and are computed here.
+//! Condition 3 is class-specific: under 1 and 2 it is equivalent to
+//! separation in the proper backdoor graph of any DAG or MAG in the class,
+//! so callers pass it in as a `blocks(z)` test on a representative member.
+//!
+//! # References
+//!
+//! E. Perković, J. Textor, M. Kalisch and M. H. Maathuis (2018). Complete
+//! graphical characterization and construction of adjustment sets in Markov
+//! equivalence classes of ancestral graphs. *JMLR*, 18(220), 1–62.
+//!
+//! E. Perković, M. Kalisch and M. H. Maathuis (2017). Interpreting and using
+//! CPDAGs with background knowledge. *UAI 2017*.
+
+use super::{bitset, subsets};
+use crate::edges::Mark;
+use crate::graph::CaugiGraph;
+use std::collections::HashSet;
+
+/// Answer to an adjustment query on a graph representing a class of models.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Amenability<T> {
+    /// The effect can be estimated by covariate adjustment in every member.
+    Amenable(T),
+    /// The possibly causal path from `x` through `first` does not start with
+    /// a (visible) directed edge, so no set is a valid adjustment set.
+    NotAmenable { x: u32, first: u32 },
+}
+
+impl<T> Amenability<T> {
+    /// Map the amenable answer, passing a not-amenable witness through.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Amenability<U> {
+        match self {
+            Amenability::Amenable(t) => Amenability::Amenable(f(t)),
+            Amenability::NotAmenable { x, first } => Amenability::NotAmenable { x, first },
+        }
+    }
+
+    /// Fallible [`map`](Self::map).
+    pub fn try_map<U, E, F>(self, f: F) -> Result<Amenability<U>, E>
+    where
+        F: FnOnce(T) -> Result<U, E>,
+    {
+        Ok(match self {
+            Amenability::Amenable(t) => Amenability::Amenable(f(t)?),
+            Amenability::NotAmenable { x, first } => Amenability::NotAmenable { x, first },
+        })
+    }
+
+    /// The amenable answer, or `None` if the effect is not amenable.
+    pub fn amenable(self) -> Option<T> {
+        match self {
+            Amenability::Amenable(t) => Some(t),
+            Amenability::NotAmenable { .. } => None,
+        }
+    }
+}
+
+/// How endpoint marks are read when following possibly directed paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Semantics {
+    /// CPDAG/MPDAG: `a --> b` and `a --- b` may point from `a` to `b`.
+    Pdag,
+    /// MAG/PAG: no arrowhead at `a` and no tail at `b`; directed edges out
+    /// of `X` must also be visible.
+    Ancestral,
+}
+
+/// Endpoint-mark adjacency: per node, sorted `(neighbour, mark here, mark there)`.
+pub(crate) struct MarkedGraph {
+    sem: Semantics,
+    adj: Vec<Vec<(u32, Mark, Mark)>>,
+}
+
+/// Forbidden set and proper possibly causal nodes of an amenable query.
+pub(crate) struct GacSets {
+    /// `Forb(X, Y)`, including `X`.
+    pub forbidden: Vec<bool>,
+    /// Nodes `W ∉ X` on a proper possibly causal path from `X` to `Y`.
+    pub causal: Vec<bool>,
+}
+
+impl MarkedGraph {
+    pub(crate) fn new(core: &CaugiGraph, sem: Semantics) -> Self {
+        let adj = (0..core.n())
+            .map(|a| {
+                let mut row: Vec<(u32, Mark, Mark)> = core
+                    .row_range(a)
+                    .map(|k| (core.col_index[k], core.my_mark(k), core.nbr_mark(k)))
+                    .collect();
+                row.sort_unstable_by_key(|e| e.0);
+                row
+            })
+            .collect();
+        Self { sem, adj }
+    }
+
+    #[inline]
+    pub(crate) fn n(&self) -> u32 {
+        self.adj.len() as u32
+    }
+
+    /// `(mark at a, mark at b)` of the edge between `a` and `b`.
+    pub(crate) fn marks(&self, a: u32, b: u32) -> Option<(Mark, Mark)> {
+        let row = &self.adj[a as usize];
+        row.binary_search_by_key(&b, |e| e.0)
+            .ok()
+            .map(|i| (row[i].1, row[i].2))
+    }
+
+    #[inline]
+    fn adjacent(&self, a: u32, b: u32) -> bool {
+        self.marks(a, b).is_some()
+    }
+
+    /// Whether an edge with these marks can be read as `a → b`.
+    #[inline]
+    fn possibly_directed(&self, at_a: Mark, at_b: Mark) -> bool {
+        match self.sem {
+            Semantics::Pdag => at_a != Mark::Arrow,
+            Semantics::Ancestral => at_a != Mark::Arrow && at_b != Mark::Tail,
+        }
+    }
+
+    /// Nodes reachable from `seeds` along unshielded possibly directed paths
+    /// that never enter `avoid` (forward), or that reach `seeds` that way
+    /// (backward). Seeds are included.
+    fn reach(&self, seeds: &[u32], avoid: &[bool], forward: bool) -> Vec<bool> {
+        let mut out = vec![false; self.adj.len()];
+        for &s in seeds {
+            out[s as usize] = true;
+        }
+        let start = seeds.iter().map(|&s| (None, s)).collect();
+        self.walk(start, avoid, forward, &mut out);
+        out
+    }
+
+    /// Continue the walks in `stack` (`(previous, current)` pairs), marking
+    /// every node reached in `out`.
+    fn walk(
+        &self,
+        mut stack: Vec<(Option<u32>, u32)>,
+        avoid: &[bool],
+        forward: bool,
+        out: &mut [bool],
+    ) {
+        let mut seen: HashSet<(u32, u32)> = HashSet::new();
+        while let Some((prev, cur)) = stack.pop() {
+            for &(w, at_cur, at_w) in &self.adj[cur as usize] {
+                if avoid[w as usize] || Some(w) == prev {
+                    continue;
+                }
+                let ok = if forward {
+                    self.possibly_directed(at_cur, at_w)
+                } else {
+                    self.possibly_directed(at_w, at_cur)
+                };
+                if !ok || prev.is_some_and(|p| self.adjacent(p, w)) {
+                    continue;
+                }
+                if seen.insert((cur, w)) {
+                    out[w as usize] = true;
+                    stack.push((Some(cur), w));
+                }
+            }
+        }
+    }
+
+    /// Nodes reachable from `x` along unshielded possibly directed paths
+    /// starting with the edge `x *-* w` (`w` included, `x` excluded).
+    pub(crate) fn possible_descendants_through(&self, x: u32, w: u32) -> Vec<bool> {
+        let mut out = vec![false; self.adj.len()];
+        if self
+            .marks(x, w)
+            .is_some_and(|(at_x, at_w)| self.possibly_directed(at_x, at_w))
+        {
+            let mut avoid = vec![false; self.adj.len()];
+            avoid[x as usize] = true;
+            out[w as usize] = true;
+            self.walk(vec![(Some(x), w)], &avoid, true, &mut out);
+        }
+        out
+    }
+
+    /// Possible descendants of `seeds` (seeds included).
+    pub(crate) fn possible_descendants_mask(&self, seeds: &[u32], avoid: &[bool]) -> Vec<bool> {
+        self.reach(seeds, avoid, true)
+    }
+
+    /// Possible ancestors of `seeds` (seeds included).
+    pub(crate) fn possible_ancestors_mask(&self, seeds: &[u32], avoid: &[bool]) -> Vec<bool> {
+        self.reach(seeds, avoid, false)
+    }
+
+    /// Whether the directed edge `x → d` is visible (Zhang, 2008): some `c`
+    /// not adjacent to `d` has an arrowhead into `x`, directly or through a
+    /// collider path into `x` whose inner nodes are all parents of `d`.
+    fn is_visible(&self, x: u32, d: u32) -> bool {
+        let mut seen = vec![false; self.adj.len()];
+        seen[x as usize] = true;
+        let mut stack = vec![x];
+        while let Some(v) = stack.pop() {
+            for &(c, at_v, at_c) in &self.adj[v as usize] {
+                if at_v != Mark::Arrow || c == d || seen[c as usize] {
+                    continue;
+                }
+                if !self.adjacent(c, d) {
+                    return true;
+                }
+                if at_c == Mark::Arrow && self.marks(c, d) == Some((Mark::Tail, Mark::Arrow)) {
+                    seen[c as usize] = true;
+                    stack.push(c);
+                }
+            }
+        }
+        false
+    }
+}
+
+fn check_nodes(n: u32, nodes: &[u32]) -> Result<(), String> {
+    match nodes.iter().find(|&&v| v >= n) {
+        Some(v) => Err(format!("Index {} is out of bounds (n = {})", v, n)),
+        None => Ok(()),
+    }
+}
+
+/// Amenability check plus the forbidden and causal node sets for `(xs, ys)`.
+pub(crate) fn analyse(
+    g: &MarkedGraph,
+    xs: &[u32],
+    ys: &[u32],
+) -> Result<Amenability<GacSets>, String> {
+    let n = g.n();
+    check_nodes(n, xs)?;
+    check_nodes(n, ys)?;
+    if xs.is_empty() || ys.is_empty() {
+        return Err("adjustment requires non-empty X and Y".into());
+    }
+    let x_mask = bitset::mask_from(xs, n);
+    if ys.iter().any(|&y| x_mask[y as usize]) {
+        return Err("X and Y must be disjoint".into());
+    }
+
+    let an_y = g.possible_ancestors_mask(ys, &x_mask);
+    for &x in xs {
+        for &(w, at_x, at_w) in &g.adj[x as usize] {
+            if x_mask[w as usize] || !an_y[w as usize] || !g.possibly_directed(at_x, at_w) {
+                continue;
+            }
+            let directed = at_x == Mark::Tail
+                && at_w == Mark::Arrow
+                && (g.sem == Semantics::Pdag || g.is_visible(x, w));
+            if !directed {
+                return Ok(Amenability::NotAmenable { x, first: w });
+            }
+        }
+    }
+
+    let de_x = g.possible_descendants_mask(xs, &x_mask);
+    let causal: Vec<bool> = (0..n as usize)
+        .map(|v| de_x[v] && an_y[v] && !x_mask[v])
+        .collect();
+    let none = vec![false; n as usize];
+    let mut forbidden = g.possible_descendants_mask(&bitset::collect_from_mask(&causal), &none);
+    for &x in xs {
+        forbidden[x as usize] = true;
+    }
+    Ok(Amenability::Amenable(GacSets { forbidden, causal }))
+}
+
+/// Conditions 2 and 3 for a candidate `z` of an amenable query.
+pub(crate) fn is_valid<F>(sets: &GacSets, ys: &[u32], z: &[u32], blocks: F) -> Result<bool, String>
+where
+    F: Fn(&[u32]) -> bool,
+{
+    check_nodes(sets.forbidden.len() as u32, z)?;
+    if z.iter()
+        .any(|&v| sets.forbidden[v as usize] || ys.contains(&v))
+    {
+        return Ok(false);
+    }
+    Ok(blocks(z))
+}
+
+/// Enumerate valid adjustment sets up to `max_size` among `V \ (Forb ∪ Y)`.
+/// If `minimal` is true, return only inclusion-minimal sets.
+pub(crate) fn all_sets<F>(
+    sets: &GacSets,
+    ys: &[u32],
+    minimal: bool,
+    max_size: u32,
+    blocks: F,
+) -> Vec<Vec<u32>>
+where
+    F: Fn(&[u32]) -> bool,
+{
+    let universe: Vec<u32> = (0..sets.forbidden.len() as u32)
+        .filter(|v| !sets.forbidden[*v as usize] && !ys.contains(v))
+        .collect();
+    let mut acc = Vec::new();
+    let mut cur = Vec::new();
+    for k in 0..=(max_size as usize).min(universe.len()) {
+        subsets::k_subsets(&universe, k, 0, &mut cur, &mut acc);
+    }
+    acc.retain(|z| blocks(z));
+    if minimal {
+        subsets::prune_minimal(&mut acc);
+    }
+    acc
+}
+
+/// `Adjust(X, Y) = PossAn(X ∪ Y) \ (X ∪ Y ∪ Forb)`, which is a valid
+/// adjustment set whenever one exists (Perković et al., 2018).
+pub(crate) fn canonical(g: &MarkedGraph, sets: &GacSets, xs: &[u32], ys: &[u32]) -> Vec<u32> {
+    let mut seeds = xs.to_vec();
+    seeds.extend_from_slice(ys);
+    let none = vec![false; g.adj.len()];
+    let mut keep = g.possible_ancestors_mask(&seeds, &none);
+    for (v, k) in keep.iter_mut().enumerate() {
+        *k &= !sets.forbidden[v] && !ys.contains(&(v as u32));
+    }
+    bitset::collect_from_mask(&keep)
+}
+
+/// Error for an O-set that fails the criterion although a valid adjustment
+/// set exists; this can only happen when `Y` is not within the (possible)
+/// descendants of `X`.
+pub(crate) const OPTIMAL_UNDEFINED: &str =
+    "optimal adjustment set is undefined: some nodes of Y are not descendants of X, \
+     but a valid adjustment set exists";
+
+/// `O(X, Y) = Pa(cn(X, Y)) \ (Forb(X, Y) ∪ Y)` over definite (`-->`) parents.
+pub(crate) fn optimal(g: &MarkedGraph, sets: &GacSets, ys: &[u32]) -> Vec<u32> {
+    let mut keep = vec![false; g.adj.len()];
+    for c in bitset::collect_from_mask(&sets.causal) {
+        for &(p, at_c, at_p) in &g.adj[c as usize] {
+            if at_c == Mark::Arrow && at_p == Mark::Tail && !sets.forbidden[p as usize] {
+                keep[p as usize] = true;
+            }
+        }
+    }
+    for &y in ys {
+        keep[y as usize] = false;
+    }
+    bitset::collect_from_mask(&keep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+
+    fn graph(n: u32, edges: &[(u32, u32, &str)]) -> CaugiGraph {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        b.finalize().unwrap()
+    }
+
+    #[test]
+    fn unshielded_reach_skips_shielded_shortcuts() {
+        // 0 --- 1 --- 2 with 0 --- 2: 0 reaches 2 directly, and 1 via 0 --- 1.
+        let core = graph(3, &[(0, 1, "---"), (1, 2, "---"), (0, 2, "---")]);
+        let g = MarkedGraph::new(&core, Semantics::Pdag);
+        let none = vec![false; 3];
+        assert_eq!(g.possible_descendants_mask(&[0], &none), vec![true; 3]);
+        // Avoiding 1 still leaves the direct edge.
+        let avoid = vec![false, true, false];
+        assert_eq!(
+            g.possible_descendants_mask(&[0], &avoid),
+            vec![true, false, true]
+        );
+    }
+
+    #[test]
+    fn visibility_needs_an_arrowhead_from_a_non_neighbour() {
+        // 2 o-> 0 --> 1: visible because 2 is not adjacent to 1.
+        let core = graph(3, &[(2, 0, "o->"), (0, 1, "-->")]);
+        let g = MarkedGraph::new(&core, Semantics::Ancestral);
+        assert!(g.is_visible(0, 1));
+        // 0 --> 1 alone is not visible.
+        let core = graph(2, &[(0, 1, "-->")]);
+        let g = MarkedGraph::new(&core, Semantics::Ancestral);
+        assert!(!g.is_visible(0, 1));
+    }
+
+    #[test]
+    fn analyse_reports_undirected_first_edge() {
+        let core = graph(3, &[(0, 1, "---"), (1, 2, "-->")]);
+        let g = MarkedGraph::new(&core, Semantics::Pdag);
+        assert!(matches!(
+            analyse(&g, &[0], &[2]).unwrap(),
+            Amenability::NotAmenable { x: 0, first: 1 }
+        ));
+        assert!(analyse(&g, &[0], &[0]).is_err());
+        assert!(analyse(&g, &[0], &[3]).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT
+//! Small dense linear algebra on row-major `f64` matrices.
+//!
+//! Just enough for Gaussian scores and tests: a Cholesky factorisation that
+//! tolerates positive semi-definite input, and regressions on covariance
+//! submatrices built from it.
+
+/// Relative pivot below which a column is treated as linearly dependent.
+const PIVOT_TOL: f64 = 1e-12;
+
+/// Lower-triangular Cholesky factor `L` of the symmetric `k × k` matrix `a`,
+/// with `a = L Lᵀ`.
+///
+/// A pivot that is (numerically) zero marks a column that is a linear
+/// combination of the earlier ones; its column of `L` is left zero, so
+/// [`cholesky_solve`] returns the minimum-norm-free solution that ignores
+/// it. Returns `None` if `a` is not positive semi-definite.
+pub(crate) fn cholesky(a: &[f64], k: usize) -> Option<Vec<f64>> {
+    let scale = (0..k).map(|i| a[i * k + i].abs()).fold(0.0, f64::max);
+    let tol = PIVOT_TOL * scale.max(f64::MIN_POSITIVE);
+    let mut l = vec![0.0; k * k];
+    for j in 0..k {
+        let d = a[j * k + j] - (0..j).map(|t| l[j * k + t] * l[j * k + t]).sum::<f64>();
+        if d < -tol {
+            return None;
+        }
+        if d <= tol {
+            // A zero pivot in a PSD matrix forces the rest of its column to
+            // vanish: |a_ij|² ≤ a_jj · a_ii ≤ tol · scale after elimination.
+            let dependent = ((j + 1)..k).all(|i| {
+                let s = a[i * k + j] - (0..j).map(|t| l[i * k + t] * l[j * k + t]).sum::<f64>();
+                s * s <= tol * scale
+            });
+            if !dependent {
+                return None;
+            }
+            continue;
+        }
+        let ljj = d.sqrt();
+        l[j * k + j] = ljj;
+        for i in (j + 1)..k {
+            let s = a[i * k + j] - (0..j).map(|t| l[i * k + t] * l[j * k + t]).sum::<f64>();
+            l[i * k + j] = s / ljj;
+        }
+    }
+    Some(l)
+}
+
+/// Solve `L Lᵀ x = b` in place for a factor from [`cholesky`]; components
+/// on dependent columns are set to zero.
+pub(crate) fn cholesky_solve(l: &[f64], k: usize, b: &mut [f64]) {
+    for i in 0..k {
+        let lii = l[i * k + i];
+        let s = b[i] - (0..i).map(|t| l[i * k + t] * b[t]).sum::<f64>();
+        b[i] = if lii == 0.0 { 0.0 } else { s / lii };
+    }
+    for i in (0..k).rev() {
+        let lii = l[i * k + i];
+        let s = b[i] - ((i + 1)..k).map(|t| l[t * k + i] * b[t]).sum::<f64>();
+        b[i] = if lii == 0.0 { 0.0 } else { s / lii };
+    }
+}
+
+/// Submatrix of the `p × p` matrix `m` on `rows × cols`.
+pub(crate) fn submatrix(m: &[f64], p: usize, rows: &[u32], cols: &[u32]) -> Vec<f64> {
+    rows.iter()
+        .flat_map(|&r| cols.iter().map(move |&c| m[r as usize * p + c as usize]))
+        .collect()
+}
+
+/// Covariance of `a` and `b` left after regressing both on `set`, from the
+/// `p × p` covariance matrix `cov`: `Σ_ab − Σ_aS Σ_SS⁻¹ Σ_Sb`.
+///
+/// Returns `None` if `Σ_SS` is not positive semi-definite.
+pub(crate) fn residual_covariance(
+    cov: &[f64],
+    p: usize,
+    a: u32,
+    b: u32,
+    set: &[u32],
+) -> Option<f64> {
+    let k = set.len();
+    let l = cholesky(&submatrix(cov, p, set, set), k)?;
+    let mut x = submatrix(cov, p, set, &[b]);
+    cholesky_solve(&l, k, &mut x);
+    let explained: f64 = submatrix(cov, p, &[a], set)
+        .iter()
+        .zip(&x)
+        .map(|(ai, xi)| ai * xi)
+        .sum();
+    Some(cov[a as usize * p + b as usize] - explained)
+}
+
+/// Variance of `v` left after regressing it on `set`: `Σ_vv − Σ_vS Σ_SS⁻¹ Σ_Sv`.
+pub(crate) fn residual_variance(cov: &[f64], p: usize, v: u32, set: &[u32]) -> Option<f64> {
+    residual_covariance(cov, p, v, v, set)
+}
+
+/// Sample covariance (divisor `n − 1`) of the columns of the column-major
+/// `n × p` matrix `data`, as a `p × p` matrix.
+pub(crate) fn column_covariance(data: &[f64], n: usize, p: usize) -> Vec<f64> {
+    let col = |j: usize| &data[j * n..(j + 1) * n];
+    let means: Vec<f64> = (0..p)
+        .map(|j| col(j).iter().sum::<f64>() / n as f64)
+        .collect();
+    let mut cov = vec![0.0; p * p];
+    for i in 0..p {
+        for j in i..p {
+            let s: f64 = col(i)
+                .iter()
+                .zip(col(j))
+                .map(|(a, b)| (a - means[i]) * (b - means[j]))
+                .sum();
+            cov[i * p + j] = s / (n as f64 - 1.0);
+            cov[j * p + i] = cov[i * p + j];
+        }
+    }
+    cov
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cholesky_solves_and_tolerates_dependent_columns() {
+        // [[4, 2], [2, 3]] x = [2, 1]  =>  x = [0.5, 0]
+        let a = [4.0, 2.0, 2.0, 3.0];
+        let l = cholesky(&a, 2).unwrap();
+        let mut b = [2.0, 1.0];
+        cholesky_solve(&l, 2, &mut b);
+        assert!((b[0] - 0.5).abs() < 1e-12 && b[1].abs() < 1e-12);
+
+        // Second column duplicates the first: it is ignored.
+        let a = [1.0, 1.0, 1.0, 1.0];
+        let l = cholesky(&a, 2).unwrap();
+        let mut b = [2.0, 2.0];
+        cholesky_solve(&l, 2, &mut b);
+        assert_eq!(b, [2.0, 0.0]);
+        assert!(cholesky(&[1.0, 2.0, 2.0, 1.0], 2).is_none());
+        // A zero pivot with a nonzero entry below it is not PSD.
+        assert!(cholesky(&[0.0, 0.5, 0.5, 1.0], 2).is_none());
+        assert!(cholesky(&[1.0, 1.0, 0.0, 1.0, 1.0, 0.5, 0.0, 0.5, 1.0], 3).is_none());
+
+        // X1 = X0 + e, var(e) = 1: var(X1 | X0) = 1.
+        let cov = [1.0, 1.0, 1.0, 2.0];
+        assert!((residual_variance(&cov, 2, 1, &[0]).unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(residual_variance(&cov, 2, 1, &[]), Some(2.0));
+    }
+
+    #[test]
+    fn column_covariance_and_residual_covariance() {
+        // Columns x = 1..5 and y = (2, 1, 4, 3, 5): var 2.5 each, cov 2.
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0, 2.0, 1.0, 4.0, 3.0, 5.0];
+        let cov = column_covariance(&data, 5, 2);
+        assert_eq!(cov, vec![2.5, 2.0, 2.0, 2.5]);
+        // Given y, x keeps 2.5 - 2 * 2 / 2.5 = 0.9 of its variance.
+        let r = residual_covariance(&cov, 2, 0, 0, &[1]).unwrap();
+        assert!((r - 0.9).abs() < 1e-12);
+        assert!(residual_covariance(&cov, 2, 0, 1, &[1]).unwrap().abs() < 1e-12);
+    }
+}
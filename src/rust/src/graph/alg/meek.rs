@@ -175,7 +175,24 @@ pub(crate) fn apply_meek_closure(
             }
         }
 
-        // R4: a--b and (a ⇒ b or b ⇒ a)  =>  orient along reachability
+        // R4 (Meek): a--b, a--c, c->d->b, c !~ b, a ~ d  =>  a->b
+        for a in 0..n {
+            let uab: Vec<u32> = und[a].iter().copied().collect();
+            for b_u in uab {
+                let b = b_u as usize;
+                let fires = pa[b].iter().any(|&d| {
+                    adjacent(a, d as usize, und, pa, ch)
+                        && pa[d as usize]
+                            .iter()
+                            .any(|&c| und[a].contains(&c) && !adjacent(c as usize, b, und, pa, ch))
+                });
+                if fires && try_orient(a as u32, b_u, und, pa, ch) {
+                    changed = true;
+                }
+            }
+        }
+
+        // Acyclicity: a--b and (a ⇒ b or b ⇒ a)  =>  orient along reachability
         for a in 0..n {
             let uab: Vec<u32> = und[a].clone().into_iter().collect();
             for b_u in uab {
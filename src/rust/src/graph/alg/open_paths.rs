@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: MIT
+//! Open (d- or m-connecting) paths as witnesses of non-separation.
+//!
+//! A path between `X` and `Y` is open given `Z` if every non-collider on it is
+//! outside `Z` and every collider is an ancestor of `Z` (or in `Z`). Paths are
+//! simple, start in `X`, end in `Y`, and have no other node in `X ∪ Y`; every
+//! node on an open path lies in `An(X ∪ Y ∪ Z)`, so the search is confined to
+//! that set. A node in both `X` and `Y` but not in `Z` is an open path of
+//! length zero on its own.
+
+use crate::graph::alg::min_msep::MixedGraph;
+use std::collections::VecDeque;
+
+/// An edge on a path, read from `nodes[i]` to `nodes[i + 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathEdge {
+    /// `nodes[i] --> nodes[i + 1]`
+    Forward,
+    /// `nodes[i] <-- nodes[i + 1]`
+    Backward,
+    /// `nodes[i] <-> nodes[i + 1]`
+    Bidirected,
+    /// `nodes[i] --- nodes[i + 1]`
+    Undirected,
+}
+
+impl PathEdge {
+    #[inline]
+    fn arrow_at_start(self) -> bool {
+        matches!(self, PathEdge::Backward | PathEdge::Bidirected)
+    }
+
+    #[inline]
+    fn arrow_at_end(self) -> bool {
+        matches!(self, PathEdge::Forward | PathEdge::Bidirected)
+    }
+}
+
+/// A path that is open given a conditioning set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenPath {
+    pub nodes: Vec<u32>,
+    /// `edges[i]` joins `nodes[i]` and `nodes[i + 1]`.
+    pub edges: Vec<PathEdge>,
+    /// Each collider on the path, paired with the nearest conditioned node
+    /// that opens it: the collider itself if conditioned on, otherwise a
+    /// conditioned descendant.
+    pub opened_colliders: Vec<(u32, u32)>,
+}
+
+/// A shortest open path between `xs` and `ys` given `z`, if any.
+///
+/// Uses iterative deepening over simple paths, which is exponential in the
+/// worst case; callers should rule out separation with a linear-time test
+/// first.
+pub(crate) fn shortest_open_path<G: MixedGraph>(
+    g: &G,
+    xs: &[u32],
+    ys: &[u32],
+    z: &[u32],
+) -> Option<OpenPath> {
+    let search = Search::new(g, xs, ys, z);
+    (0..g.n() as usize).find_map(|len| {
+        let mut out = Vec::new();
+        search.run(len, true, &mut out);
+        out.pop()
+    })
+}
+
+/// Every open path between `xs` and `ys` given `z` with at most `max_len`
+/// edges, ordered by length and then by nodes and edges.
+pub(crate) fn open_paths<G: MixedGraph>(
+    g: &G,
+    xs: &[u32],
+    ys: &[u32],
+    z: &[u32],
+    max_len: usize,
+) -> Vec<OpenPath> {
+    let mut out = Vec::new();
+    Search::new(g, xs, ys, z).run(max_len, false, &mut out);
+    out.sort_by(|a, b| {
+        (a.nodes.len(), &a.nodes, &a.edges).cmp(&(b.nodes.len(), &b.nodes, &b.edges))
+    });
+    out
+}
+
+struct Search<'a, G: MixedGraph> {
+    g: &'a G,
+    starts: Vec<u32>,
+    in_x: Vec<bool>,
+    in_y: Vec<bool>,
+    in_z: Vec<bool>,
+    /// `An(Z) ∪ Z`: where a collider is open.
+    an_z: Vec<bool>,
+    /// `An(X ∪ Y ∪ Z)`: where an open path can run.
+    relevant: Vec<bool>,
+}
+
+impl<'a, G: MixedGraph> Search<'a, G> {
+    fn new(g: &'a G, xs: &[u32], ys: &[u32], z: &[u32]) -> Self {
+        let n = g.n() as usize;
+        let mask = |s: &[u32]| {
+            let mut m = vec![false; n];
+            for &v in s {
+                m[v as usize] = true;
+            }
+            m
+        };
+        let mut starts = xs.to_vec();
+        starts.sort_unstable();
+        starts.dedup();
+        let seeds: Vec<u32> = xs.iter().chain(ys).chain(z).copied().collect();
+        Self {
+            g,
+            starts,
+            in_x: mask(xs),
+            in_y: mask(ys),
+            in_z: mask(z),
+            an_z: g.anteriors_mask(z),
+            relevant: g.anteriors_mask(&seeds),
+        }
+    }
+
+    /// Incident edges of `v`, by neighbour and then edge kind.
+    fn incident(&self, v: u32) -> Vec<(u32, PathEdge)> {
+        let g = self.g;
+        let mut out: Vec<(u32, PathEdge)> = g
+            .children_of(v)
+            .iter()
+            .map(|&w| (w, PathEdge::Forward))
+            .chain(g.parents_of(v).iter().map(|&w| (w, PathEdge::Backward)))
+            .chain(g.spouses_of(v).iter().map(|&w| (w, PathEdge::Bidirected)))
+            .chain(
+                g.undirected_of(v)
+                    .iter()
+                    .map(|&w| (w, PathEdge::Undirected)),
+            )
+            .filter(|&(w, _)| self.relevant[w as usize])
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// Collect open paths of at most `max_len` edges; stop at the first one
+    /// of exactly `max_len` edges if `first_only`.
+    fn run(&self, max_len: usize, first_only: bool, out: &mut Vec<OpenPath>) {
+        let mut on_path = vec![false; self.g.n() as usize];
+        for &x in &self.starts {
+            if self.in_y[x as usize] {
+                if !self.in_z[x as usize] && (!first_only || max_len == 0) {
+                    out.push(self.finish(&[x], &[]));
+                    if first_only {
+                        return;
+                    }
+                }
+                continue;
+            }
+            if !self.relevant[x as usize] {
+                continue;
+            }
+            on_path[x as usize] = true;
+            let mut nodes = vec![x];
+            let mut edges = Vec::new();
+            let done = self.extend(
+                &mut nodes,
+                &mut edges,
+                &mut on_path,
+                max_len,
+                first_only,
+                out,
+            );
+            on_path[x as usize] = false;
+            if done {
+                return;
+            }
+        }
+    }
+
+    /// Returns `true` once a path is found in `first_only` mode.
+    fn extend(
+        &self,
+        nodes: &mut Vec<u32>,
+        edges: &mut Vec<PathEdge>,
+        on_path: &mut [bool],
+        max_len: usize,
+        first_only: bool,
+        out: &mut Vec<OpenPath>,
+    ) -> bool {
+        if edges.len() == max_len {
+            return false;
+        }
+        let cur = *nodes.last().unwrap();
+        for (next, e) in self.incident(cur) {
+            let ni = next as usize;
+            if on_path[ni] || self.in_x[ni] {
+                continue;
+            }
+            if let Some(&prev) = edges.last() {
+                let collider = prev.arrow_at_end() && e.arrow_at_start();
+                let open = if collider {
+                    self.an_z[cur as usize]
+                } else {
+                    !self.in_z[cur as usize]
+                };
+                if !open {
+                    continue;
+                }
+            }
+            nodes.push(next);
+            edges.push(e);
+            if self.in_y[ni] {
+                if !first_only || edges.len() == max_len {
+                    out.push(self.finish(nodes, edges));
+                    if first_only {
+                        return true;
+                    }
+                }
+            } else {
+                on_path[ni] = true;
+                let done = self.extend(nodes, edges, on_path, max_len, first_only, out);
+                on_path[ni] = false;
+                if done {
+                    return true;
+                }
+            }
+            nodes.pop();
+            edges.pop();
+        }
+        false
+    }
+
+    fn finish(&self, nodes: &[u32], edges: &[PathEdge]) -> OpenPath {
+        let opened_colliders = (1..nodes.len() - 1)
+            .filter(|&i| edges[i - 1].arrow_at_end() && edges[i].arrow_at_start())
+            .map(|i| (nodes[i], self.nearest_conditioned_descendant(nodes[i])))
+            .collect();
+        OpenPath {
+            nodes: nodes.to_vec(),
+            edges: edges.to_vec(),
+            opened_colliders,
+        }
+    }
+
+    /// Breadth-first over children from `c`; `c` is known to be in `An(Z) ∪ Z`.
+    fn nearest_conditioned_descendant(&self, c: u32) -> u32 {
+        let mut seen = vec![false; self.g.n() as usize];
+        seen[c as usize] = true;
+        let mut queue = VecDeque::from([c]);
+        while let Some(v) = queue.pop_front() {
+            if self.in_z[v as usize] {
+                return v;
+            }
+            for &w in self.g.children_of(v) {
+                if !seen[w as usize] {
+                    seen[w as usize] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+        unreachable!("an open collider has a conditioned descendant")
+    }
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT
+//! Seeded pseudo-random numbers for reproducible sampling.
+//!
+//! SplitMix64 (Steele, Lea & Flood, 2014): tiny, fast, and good enough for
+//! simulation; not for cryptographic use. The same seed yields the same
+//! stream on every platform.
+
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Uniform float in `[0, 1)` with 53 random bits.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Standard normal draw (Box–Muller; one of the pair is discarded).
+    pub(crate) fn standard_normal(&mut self) -> f64 {
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+
+    /// Uniform integer in `0..n`; `n` must be positive.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        debug_assert!(n > 0, "SplitMix64::below called with n = 0");
+        // Reject the top partial block so every residue is equally likely.
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// Fisher–Yates shuffle.
+    pub(crate) fn shuffle<T>(&mut self, xs: &mut [T]) {
+        for i in (1..xs.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            xs.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitmix_is_reproducible_and_covers_its_range() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        let xs: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+        assert_eq!(xs, ys);
+
+        let mut hits = [0usize; 5];
+        for _ in 0..5000 {
+            hits[a.below(5) as usize] += 1;
+        }
+        assert!(hits.iter().all(|&h| (800..1200).contains(&h)));
+
+        let mut v: Vec<u32> = (0..10).collect();
+        a.shuffle(&mut v);
+        let mut sorted = v.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn uniform_and_normal_draws_have_the_right_moments() {
+        let mut r = SplitMix64::new(9);
+        let m = 100_000;
+        let u: Vec<f64> = (0..m).map(|_| r.next_f64()).collect();
+        assert!(u.iter().all(|&x| (0.0..1.0).contains(&x)));
+        assert!((u.iter().sum::<f64>() / m as f64 - 0.5).abs() < 0.01);
+        let z: Vec<f64> = (0..m).map(|_| r.standard_normal()).collect();
+        let mean = z.iter().sum::<f64>() / m as f64;
+        let var = z.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / m as f64;
+        assert!(mean.abs() < 0.02 && (var - 1.0).abs() < 0.02);
+    }
+}
@@ -9,6 +9,10 @@
 //! composition over an `Mpdag` (same `PackedBuckets<3>` storage) plus a stronger
 //! invariant. Accessors are inherited from `Mpdag` (and thus `Pdag`) via `Deref`.
 
+mod mec;
+
+pub use mec::MecSize;
+
 use super::mpdag::Mpdag;
 use super::pdag::Pdag;
 use super::traits::{Acyclic, MeekClosed, NoBidirected};
@@ -2,7 +2,9 @@
 //! Adjustment sets, backdoor criterion, and d-separation for DAGs.
 
 use super::Dag;
-use crate::graph::alg::{bitset, reachability, subsets};
+use crate::graph::admg::{AdjustmentSets, Admg};
+use crate::graph::alg::open_paths::{self, OpenPath};
+use crate::graph::alg::{bitset, gac, reachability, subsets};
 use crate::graph::CaugiGraph;
 use std::sync::Arc;
 
@@ -91,42 +93,55 @@ impl Dag {
         }
     }
 
-    /// Optimal O-set for single exposure-outcome pair `x → y`.
+    /// Optimal adjustment set (O-set) for `Xs → Ys` (Henckel, Perković &
+    /// Maathuis, 2022).
     ///
     /// Definition:
-    /// - Let `Cn = (De(x) ∩ An(y)) ∪ {y if y ∈ De(x)}`.
-    /// - Return `Pa(Cn) \ (Cn ∪ {x})`.
-    pub fn adjustment_set_optimal(&self, x: u32, y: u32) -> Vec<u32> {
+    /// - Let `Cn` be the nodes outside `Xs` on proper causal paths from `Xs`
+    ///   to `Ys` (directed paths that meet `Xs` only at their first node).
+    /// - Return `Pa(Cn) \ (De(Cn) ∪ Xs ∪ Ys)`.
+    ///
+    /// When `Ys ⊆ De(Xs)`, the O-set is valid whenever any valid adjustment
+    /// set exists, which is always the case for a single exposure, and then
+    /// gives the smallest asymptotic variance among valid sets. Returns
+    /// `Ok(None)` if no valid adjustment set exists, and an error if one does
+    /// but the O-set is not valid, so that the O-set is undefined.
+    pub fn adjustment_set_optimal(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Option<Vec<u32>>, String> {
         let n = self.n() as usize;
+        let x_mask = bitset::mask_from(xs, self.n());
 
-        let mut de_mask = self.descendants_mask(&[x]);
-        de_mask[x as usize] = false;
-
-        let an_mask = self.ancestors_mask(&[y]);
+        let first_steps = xs.iter().flat_map(|&x| self.children_of(x)).copied();
+        let from_x = reach_avoiding(n, first_steps.collect(), &x_mask, |v| self.children_of(v));
+        let to_y = reach_avoiding(n, ys.to_vec(), &x_mask, |v| self.parents_of(v));
 
-        let mut cn_mask = vec![false; n];
-        for i in 0..n {
-            if de_mask[i] && an_mask[i] {
-                cn_mask[i] = true;
-            }
-        }
-        if de_mask[y as usize] {
-            cn_mask[y as usize] = true;
+        let cn: Vec<u32> = (0..self.n())
+            .filter(|&v| from_x[v as usize] && to_y[v as usize])
+            .collect();
+        let mut forb = self.descendants_mask(&cn);
+        for &v in xs.iter().chain(ys) {
+            forb[v as usize] = true;
         }
 
-        let mut pacn_mask = vec![false; n];
-        for v in bitset::collect_from_mask(&cn_mask) {
-            for &p in self.parents_of(v) {
-                pacn_mask[p as usize] = true;
+        let mut o_mask = vec![false; n];
+        for &c in &cn {
+            for &p in self.parents_of(c) {
+                if !forb[p as usize] {
+                    o_mask[p as usize] = true;
+                }
             }
         }
-        pacn_mask[x as usize] = false;
-        for i in 0..n {
-            if cn_mask[i] {
-                pacn_mask[i] = false;
-            }
+        let o = bitset::collect_from_mask(&o_mask);
+        if self.is_valid_adjustment_set(xs, ys, &o) {
+            Ok(Some(o))
+        } else if self.adjustment_set_canonical(xs, ys).is_some() {
+            Err(gac::OPTIMAL_UNDEFINED.into())
+        } else {
+            Ok(None)
         }
-        bitset::collect_from_mask(&pacn_mask)
     }
 
     /// d-separation test via ancestral reduction + moralization + BFS.
@@ -153,6 +168,27 @@ impl Dag {
         !reachability::reachable_to_any(&adj, &mask, xs, &blocked, ys)
     }
 
+    /// A shortest d-connecting path between `xs` and `ys` given `z`, or
+    /// `None` if they are d-separated.
+    pub fn d_connecting_path(&self, xs: &[u32], ys: &[u32], z: &[u32]) -> Option<OpenPath> {
+        if self.d_separated(xs, ys, z) {
+            return None;
+        }
+        open_paths::shortest_open_path(self, xs, ys, z)
+    }
+
+    /// Every d-connecting path between `xs` and `ys` given `z` with at most
+    /// `max_len` edges, shortest first.
+    pub fn d_connecting_paths(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+        max_len: usize,
+    ) -> Vec<OpenPath> {
+        open_paths::open_paths(self, xs, ys, z, max_len)
+    }
+
     /// Validates a proposed backdoor set `z` for pair `(x, y)`.
     ///
     /// Conditions:
@@ -211,13 +247,108 @@ impl Dag {
         Dag::new(Arc::new(core))
     }
 
-    /// Validates `z` as an adjustment set for `Xs → Ys` using the proper backdoor graph.
+    /// Validates `z` as an adjustment set for `Xs → Ys`: `z` avoids
+    /// `forb(Xs, Ys)` and d-separates `Xs` and `Ys` in the proper backdoor
+    /// graph.
     pub fn is_valid_adjustment_set(&self, xs: &[u32], ys: &[u32], z: &[u32]) -> bool {
+        let forbidden = self.forbidden_set(xs, ys);
+        if z.iter().any(|&v| forbidden[v as usize]) {
+            return false;
+        }
         self.proper_backdoor_graph(xs, ys)
             .map(|g| g.d_separated(xs, ys, z))
             .unwrap_or(false)
     }
 
+    /// Enumerate valid adjustment sets for `Xs → Ys` up to `max_size` among
+    /// `V \ (forb(Xs, Ys) ∪ Ys)`. If `minimal` is true, return only
+    /// inclusion-minimal sets.
+    pub fn all_adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+        max_size: u32,
+    ) -> Vec<Vec<u32>> {
+        let forbidden = self.forbidden_set(xs, ys);
+        let universe: Vec<u32> = (0..self.n())
+            .filter(|v| !forbidden[*v as usize] && !ys.contains(v))
+            .collect();
+        let mut acc = Vec::new();
+        let mut cur = Vec::new();
+        for k in 0..=(max_size as usize).min(universe.len()) {
+            subsets::k_subsets(&universe, k, 0, &mut cur, &mut acc);
+        }
+        acc.retain(|z| self.is_valid_adjustment_set(xs, ys, z));
+        if minimal {
+            subsets::prune_minimal(&mut acc);
+        }
+        acc
+    }
+
+    /// Canonical adjustment set `An(Xs ∪ Ys) \ (Xs ∪ Ys ∪ forb(Xs, Ys))`
+    /// (Perković et al., 2018), or `None` if no valid adjustment set exists.
+    pub fn adjustment_set_canonical(&self, xs: &[u32], ys: &[u32]) -> Option<Vec<u32>> {
+        let forbidden = self.forbidden_set(xs, ys);
+        let mut seeds = xs.to_vec();
+        seeds.extend_from_slice(ys);
+        let mut keep = self.ancestors_mask(&seeds);
+        for (v, k) in keep.iter_mut().enumerate() {
+            *k &= !forbidden[v] && !ys.contains(&(v as u32));
+        }
+        let z = bitset::collect_from_mask(&keep);
+        self.is_valid_adjustment_set(xs, ys, &z).then_some(z)
+    }
+
+    /// Lazily enumerate the valid (or only the minimal) adjustment sets for
+    /// `Xs → Ys` with polynomial delay; see [`Admg::adjustment_sets`].
+    pub fn adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+    ) -> Result<AdjustmentSets, String> {
+        Admg::new(Arc::new(self.core_ref().clone()))?.adjustment_sets(xs, ys, minimal)
+    }
+
+    /// Validates `m` as a front-door set for `x → y`; see
+    /// [`Admg::is_valid_front_door_set`].
+    pub fn is_valid_front_door_set(&self, x: u32, y: u32, m: &[u32]) -> bool {
+        Admg::new(Arc::new(self.core_ref().clone()))
+            .map(|g| g.is_valid_front_door_set(x, y, m))
+            .unwrap_or(false)
+    }
+
+    /// Find a front-door set for `x → y`; see [`Admg::front_door_set`].
+    pub fn front_door_set(&self, x: u32, y: u32) -> Option<Vec<u32>> {
+        Admg::new(Arc::new(self.core_ref().clone()))
+            .ok()?
+            .front_door_set(x, y)
+    }
+
+    /// Whether `z` is an instrument for `x → y` given `w`; see
+    /// [`Admg::is_instrument`].
+    pub fn is_instrument(&self, x: u32, y: u32, z: &[u32], w: &[u32]) -> bool {
+        Admg::new(Arc::new(self.core_ref().clone()))
+            .map(|g| g.is_instrument(x, y, z, w))
+            .unwrap_or(false)
+    }
+
+    /// All single-node instruments for `x → y` given `w`.
+    pub fn instruments(&self, x: u32, y: u32, w: &[u32]) -> Vec<u32> {
+        Admg::new(Arc::new(self.core_ref().clone()))
+            .map(|g| g.instruments(x, y, w))
+            .unwrap_or_default()
+    }
+
+    /// Ancestral instruments for `x → y` with their conditioning sets; see
+    /// [`Admg::ancestral_instruments`].
+    pub fn ancestral_instruments(&self, x: u32, y: u32) -> Vec<(u32, Vec<u32>)> {
+        Admg::new(Arc::new(self.core_ref().clone()))
+            .map(|g| g.ancestral_instruments(x, y))
+            .unwrap_or_default()
+    }
+
     /// Build the proper backdoor graph core for `Xs → Ys`.
     pub fn proper_backdoor_core(&self, xs: &[u32], ys: &[u32]) -> Result<CaugiGraph, String> {
         let reach = self.can_reach_any_y(ys);
@@ -226,6 +357,24 @@ impl Dag {
     }
 }
 
+/// Nodes reached from `seeds` by following `next`, never entering `avoid`.
+fn reach_avoiding<'a, F>(n: usize, seeds: Vec<u32>, avoid: &[bool], next: F) -> Vec<bool>
+where
+    F: Fn(u32) -> &'a [u32],
+{
+    let mut seen = vec![false; n];
+    let mut st = seeds;
+    while let Some(v) = st.pop() {
+        let vi = v as usize;
+        if seen[vi] || avoid[vi] {
+            continue;
+        }
+        seen[vi] = true;
+        st.extend_from_slice(next(v));
+    }
+    seen
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,7 +423,55 @@ mod tests {
         b.add_edge(3, 1, d).unwrap();
 
         let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
-        assert_eq!(g.adjustment_set_optimal(1, 2), vec![0]);
+        assert_eq!(g.adjustment_set_optimal(&[1], &[2]), Ok(Some(vec![0])));
+    }
+
+    #[test]
+    fn dag_optimal_adjustment_joint_intervention() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // X = {0, 1}, Y = {4}: 5 -> 0 -> 2 -> 4 <- 1 <- 3 -> 4, 6 -> 2.
+        let mut b = GraphBuilder::new_with_registry(7, true, &reg);
+        for (u, v) in [(5, 0), (0, 2), (2, 4), (1, 4), (3, 1), (3, 4), (6, 2)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+
+        let o = g.adjustment_set_optimal(&[0, 1], &[4]).unwrap().unwrap();
+        assert_eq!(o, vec![3, 6]);
+        assert!(g.is_valid_adjustment_set(&[0, 1], &[4], &o));
+        // 5 is not a descendant of 2, and the O-set leaves 2 <- 0 <- 5 open.
+        assert_eq!(g.adjustment_set_optimal(&[2], &[4]), Ok(Some(vec![1, 3])));
+        // 5 is not a descendant of 2: {0} is valid, but the O-set {1, 3, 6} is not.
+        assert!(g.is_valid_adjustment_set(&[2], &[4, 5], &[0]));
+        assert_eq!(
+            g.adjustment_set_canonical(&[2], &[4, 5]),
+            Some(vec![0, 1, 3, 6])
+        );
+        assert_eq!(
+            g.adjustment_set_optimal(&[2], &[4, 5]).unwrap_err(),
+            gac::OPTIMAL_UNDEFINED
+        );
+    }
+
+    #[test]
+    fn dag_optimal_adjustment_none_when_no_valid_set() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 0 -> 1 -> 2 -> 3, 1 -> 3 with X = {0, 2}: the backdoor path
+        // 2 <- 1 -> 3 can only be blocked by the forbidden node 1.
+        let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+        for (u, v) in [(0, 1), (1, 2), (2, 3), (1, 3)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert_eq!(g.adjustment_set_canonical(&[0, 2], &[3]), None);
+        assert_eq!(g.adjustment_set_optimal(&[0, 2], &[3]), Ok(None));
+        assert_eq!(g.adjustment_set_optimal(&[2], &[3]), Ok(Some(vec![1])));
     }
 
     #[test]
@@ -344,6 +541,83 @@ mod tests {
         assert!(g.is_valid_adjustment_set(&[1], &[2], &[0]));
     }
 
+    #[test]
+    fn dag_adjustment_sets_avoid_forbidden_nodes() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 3 -> 0 -> 1 -> 2, 3 -> 2, 1 -> 4: 4 descends from the mediator 1.
+        let mut b = GraphBuilder::new_with_registry(5, true, &reg);
+        for (u, v) in [(3, 0), (0, 1), (1, 2), (3, 2), (1, 4)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert!(g.is_valid_adjustment_set(&[0], &[2], &[3]));
+        assert!(!g.is_valid_adjustment_set(&[0], &[2], &[3, 4]));
+        assert_eq!(g.all_adjustment_sets(&[0], &[2], false, 5), vec![vec![3]]);
+        assert_eq!(g.adjustment_set_canonical(&[0], &[2]), Some(vec![3]));
+    }
+
+    #[test]
+    fn dag_adjustment_sets_iterator_matches_validity_checks() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 2 -> 0 -> 1, 2 -> 3 -> 1, 4 -> 3, 0 -> 5 -> 1
+        let mut b = GraphBuilder::new_with_registry(6, true, &reg);
+        for (u, v) in [(2, 0), (0, 1), (2, 3), (3, 1), (4, 3), (0, 5), (5, 1)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+
+        let mut minimal: Vec<_> = g.adjustment_sets(&[0], &[1], true).unwrap().collect();
+        minimal.sort();
+        assert_eq!(minimal, vec![vec![2], vec![3]]);
+
+        let all: Vec<_> = g.adjustment_sets(&[0], &[1], false).unwrap().collect();
+        assert_eq!(all.len(), 6);
+        assert!(all.iter().all(|z| g.is_valid_adjustment_set(&[0], &[1], z)));
+        assert!(!all.iter().any(|z| z.contains(&5)));
+    }
+
+    #[test]
+    fn dag_front_door_with_unobserved_confounder_as_node() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 3 -> 0 -> 1 -> 2, 3 -> 2: 3 plays the role of a latent confounder.
+        let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+        for (u, v) in [(3, 0), (0, 1), (1, 2), (3, 2)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert!(g.is_valid_front_door_set(0, 2, &[1]));
+        assert!(!g.is_valid_front_door_set(0, 2, &[1, 3]));
+        assert_eq!(g.front_door_set(0, 2), Some(vec![1]));
+    }
+
+    #[test]
+    fn dag_instruments_given_observed_confounder() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 2 -> 0 -> 1, 3 -> 2, 3 -> 1, 4 -> 0, 4 -> 1.
+        let mut b = GraphBuilder::new_with_registry(5, true, &reg);
+        for (u, v) in [(2, 0), (0, 1), (3, 2), (3, 1), (4, 0), (4, 1)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert!(!g.is_instrument(0, 1, &[2], &[]));
+        assert!(g.is_instrument(0, 1, &[2], &[3]));
+        assert!(g.is_instrument(0, 1, &[2], &[3, 4]));
+        assert_eq!(g.instruments(0, 1, &[3, 4]), vec![2]);
+        assert_eq!(g.ancestral_instruments(0, 1), vec![(2, vec![3, 4])]);
+    }
+
     #[test]
     fn dag_d_separated_empty_x_or_y_trivially_true() {
         let mut reg = EdgeRegistry::new();
@@ -474,4 +748,71 @@ mod tests {
         assert_eq!(adj[0], vec![2]);
         assert!(adj[1].is_empty());
     }
+
+    #[test]
+    fn d_connecting_path_explains_an_opened_collider() {
+        use crate::graph::alg::open_paths::PathEdge::{Backward, Forward};
+
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 0 -> 2 <- 1, 2 -> 3, 0 -> 4 -> 1
+        let mut b = GraphBuilder::new_with_registry(5, true, &reg);
+        for (u, v) in [(0, 2), (1, 2), (2, 3), (0, 4), (4, 1)] {
+            b.add_edge(u, v, d).unwrap();
+        }
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert_eq!(g.d_connecting_path(&[0], &[1], &[4]), None);
+
+        // Conditioning on 3 opens the collider 2.
+        let p = g.d_connecting_path(&[0], &[1], &[3, 4]).unwrap();
+        assert_eq!(p.nodes, vec![0, 2, 1]);
+        assert_eq!(p.edges, vec![Forward, Backward]);
+        assert_eq!(p.opened_colliders, vec![(2, 3)]);
+
+        let all = g.d_connecting_paths(&[0], &[1], &[3], 4);
+        let nodes: Vec<Vec<u32>> = all.iter().map(|p| p.nodes.clone()).collect();
+        assert_eq!(nodes, vec![vec![0, 2, 1], vec![0, 4, 1]]);
+        assert!(all[1].opened_colliders.is_empty());
+        assert!(g.d_connecting_paths(&[0], &[1], &[3], 1).is_empty());
+    }
+
+    #[test]
+    fn d_connecting_path_of_a_node_in_both_sets_is_trivial() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+
+        // 0 -> 1 -> 2 with X = {0, 1}, Y = {1, 2}.
+        let mut b = GraphBuilder::new_with_registry(3, true, &reg);
+        b.add_edge(0, 1, d).unwrap();
+        b.add_edge(1, 2, d).unwrap();
+        let g = Dag::new(Arc::new(b.finalize().unwrap())).unwrap();
+
+        assert!(!g.d_separated(&[0, 1], &[1, 2], &[]));
+        let p = g.d_connecting_path(&[0, 1], &[1, 2], &[]).unwrap();
+        assert_eq!(p.nodes, vec![1]);
+        assert!(p.edges.is_empty() && p.opened_colliders.is_empty());
+        let nodes: Vec<Vec<u32>> = g
+            .d_connecting_paths(&[0, 1], &[1, 2], &[], 2)
+            .into_iter()
+            .map(|p| p.nodes)
+            .collect();
+        // 0 -> 1 and 1 -> 2 touch X ∪ Y twice, so only the trivial path remains.
+        assert_eq!(nodes, vec![vec![1]]);
+
+        // Conditioning on the shared node blocks it, and the sets separate.
+        assert!(g.d_separated(&[0, 1], &[1, 2], &[1]));
+        assert_eq!(g.d_connecting_path(&[0, 1], &[1, 2], &[1]), None);
+
+        // A single node is connected to itself.
+        let g = Dag::new(Arc::new(
+            GraphBuilder::new_with_registry(1, true, &reg)
+                .finalize()
+                .unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(g.d_connecting_path(&[0], &[0], &[]).unwrap().nodes, vec![0]);
+    }
 }
@@ -3,8 +3,11 @@
 
 mod adjustment;
 mod separation;
+mod simulate;
 mod transforms;
 
+pub use simulate::NoiseFamily;
+
 use super::error::DagError;
 use super::packed::{PackedBuckets, PackedBucketsBuilder};
 use super::CaugiGraph;
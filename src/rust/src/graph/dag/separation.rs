@@ -3,7 +3,11 @@
 
 use super::Dag;
 use crate::graph::alg::bitset;
+use crate::graph::alg::do_calculus::{self, DoCalculusRules};
+use crate::graph::alg::implied::ConditionalIndependence;
+use crate::graph::alg::min_msep::MixedGraph;
 use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
 
 /// Direction of traversal in Bayes-ball algorithm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,6 +18,27 @@ enum Direction {
     Down,
 }
 
+impl MixedGraph for Dag {
+    fn n(&self) -> u32 {
+        Dag::n(self)
+    }
+    fn parents_of(&self, v: u32) -> &[u32] {
+        Dag::parents_of(self, v)
+    }
+    fn children_of(&self, v: u32) -> &[u32] {
+        Dag::children_of(self, v)
+    }
+    fn spouses_of(&self, _v: u32) -> &[u32] {
+        &[]
+    }
+    fn undirected_of(&self, _v: u32) -> &[u32] {
+        &[]
+    }
+    fn anteriors_mask(&self, seeds: &[u32]) -> Vec<bool> {
+        self.ancestors_mask(seeds)
+    }
+}
+
 /// Validates that all node IDs are within bounds [0, n).
 ///
 /// Returns `Err` with a descriptive message if any node ID is out of bounds.
@@ -338,6 +363,56 @@ impl Dag {
         // Step 9: Return Z
         Ok(Some(bitset::collect_from_mask(&z_mask)))
     }
+
+    /// Check which of Pearl's do-calculus rules apply to the query
+    /// `(xs, ys, zs, ws)`, by d-separation in the mutilated DAGs.
+    ///
+    /// See [`do_calculus`](crate::graph::alg::do_calculus) for the rules.
+    /// The four sets must be disjoint, and `ys` and `zs` non-empty.
+    pub fn do_calculus_rules(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+        ws: &[u32],
+    ) -> Result<DoCalculusRules, String> {
+        do_calculus::check_rules(
+            self.core_ref(),
+            |v| self.parents_of(v),
+            xs,
+            ys,
+            zs,
+            ws,
+            |g, ys, zs, cond| Ok(Dag::new(Arc::new(g))?.d_separated(ys, zs, cond)),
+        )
+    }
+
+    /// The local Markov basis: `v ⊥ nd(v) \ pa(v) | pa(v)` for each node `v`
+    /// with a non-empty right-hand side, ordered by `v`.
+    ///
+    /// Together these imply every d-separation in the DAG.
+    pub fn implied_independencies(&self) -> Vec<ConditionalIndependence> {
+        let n = self.n();
+        (0..n)
+            .filter_map(|v| {
+                let mut excluded = vec![false; n as usize];
+                excluded[v as usize] = true;
+                for w in self
+                    .descendants_of(v)
+                    .into_iter()
+                    .chain(self.parents_of(v).iter().copied())
+                {
+                    excluded[w as usize] = true;
+                }
+                let y: Vec<u32> = (0..n).filter(|&w| !excluded[w as usize]).collect();
+                (!y.is_empty()).then(|| ConditionalIndependence {
+                    x: vec![v],
+                    y,
+                    z: self.parents_of(v).to_vec(),
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -345,7 +420,6 @@ mod tests {
     use super::*;
     use crate::edges::EdgeRegistry;
     use crate::graph::builder::GraphBuilder;
-    use std::sync::Arc;
 
     fn build_dag(edges: &[(u32, u32)], n: u32) -> Dag {
         let mut reg = EdgeRegistry::new();
@@ -576,4 +650,47 @@ mod tests {
         // Out-of-bounds restrict
         assert!(g.minimal_d_separator(&[0], &[1], &[], &[5]).is_err());
     }
+
+    #[test]
+    fn do_calculus_rules_backdoor_dag() {
+        // 0:Z --> 1:X --> 2:Y, Z --> Y
+        let g = build_dag(&[(0, 1), (1, 2), (0, 2)], 3);
+        // P(y | do(x), z) = P(y | x, z): rule 2 with X = ∅, Z = {x}, W = {z}
+        let r = g.do_calculus_rules(&[], &[2], &[1], &[0]).unwrap();
+        assert!(r.rule2);
+        assert!(!r.rule1);
+        assert!(!r.rule3);
+        // Without conditioning on the confounder the exchange is invalid
+        assert!(!g.do_calculus_rules(&[], &[2], &[1], &[]).unwrap().rule2);
+        // do(y) has no effect on x: rule 3
+        assert!(g.do_calculus_rules(&[], &[1], &[2], &[]).unwrap().rule3);
+    }
+
+    #[test]
+    fn do_calculus_rules_validate_sets() {
+        let g = build_dag(&[(0, 1)], 2);
+        assert!(g.do_calculus_rules(&[0], &[1], &[0], &[]).is_err());
+        assert!(g.do_calculus_rules(&[], &[1], &[], &[]).is_err());
+        assert!(g.do_calculus_rules(&[], &[1], &[2], &[]).is_err());
+    }
+
+    #[test]
+    fn implied_independencies_local_markov_basis() {
+        // 0 -> 1 -> 2, 0 -> 3
+        let dag = build_dag(&[(0, 1), (1, 2), (0, 3)], 4);
+        let ci: Vec<(Vec<u32>, Vec<u32>, Vec<u32>)> = dag
+            .implied_independencies()
+            .into_iter()
+            .map(|c| (c.x, c.y, c.z))
+            .collect();
+        assert_eq!(
+            ci,
+            vec![
+                (vec![1], vec![3], vec![0]),
+                (vec![2], vec![0, 3], vec![1]),
+                (vec![3], vec![1, 2], vec![0]),
+            ]
+        );
+        assert!(ci.iter().all(|(x, y, z)| dag.d_separated(x, y, z)));
+    }
 }
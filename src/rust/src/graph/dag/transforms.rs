@@ -2,9 +2,10 @@
 //! Graph transformations for DAGs.
 
 use super::Dag;
-use crate::edges::EdgeClass;
+use crate::edges::{EdgeClass, Mark};
 use crate::graph::admg::Admg;
-use crate::graph::alg::{csr, meek};
+use crate::graph::ag::Ag;
+use crate::graph::alg::{bitset, csr, meek};
 use crate::graph::cpdag::Cpdag;
 use crate::graph::mpdag::Mpdag;
 use crate::graph::pdag::Pdag;
@@ -652,6 +653,97 @@ impl Dag {
         Admg::new(Arc::new(core))
     }
 
+    /// Project a DAG with latent and selection variables onto its observed
+    /// nodes as a maximal ancestral graph (MAG).
+    ///
+    /// Two observed nodes `a` and `b` are adjacent in the MAG iff the DAG has
+    /// an inducing path between them relative to `⟨latents, selected⟩`, which
+    /// holds iff they are d-connected given
+    /// `(An({a, b} ∪ S) \ (L ∪ S ∪ {a, b})) ∪ S`. Each adjacency is then
+    /// oriented from anterior relations: `a` gets a tail iff `a ∈ An({b} ∪ S)`
+    /// and an arrowhead otherwise, giving `-->`, `<->` or `---`.
+    ///
+    /// This replaces the subset search over conditioning sets with one
+    /// d-separation test per pair of observed nodes.
+    ///
+    /// # Arguments
+    /// * `latents` - Node indices to marginalize out (0-indexed)
+    /// * `selected` - Node indices to condition on (0-indexed)
+    ///
+    /// # Returns
+    /// An `Ag` over the remaining nodes, in their original relative order.
+    ///
+    /// # Errors
+    /// Returns an error if an index is out of bounds or a node is both latent
+    /// and selected.
+    ///
+    /// # References
+    ///
+    /// T. Richardson and P. Spirtes (2002). Ancestral graph Markov models.
+    /// *The Annals of Statistics*, 30(4), 962–1030.
+    pub fn to_mag(&self, latents: &[u32], selected: &[u32]) -> Result<Ag, String> {
+        let n = self.n() as usize;
+
+        let mut latent = vec![false; n];
+        for &v in latents {
+            if v >= self.n() {
+                return Err(format!(
+                    "Latent index {} is out of bounds (n = {})",
+                    v,
+                    self.n()
+                ));
+            }
+            latent[v as usize] = true;
+        }
+        let mut sel = vec![false; n];
+        for &v in selected {
+            if v >= self.n() {
+                return Err(format!(
+                    "Selected index {} is out of bounds (n = {})",
+                    v,
+                    self.n()
+                ));
+            }
+            if latent[v as usize] {
+                return Err(format!("Node {} cannot be both latent and selected", v));
+            }
+            sel[v as usize] = true;
+        }
+
+        let kept: Vec<u32> = (0..self.n())
+            .filter(|&u| !latent[u as usize] && !sel[u as usize])
+            .collect();
+        let sel_nodes = bitset::collect_from_mask(&sel);
+        let an_sel = self.ancestors_mask(&sel_nodes);
+        let an_kept: Vec<Vec<bool>> = kept.iter().map(|&v| self.ancestors_mask(&[v])).collect();
+
+        let mut edges = Vec::new();
+        for i in 0..kept.len() {
+            for j in (i + 1)..kept.len() {
+                let (a, b) = (kept[i], kept[j]);
+                let (an_a, an_b) = (&an_kept[i], &an_kept[j]);
+
+                let mut z = sel_nodes.clone();
+                z.extend(kept.iter().enumerate().filter_map(|(k, &v)| {
+                    (k != i
+                        && k != j
+                        && (an_sel[v as usize] || an_a[v as usize] || an_b[v as usize]))
+                        .then_some(v)
+                }));
+                if self.d_separated(&[a], &[b], &z) {
+                    continue;
+                }
+
+                let mark = |tail: bool| if tail { Mark::Tail } else { Mark::Arrow };
+                let at_a = mark(an_b[a as usize] || an_sel[a as usize]);
+                let at_b = mark(an_a[b as usize] || an_sel[b as usize]);
+                edges.push((i as u32, j as u32, at_a, at_b));
+            }
+        }
+
+        Ag::from_marked_edges(&self.core_ref().registry, kept.len() as u32, edges)
+    }
+
     /// Convert DAG to CPDAG: the essential graph of the DAG's Markov
     /// equivalence class (skeleton, oriented v-structures, then Meek closure).
     ///
@@ -1666,6 +1758,89 @@ mod tests {
         assert!(!admg.core_ref().simple);
     }
 
+    // ── MAG projection tests ─────────────────────────────────────────────────
+
+    fn dag_from(n: u32, edges: &[(u32, u32)]) -> Dag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v) in edges {
+            b.add_edge(u, v, d).unwrap();
+        }
+        Dag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn to_mag_latent_confounder_becomes_bidirected() {
+        // 0:U -> 1:X, U -> 2:Y
+        let mag = dag_from(3, &[(0, 1), (0, 2)]).to_mag(&[0], &[]).unwrap();
+        assert_eq!(mag.n(), 2);
+        assert_eq!(mag.spouses_of(0), &[1]);
+        assert!(mag.parents_of(1).is_empty());
+    }
+
+    #[test]
+    fn to_mag_confounded_edge_stays_directed() {
+        // U -> X, U -> Y, X -> Y: X is an ancestor of Y, so X --> Y only
+        let mag = dag_from(3, &[(0, 1), (0, 2), (1, 2)])
+            .to_mag(&[0], &[])
+            .unwrap();
+        assert_eq!(mag.parents_of(1), &[0]);
+        assert!(mag.spouses_of(0).is_empty());
+    }
+
+    #[test]
+    fn to_mag_selection_on_common_child_becomes_undirected() {
+        // 0 -> 2 <- 1, select 2
+        let mag = dag_from(3, &[(0, 2), (1, 2)]).to_mag(&[], &[2]).unwrap();
+        assert_eq!(mag.n(), 2);
+        assert_eq!(mag.undirected_of(0), &[1]);
+    }
+
+    #[test]
+    fn to_mag_inducing_path_adds_adjacency() {
+        // 0:A -> 1:B <- 2:L -> 3:C, B -> C; A ... C via inducing path through B
+        let mag = dag_from(4, &[(0, 1), (2, 1), (2, 3), (1, 3)])
+            .to_mag(&[2], &[])
+            .unwrap();
+        // observed: A=0, B=1, C=2
+        assert_eq!(mag.children_of(0), &[1, 2]);
+        assert_eq!(mag.parents_of(2), &[0, 1]);
+        assert!(mag.spouses_of(1).is_empty());
+        assert!(mag.is_mag());
+    }
+
+    #[test]
+    fn to_mag_latent_chain_and_non_inducing_collider() {
+        // 0 -> 1:L -> 2; 3 -> 4 <- 5 with 4 latent: 3 and 5 stay separated
+        let mag = dag_from(6, &[(0, 1), (1, 2), (3, 4), (5, 4)])
+            .to_mag(&[1, 4], &[])
+            .unwrap();
+        // observed: 0, 2, 3, 5 -> new 0, 1, 2, 3
+        assert_eq!(mag.children_of(0), &[1]);
+        assert!(mag.neighbors_of(2).is_empty());
+        assert!(mag.neighbors_of(3).is_empty());
+    }
+
+    #[test]
+    fn to_mag_without_latents_is_the_dag() {
+        let dag = dag_from(4, &[(0, 1), (1, 2), (0, 3), (3, 2)]);
+        let mag = dag.to_mag(&[], &[]).unwrap();
+        for v in 0..4 {
+            assert_eq!(mag.parents_of(v), dag.parents_of(v));
+            assert_eq!(mag.children_of(v), dag.children_of(v));
+        }
+    }
+
+    #[test]
+    fn to_mag_rejects_bad_indices() {
+        let dag = dag_from(2, &[(0, 1)]);
+        assert!(dag.to_mag(&[2], &[]).is_err());
+        assert!(dag.to_mag(&[], &[5]).is_err());
+        assert!(dag.to_mag(&[0], &[0]).is_err());
+    }
+
     #[test]
     fn to_cpdag_and_latent_project_with_multiple_registry_specs() {
         let mut reg = EdgeRegistry::new();
@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: MIT
+//! Markov equivalence of two graphs, with a certificate when they differ.
+//!
+//! Two DAGs (or a CPDAG and a DAG) are Markov equivalent iff they share the
+//! skeleton and the unshielded colliders (Verma & Pearl, 1990). Two MAGs must
+//! in addition agree on the middle node of every path that is discriminating
+//! in both (Spirtes & Richardson, 1996). When the answer is no, the first
+//! differing adjacency or collider is returned as a witness.
+//!
+//! # References
+//!
+//! T. Verma and J. Pearl (1990). Equivalence and synthesis of causal models.
+//! *UAI 1990*, 255–270.
+//!
+//! P. Spirtes and T. Richardson (1996). A polynomial time algorithm for
+//! determining DAG equivalence in the presence of latent variables and
+//! selection bias. *AISTATS 1996*.
+
+use crate::edges::Mark;
+use crate::graph::alg::fci::MarkMatrix;
+use crate::graph::view::GraphView;
+use std::collections::VecDeque;
+
+/// Why two graphs are not Markov equivalent. Node indices refer to the first
+/// graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquivalenceWitness {
+    /// `u` and `v` are adjacent in exactly one of the graphs.
+    Adjacency { u: u32, v: u32, in_first: bool },
+    /// The second-to-last node of `path` is a collider in exactly one of the
+    /// graphs. `path` is `[a, c, b]` for an unshielded triple, or a path
+    /// `[θ, ..., a, c, b]` that is discriminating for `c` in both graphs.
+    Collider { path: Vec<u32>, in_first: bool },
+}
+
+/// Whether `a` and `b` are Markov equivalent, assuming the same node order.
+///
+/// Returns `None` if they are, and a witness otherwise. Supported pairs are
+/// DAG/DAG, CPDAG/DAG (either order, or two CPDAGs) and MAG/MAG, where a DAG
+/// also counts as a MAG.
+pub fn markov_equivalent(
+    a: &GraphView,
+    b: &GraphView,
+) -> Result<Option<EquivalenceWitness>, String> {
+    markov_equivalent_with_perm(a, b, &(0..a.n()).collect::<Vec<_>>())
+}
+
+/// Markov equivalence with a node permutation mapping.
+/// `perm[i]` gives the index in `b` that corresponds to node `i` in `a`.
+pub fn markov_equivalent_with_perm(
+    a: &GraphView,
+    b: &GraphView,
+    perm: &[u32],
+) -> Result<Option<EquivalenceWitness>, String> {
+    if a.n() != b.n() {
+        return Err("graph size mismatch".into());
+    }
+    if perm.len() as u32 != a.n() {
+        return Err("perm length mismatch".into());
+    }
+    let discriminating = pair_kind(a, b)?;
+
+    let ga = MarkMatrix::from_core(a.core());
+    let gb = {
+        let core = b.core();
+        let mut inv = vec![0u32; perm.len()];
+        for (i, &j) in perm.iter().enumerate() {
+            inv[j as usize] = i as u32;
+        }
+        let edges = (0..core.n()).flat_map(|u| {
+            let inv = &inv;
+            core.row_range(u).filter_map(move |k| {
+                let v = core.col_index[k];
+                (u < v).then(|| {
+                    (
+                        inv[u as usize],
+                        inv[v as usize],
+                        core.my_mark(k),
+                        core.nbr_mark(k),
+                    )
+                })
+            })
+        });
+        MarkMatrix::from_edges(perm.len(), edges)
+    };
+
+    let witness = skeleton_difference(&ga, &gb)
+        .or_else(|| unshielded_collider_difference(&ga, &gb))
+        .or_else(|| {
+            discriminating
+                .then(|| discriminated_collider_difference(&ga, &gb))
+                .flatten()
+        });
+    Ok(witness)
+}
+
+/// Whether the pair is compared as MAGs (with discriminating paths) or as
+/// DAGs/CPDAGs.
+fn pair_kind(a: &GraphView, b: &GraphView) -> Result<bool, String> {
+    use GraphView::{Ag, Cpdag, Dag};
+    match (a, b) {
+        (Dag(_) | Cpdag(_), Dag(_) | Cpdag(_)) => Ok(false),
+        (Ag(_) | Dag(_), Ag(_) | Dag(_)) => {
+            for g in [a, b] {
+                if let Ag(g) = g {
+                    if !g.is_mag() {
+                        return Err("markov_equivalent requires maximal ancestral graphs".into());
+                    }
+                }
+            }
+            Ok(true)
+        }
+        _ => {
+            Err("markov_equivalent is only defined for DAG/DAG, CPDAG/DAG and MAG/MAG pairs".into())
+        }
+    }
+}
+
+fn skeleton_difference(ga: &MarkMatrix, gb: &MarkMatrix) -> Option<EquivalenceWitness> {
+    let n = ga.n() as u32;
+    (0..n)
+        .flat_map(|u| ((u + 1)..n).map(move |v| (u, v)))
+        .find(|&(u, v)| ga.adjacent(u, v) != gb.adjacent(u, v))
+        .map(|(u, v)| EquivalenceWitness::Adjacency {
+            u,
+            v,
+            in_first: ga.adjacent(u, v),
+        })
+}
+
+/// `a *-> c <-* b`.
+#[inline]
+fn is_collider(g: &MarkMatrix, a: u32, c: u32, b: u32) -> bool {
+    g.is(a, c, Mark::Arrow) && g.is(b, c, Mark::Arrow)
+}
+
+/// Assumes equal skeletons.
+fn unshielded_collider_difference(ga: &MarkMatrix, gb: &MarkMatrix) -> Option<EquivalenceWitness> {
+    for c in 0..ga.n() as u32 {
+        let nb = ga.neighbors(c);
+        for (i, &a) in nb.iter().enumerate() {
+            for &b in &nb[i + 1..] {
+                if ga.adjacent(a, b) {
+                    continue;
+                }
+                let in_first = is_collider(ga, a, c, b);
+                if in_first != is_collider(gb, a, c, b) {
+                    return Some(EquivalenceWitness::Collider {
+                        path: vec![a, c, b],
+                        in_first,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Assumes equal skeletons and unshielded colliders.
+fn discriminated_collider_difference(
+    ga: &MarkMatrix,
+    gb: &MarkMatrix,
+) -> Option<EquivalenceWitness> {
+    let both = |f: &dyn Fn(&MarkMatrix) -> bool| f(ga) && f(gb);
+    for c in 0..ga.n() as u32 {
+        let nb = ga.neighbors(c);
+        for &b in nb {
+            for &a in nb {
+                // `a` is a collider on the path and a parent of `b` in both.
+                if a == b || !both(&|g| g.is(c, a, Mark::Arrow) && g.is_directed(a, b)) {
+                    continue;
+                }
+                let in_first = is_collider(ga, a, c, b);
+                if in_first == is_collider(gb, a, c, b) {
+                    continue;
+                }
+                if let Some(mut path) = discriminating_path(ga, gb, a, c, b) {
+                    path.extend([c, b]);
+                    return Some(EquivalenceWitness::Collider { path, in_first });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Shortest path `θ, ..., a` that extends `a, c, b` to a path discriminating
+/// for `c` in both graphs: every node after `θ` is a collider on the path and
+/// a parent of `b`, and `θ` is not adjacent to `b`.
+fn discriminating_path(
+    ga: &MarkMatrix,
+    gb: &MarkMatrix,
+    a: u32,
+    c: u32,
+    b: u32,
+) -> Option<Vec<u32>> {
+    let both = |f: &dyn Fn(&MarkMatrix) -> bool| f(ga) && f(gb);
+    let mut prev: Vec<Option<u32>> = vec![None; ga.n()];
+    let mut seen = vec![false; ga.n()];
+    for v in [a, b, c] {
+        seen[v as usize] = true;
+    }
+    let mut queue = VecDeque::from([a]);
+    while let Some(v) = queue.pop_front() {
+        for &w in ga.neighbors(v) {
+            if seen[w as usize] || !both(&|g| g.is(w, v, Mark::Arrow)) {
+                continue;
+            }
+            prev[w as usize] = Some(v);
+            if !ga.adjacent(w, b) {
+                let mut path = vec![w];
+                let mut cur = w;
+                while let Some(p) = prev[cur as usize] {
+                    path.push(p);
+                    cur = p;
+                }
+                return Some(path);
+            }
+            if both(&|g| g.is(v, w, Mark::Arrow) && g.is_directed(w, b)) {
+                seen[w as usize] = true;
+                queue.push_back(w);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::ag::Ag;
+    use crate::graph::builder::GraphBuilder;
+    use crate::graph::dag::Dag;
+    use crate::graph::CaugiGraph;
+    use std::sync::Arc;
+
+    fn core(n: u32, edges: &[(u32, u32, &str)]) -> Arc<CaugiGraph> {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Arc::new(b.finalize().unwrap())
+    }
+
+    fn dag(n: u32, edges: &[(u32, u32)]) -> GraphView {
+        let e: Vec<(u32, u32, &str)> = edges.iter().map(|&(u, v)| (u, v, "-->")).collect();
+        GraphView::Dag(Arc::new(Dag::new(core(n, &e)).unwrap()))
+    }
+
+    fn mag(n: u32, edges: &[(u32, u32, &str)]) -> GraphView {
+        GraphView::Ag(Arc::new(Ag::new(core(n, edges)).unwrap()))
+    }
+
+    #[test]
+    fn dags_compare_skeletons_and_v_structures() {
+        // 0 -> 1 -> 2 and 0 <- 1 <- 2 are equivalent; 0 -> 1 <- 2 is not.
+        let chain = dag(3, &[(0, 1), (1, 2)]);
+        assert_eq!(
+            markov_equivalent(&chain, &dag(3, &[(1, 0), (2, 1)])),
+            Ok(None)
+        );
+        assert_eq!(
+            markov_equivalent(&chain, &dag(3, &[(0, 1), (2, 1)])),
+            Ok(Some(EquivalenceWitness::Collider {
+                path: vec![0, 1, 2],
+                in_first: false,
+            }))
+        );
+        assert_eq!(
+            markov_equivalent(&chain, &dag(3, &[(0, 1), (1, 2), (0, 2)])),
+            Ok(Some(EquivalenceWitness::Adjacency {
+                u: 0,
+                v: 2,
+                in_first: false,
+            }))
+        );
+        // The node order of the second graph is given by `perm`:
+        // 2 -> 0 -> 1 in `b` is 0 -> 1 -> 2 after relabelling.
+        let b = dag(3, &[(2, 0), (0, 1)]);
+        assert_eq!(
+            markov_equivalent_with_perm(&chain, &b, &[2, 0, 1]),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn cpdag_is_equivalent_to_its_members() {
+        let d = dag(4, &[(0, 2), (1, 2), (2, 3)]);
+        let GraphView::Dag(g) = &d else {
+            unreachable!()
+        };
+        let c = GraphView::Cpdag(Arc::new(g.to_cpdag().unwrap()));
+        assert_eq!(markov_equivalent(&c, &d), Ok(None));
+        assert_eq!(markov_equivalent(&d, &c), Ok(None));
+        let other = dag(4, &[(0, 2), (2, 1), (2, 3)]);
+        assert_eq!(
+            markov_equivalent(&c, &other),
+            Ok(Some(EquivalenceWitness::Collider {
+                path: vec![0, 2, 1],
+                in_first: true,
+            }))
+        );
+        assert!(markov_equivalent(&c, &mag(4, &[])).is_err());
+    }
+
+    #[test]
+    fn mags_differ_on_a_discriminating_path() {
+        // 0 -> 1 <-> 2 <- 3 style: θ = 0, path 0 *-> 1 <-> 2 *-* 3 with 1 -> 3.
+        // In the first MAG 2 is a non-collider (2 -> 3), in the second a
+        // collider (1 <-> 2 <-> 3); skeleton and unshielded colliders agree.
+        let first = mag(
+            4,
+            &[(0, 1, "-->"), (1, 2, "<->"), (1, 3, "-->"), (2, 3, "-->")],
+        );
+        let second = mag(
+            4,
+            &[(0, 1, "-->"), (1, 2, "<->"), (1, 3, "-->"), (2, 3, "<->")],
+        );
+        assert_eq!(
+            markov_equivalent(&first, &second),
+            Ok(Some(EquivalenceWitness::Collider {
+                path: vec![0, 1, 2, 3],
+                in_first: false,
+            }))
+        );
+        assert_eq!(markov_equivalent(&first, &first), Ok(None));
+        // A DAG counts as a MAG.
+        let d = dag(3, &[(0, 1), (1, 2)]);
+        assert_eq!(
+            markov_equivalent(&d, &mag(3, &[(1, 0, "-->"), (1, 2, "-->")])),
+            Ok(None)
+        );
+    }
+}
@@ -143,6 +143,53 @@ impl std::fmt::Display for AgError {
 
 impl std::error::Error for AgError {}
 
+// ── PAG Errors ───────────────────────────────────────────────────────────────
+
+/// Errors that can occur when constructing or validating a Partial Ancestral Graph.
+#[derive(Debug, Clone)]
+pub enum PagError {
+    /// The definite directed part of the graph contains a cycle.
+    DirectedCycle,
+    /// An edge with an endpoint mark other than arrow, tail, or circle was found.
+    InvalidEdgeType { found: String },
+    /// More than one edge connects the same pair of nodes.
+    MultipleEdges { a: u32, b: u32 },
+    /// Anterior constraint violated: a node with an arrowhead is an anterior of the source.
+    AnteriorConstraintViolation { source: u32, target: u32 },
+    /// Undirected constraint violated: a node has both undirected and arrowhead edges.
+    UndirectedConstraintViolation { node: u32 },
+}
+
+impl std::fmt::Display for PagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DirectedCycle => write!(f, "PAG contains a directed cycle"),
+            Self::InvalidEdgeType { found } => write!(
+                f,
+                "PAG can only contain edges with arrow, tail, or circle marks, found: {}",
+                found
+            ),
+            Self::MultipleEdges { a, b } => write!(
+                f,
+                "PAG can contain at most one edge between nodes {} and {}",
+                a, b
+            ),
+            Self::AnteriorConstraintViolation { source, target } => write!(
+                f,
+                "Anterior constraint violated: node {} has arrowhead from {} but is an anterior of {}",
+                target, source, source
+            ),
+            Self::UndirectedConstraintViolation { node } => write!(
+                f,
+                "Undirected constraint violated: node {} has both undirected and arrowhead edges",
+                node
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PagError {}
+
 // ── Builder Errors ────────────────────────────────────────────────────────────
 
 /// Errors that can occur when building a graph.
@@ -337,6 +384,44 @@ mod tests {
         assert!(err.to_string().contains("node 2"));
     }
 
+    // ── PAG Error Tests ───────────────────────────────────────────────────────
+
+    #[test]
+    fn pag_error_display_directed_cycle() {
+        let err = PagError::DirectedCycle;
+        assert_eq!(err.to_string(), "PAG contains a directed cycle");
+    }
+
+    #[test]
+    fn pag_error_display_invalid_edge_type() {
+        let err = PagError::InvalidEdgeType {
+            found: "-x-".to_string(),
+        };
+        assert!(err
+            .to_string()
+            .contains("PAG can only contain edges with arrow, tail, or circle marks"));
+        assert!(err.to_string().contains("-x-"));
+    }
+
+    #[test]
+    fn pag_error_display_multiple_edges() {
+        let err = PagError::MultipleEdges { a: 1, b: 3 };
+        assert!(err.to_string().contains("at most one edge"));
+        assert!(err.to_string().contains("1 and 3"));
+    }
+
+    #[test]
+    fn pag_error_display_constraint_violations() {
+        let err = PagError::AnteriorConstraintViolation {
+            source: 0,
+            target: 1,
+        };
+        assert!(err.to_string().contains("Anterior constraint violated"));
+        let err = PagError::UndirectedConstraintViolation { node: 2 };
+        assert!(err.to_string().contains("Undirected constraint violated"));
+        assert!(err.to_string().contains("node 2"));
+    }
+
     // ── Builder Error Tests ───────────────────────────────────────────────────
 
     #[test]
@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MIT
+//! Seeded random graph generators.
+//!
+//! Every generator draws its structure over positions `0..n` and then maps
+//! positions to node ids through a random permutation, so node ids carry no
+//! information about the topological order. Nodes are named `V1, …, Vn`, as
+//! in the R `generate_graph`, and the result is a [`GraphSession`] of the
+//! matching class. The same seed gives the same graph on every platform.
+
+use super::admg::Admg;
+use super::ag::Ag;
+use super::alg::rng::SplitMix64;
+use super::builder::GraphBuilder;
+use super::dag::Dag;
+use super::ug::Ug;
+use super::{GraphClass, GraphSession, GraphView};
+use crate::edges::EdgeRegistry;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// DAG with each of the `n(n − 1)/2` pairs joined independently with
+/// probability `p`, oriented along a random topological order.
+pub fn erdos_renyi_dag(
+    registry: &EdgeRegistry,
+    n: u32,
+    p: f64,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    check_probability("p", p)?;
+    let mut rng = SplitMix64::new(seed);
+    let dag = dag_from_pairs(registry, n, &bernoulli_pairs(n, p, &mut rng), &mut rng)?;
+    Ok(into_session(GraphView::Dag(Arc::new(dag)), GraphClass::Dag))
+}
+
+/// Erdős–Rényi DAG whose nodes have expected degree `degree` (in plus out),
+/// i.e. edge probability `degree / (n − 1)`.
+pub fn expected_degree_dag(
+    registry: &EdgeRegistry,
+    n: u32,
+    degree: f64,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    let max = n.saturating_sub(1) as f64;
+    if !(degree >= 0.0 && degree <= max) {
+        return Err(format!("degree must be in [0, {}], got {}", max, degree));
+    }
+    let p = if max > 0.0 { degree / max } else { 0.0 };
+    erdos_renyi_dag(registry, n, p, seed)
+}
+
+/// Scale-free DAG by preferential attachment (Barabási and Albert, 1999).
+///
+/// Nodes arrive one at a time and each receives edges from `m` distinct
+/// earlier nodes (all of them while fewer than `m` exist), picked with
+/// probability proportional to their degree plus one. Early nodes become
+/// hubs with many children.
+pub fn barabasi_albert_dag(
+    registry: &EdgeRegistry,
+    n: u32,
+    m: u32,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    if m >= n.max(1) {
+        return Err(format!("m must be in 0..{}, got {}", n.max(1), m));
+    }
+    let mut rng = SplitMix64::new(seed);
+    // Each node appears once, plus once per incident edge.
+    let mut pool: Vec<u32> = Vec::new();
+    let mut pairs = Vec::new();
+    for t in 0..n {
+        let k = m.min(t) as usize;
+        let mut targets: Vec<u32> = Vec::with_capacity(k);
+        while targets.len() < k {
+            let u = pool[rng.below(pool.len() as u64) as usize];
+            if !targets.contains(&u) {
+                targets.push(u);
+            }
+        }
+        for &u in &targets {
+            pairs.push((u, t));
+            pool.push(u);
+            pool.push(t);
+        }
+        pool.push(t);
+    }
+    let dag = dag_from_pairs(registry, n, &pairs, &mut rng)?;
+    Ok(into_session(GraphView::Dag(Arc::new(dag)), GraphClass::Dag))
+}
+
+/// ADMG made of an Erdős–Rényi DAG with edge probability `p_directed`, plus
+/// `<->` with probability `p_bidirected` between each pair not already
+/// joined by an arrow.
+pub fn random_admg(
+    registry: &EdgeRegistry,
+    n: u32,
+    p_directed: f64,
+    p_bidirected: f64,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    check_probability("p_directed", p_directed)?;
+    check_probability("p_bidirected", p_bidirected)?;
+    let mut rng = SplitMix64::new(seed);
+    let directed = bernoulli_pairs(n, p_directed, &mut rng);
+    let taken: HashSet<(u32, u32)> = directed.iter().copied().collect();
+    let bidirected: Vec<(u32, u32)> = bernoulli_pairs(n, p_bidirected, &mut rng)
+        .into_iter()
+        .filter(|pair| !taken.contains(pair))
+        .collect();
+
+    let perm = permutation(n, &mut rng);
+    let d = code(registry, "-->")?;
+    let b = code(registry, "<->")?;
+    let mut builder = GraphBuilder::new_with_registry(n, true, registry);
+    for &(i, j) in &directed {
+        builder.add_edge(perm[i as usize], perm[j as usize], d)?;
+    }
+    for &(i, j) in &bidirected {
+        builder.add_edge(perm[i as usize], perm[j as usize], b)?;
+    }
+    let admg = Admg::new(Arc::new(builder.finalize()?))?;
+    Ok(into_session(
+        GraphView::Admg(Arc::new(admg)),
+        GraphClass::Admg,
+    ))
+}
+
+/// MAG of an Erdős–Rényi DAG on `n + n_latent` nodes with edge probability
+/// `p`, after marginalising `n_latent` of them.
+///
+/// Latent confounding shows up as `<->` edges; the MAG is maximal and
+/// ancestral by construction.
+pub fn random_mag(
+    registry: &EdgeRegistry,
+    n: u32,
+    n_latent: u32,
+    p: f64,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    check_probability("p", p)?;
+    let total = n
+        .checked_add(n_latent)
+        .ok_or("n + n_latent overflows u32")?;
+    let mut rng = SplitMix64::new(seed);
+    let dag = dag_from_pairs(
+        registry,
+        total,
+        &bernoulli_pairs(total, p, &mut rng),
+        &mut rng,
+    )?;
+    let latents: Vec<u32> = (n..total).collect();
+    let mag: Ag = dag.to_mag(&latents, &[])?;
+    Ok(into_session(GraphView::Ag(Arc::new(mag)), GraphClass::Ag))
+}
+
+/// CPDAG of an Erdős–Rényi DAG with edge probability `p`.
+pub fn random_cpdag(
+    registry: &EdgeRegistry,
+    n: u32,
+    p: f64,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    check_probability("p", p)?;
+    let mut rng = SplitMix64::new(seed);
+    let dag = dag_from_pairs(registry, n, &bernoulli_pairs(n, p, &mut rng), &mut rng)?;
+    let cpdag = dag.to_cpdag()?;
+    Ok(into_session(
+        GraphView::Cpdag(Arc::new(cpdag)),
+        GraphClass::Cpdag,
+    ))
+}
+
+/// Chordal undirected graph: an Erdős–Rényi graph with edge probability `p`,
+/// triangulated by eliminating its nodes in a random order.
+///
+/// Eliminating a node joins all of its remaining neighbours, so the
+/// elimination order is a perfect elimination order of the result. The
+/// fill-in means the result can be much denser than `p` suggests.
+pub fn random_chordal_ug(
+    registry: &EdgeRegistry,
+    n: u32,
+    p: f64,
+    seed: u64,
+) -> Result<GraphSession, String> {
+    check_probability("p", p)?;
+    let mut rng = SplitMix64::new(seed);
+    // Positions are eliminated in increasing order; `later[i]` holds the
+    // neighbours of `i` that are eliminated after it.
+    let mut later: Vec<HashSet<u32>> = vec![HashSet::new(); n as usize];
+    for (i, j) in bernoulli_pairs(n, p, &mut rng) {
+        later[i as usize].insert(j);
+    }
+    for i in 0..n as usize {
+        let mut nb: Vec<u32> = later[i].iter().copied().collect();
+        nb.sort_unstable();
+        for (a, &u) in nb.iter().enumerate() {
+            for &w in &nb[a + 1..] {
+                later[u as usize].insert(w);
+            }
+        }
+    }
+
+    let perm = permutation(n, &mut rng);
+    let u = code(registry, "---")?;
+    let mut builder = GraphBuilder::new_with_registry(n, true, registry);
+    for (i, nb) in later.iter().enumerate() {
+        for &j in nb {
+            builder.add_edge(perm[i], perm[j as usize], u)?;
+        }
+    }
+    let ug = Ug::new(Arc::new(builder.finalize()?))?;
+    Ok(into_session(GraphView::Ug(Arc::new(ug)), GraphClass::Ug))
+}
+
+fn check_probability(name: &str, p: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(format!("{} must be in [0, 1], got {}", name, p))
+    }
+}
+
+fn code(registry: &EdgeRegistry, glyph: &str) -> Result<u8, String> {
+    registry.code_of(glyph).map_err(|e| e.to_string())
+}
+
+/// A uniformly random permutation of `0..n`.
+fn permutation(n: u32, rng: &mut SplitMix64) -> Vec<u32> {
+    let mut perm: Vec<u32> = (0..n).collect();
+    rng.shuffle(&mut perm);
+    perm
+}
+
+/// Pairs `(i, j)` with `i < j < n`, each kept independently with
+/// probability `p`, in increasing order.
+///
+/// Gaps between kept pairs are geometric, so the cost is linear in the
+/// number of pairs kept rather than in `n²`.
+fn bernoulli_pairs(n: u32, p: f64, rng: &mut SplitMix64) -> Vec<(u32, u32)> {
+    let n = n as u64;
+    let total = n * n.saturating_sub(1) / 2;
+    let mut out = Vec::new();
+    if p <= 0.0 || total == 0 {
+        return out;
+    }
+    let log_q = (1.0 - p).ln();
+    let (mut i, mut j) = (0u64, 0u64);
+    loop {
+        // Number of pairs skipped before the next kept one.
+        let skip = if p >= 1.0 {
+            0
+        } else {
+            ((1.0 - rng.next_f64()).ln() / log_q)
+                .floor()
+                .min(total as f64) as u64
+        };
+        j += skip + 1;
+        while j >= n {
+            i += 1;
+            if i + 1 >= n {
+                return out;
+            }
+            j = j - n + i + 1;
+        }
+        out.push((i as u32, j as u32));
+    }
+}
+
+/// DAG with an arrow `perm[i] → perm[j]` for each position pair, where
+/// `perm` is a fresh random permutation; pairs must have `i < j`.
+fn dag_from_pairs(
+    registry: &EdgeRegistry,
+    n: u32,
+    pairs: &[(u32, u32)],
+    rng: &mut SplitMix64,
+) -> Result<Dag, String> {
+    let perm = permutation(n, rng);
+    let d = code(registry, "-->")?;
+    let mut builder = GraphBuilder::new_with_registry(n, true, registry);
+    for &(i, j) in pairs {
+        builder.add_edge(perm[i as usize], perm[j as usize], d)?;
+    }
+    Dag::new(Arc::new(builder.finalize()?))
+}
+
+fn into_session(view: GraphView, class: GraphClass) -> GraphSession {
+    let core = view.core();
+    let names = (1..=core.n()).map(|i| format!("V{}", i)).collect();
+    GraphSession::from_prebuilt_core(
+        Arc::new(core.registry.clone()),
+        core.simple,
+        class,
+        core.clone(),
+        names,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reg() -> EdgeRegistry {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        reg
+    }
+
+    fn edges(s: &GraphSession) -> Vec<(u32, u32, u8)> {
+        let e = s.edge_buffer();
+        let mut out: Vec<_> = (0..e.len())
+            .map(|i| (e.from[i], e.to[i], e.etype[i]))
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    #[test]
+    fn bernoulli_pairs_cover_every_pair_once() {
+        let mut rng = SplitMix64::new(1);
+        let all = bernoulli_pairs(5, 1.0, &mut rng);
+        let expected: Vec<(u32, u32)> = (0..5)
+            .flat_map(|i| ((i + 1)..5).map(move |j| (i, j)))
+            .collect();
+        assert_eq!(all, expected);
+        assert!(bernoulli_pairs(5, 0.0, &mut rng).is_empty());
+        assert!(bernoulli_pairs(1, 1.0, &mut rng).is_empty());
+
+        // 200 nodes, 19900 pairs: about 1990 kept at p = 0.1, strictly increasing.
+        let some = bernoulli_pairs(200, 0.1, &mut rng);
+        assert!((1800..2200).contains(&some.len()));
+        assert!(some.windows(2).all(|w| w[0] < w[1]));
+        assert!(some.iter().all(|&(i, j)| i < j && j < 200));
+    }
+
+    #[test]
+    fn generators_are_seeded_named_and_of_the_right_class() {
+        let reg = reg();
+        let mut sessions = [
+            erdos_renyi_dag(&reg, 30, 0.2, 5).unwrap(),
+            expected_degree_dag(&reg, 30, 3.0, 5).unwrap(),
+            barabasi_albert_dag(&reg, 30, 2, 5).unwrap(),
+            random_admg(&reg, 30, 0.1, 0.1, 5).unwrap(),
+            random_mag(&reg, 30, 5, 0.2, 5).unwrap(),
+            random_cpdag(&reg, 30, 0.2, 5).unwrap(),
+            random_chordal_ug(&reg, 30, 0.1, 5).unwrap(),
+        ];
+        let again = [
+            erdos_renyi_dag(&reg, 30, 0.2, 5).unwrap(),
+            erdos_renyi_dag(&reg, 30, 0.2, 6).unwrap(),
+        ];
+        assert_eq!(edges(&sessions[0]), edges(&again[0]));
+        assert_ne!(edges(&sessions[0]), edges(&again[1]));
+
+        for s in sessions.iter_mut() {
+            assert_eq!(s.n(), 30);
+            assert_eq!(s.names()[0], "V1");
+            assert_eq!(s.names()[29], "V30");
+            assert!(!edges(s).is_empty());
+            let class = s.class();
+            s.view().unwrap();
+            assert_eq!(s.resolve_class(class).unwrap(), class);
+        }
+        assert!(sessions[0].is_dag_type().unwrap());
+        assert!(sessions[3].is_admg_type().unwrap());
+        assert!(sessions[4].is_mag().unwrap());
+        assert!(sessions[5].is_cpdag().unwrap());
+        // An undirected graph is a CPDAG exactly when it is chordal.
+        assert!(sessions[6].is_ug_type().unwrap() && sessions[6].is_cpdag().unwrap());
+    }
+
+    #[test]
+    fn barabasi_albert_dag_has_m_parents_per_late_node() {
+        let reg = reg();
+        let mut s = barabasi_albert_dag(&reg, 50, 3, 9).unwrap();
+        // 0 + 1 + 2 edges for the first three nodes, then 3 per node.
+        assert_eq!(edges(&s).len(), 3 + 47 * 3);
+        let mut in_deg: Vec<usize> = (0..50).map(|v| s.parents_of(v).unwrap().len()).collect();
+        in_deg.sort_unstable();
+        assert_eq!(in_deg[..3], [0, 1, 2]);
+        assert!(in_deg[3..].iter().all(|&d| d == 3));
+
+        assert!(barabasi_albert_dag(&reg, 5, 5, 0).is_err());
+        assert_eq!(edges(&barabasi_albert_dag(&reg, 5, 0, 0).unwrap()), vec![]);
+        assert!(erdos_renyi_dag(&reg, 5, 1.5, 0).is_err());
+        assert!(expected_degree_dag(&reg, 5, 4.5, 0).is_err());
+        assert!(random_admg(&reg, 5, 0.5, f64::NAN, 0).is_err());
+    }
+}
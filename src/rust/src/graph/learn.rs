@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+//! Structure learning: constraint-based (PC, FCI) and score-based (GES)
+//! search over a pluggable conditional-independence test or local score.
+
+pub mod ci;
+pub mod fci;
+pub mod fisher_z;
+pub mod ges;
+pub mod pc;
+pub(crate) mod skeleton;
+
+pub use ci::{CiTest, SeparationOracle};
+pub use fci::fci;
+pub use fisher_z::FisherZ;
+pub use ges::{ges, GaussianBic, LocalScore};
+pub use pc::pc;
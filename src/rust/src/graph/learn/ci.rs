@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT
+//! Conditional-independence tests for constraint-based learners.
+
+use crate::graph::GraphView;
+
+/// A test of `X ⊥ Y | Z` over variables `0..n_vars()`.
+///
+/// Learners only ask about distinct `x`, `y` and a sorted `z` that excludes
+/// both. Tests take `&mut self` so that they can cache or count queries.
+pub trait CiTest {
+    /// Number of variables.
+    fn n_vars(&self) -> u32;
+
+    /// Whether `x` and `y` are judged independent given `z`.
+    fn independent(&mut self, x: u32, y: u32, z: &[u32]) -> bool;
+}
+
+/// Perfect independence oracle read off a true graph: `X ⊥ Y | Z` iff `X` and
+/// `Y` are d-separated (DAG) or m-separated (ADMG, AG) by `Z`.
+///
+/// With this oracle PC recovers the CPDAG of a DAG and FCI the PAG of a MAG,
+/// which makes it the reference for checking learners.
+#[derive(Debug)]
+pub struct SeparationOracle<'a> {
+    view: &'a GraphView,
+    n_tests: usize,
+}
+
+impl<'a> SeparationOracle<'a> {
+    /// Oracle for a DAG, ADMG or AG.
+    pub fn new(view: &'a GraphView) -> Result<Self, String> {
+        match view {
+            GraphView::Dag(_) | GraphView::Admg(_) | GraphView::Ag(_) => {
+                Ok(Self { view, n_tests: 0 })
+            }
+            _ => Err("a separation oracle needs a DAG, ADMG or AG".into()),
+        }
+    }
+
+    /// Number of independence queries answered so far.
+    pub fn n_tests(&self) -> usize {
+        self.n_tests
+    }
+}
+
+impl CiTest for SeparationOracle<'_> {
+    fn n_vars(&self) -> u32 {
+        self.view.n()
+    }
+
+    fn independent(&mut self, x: u32, y: u32, z: &[u32]) -> bool {
+        self.n_tests += 1;
+        self.view
+            .m_separated(&[x], &[y], z)
+            .expect("checked in SeparationOracle::new")
+    }
+}
@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: MIT
+//! The PC algorithm (stable variant) for causally sufficient systems.
+//!
+//! An adjacency search removes every edge whose endpoints test independent
+//! given some subset of the current neighbours, unshielded triples
+//! `a — b — c` whose middle node is outside the separating set of `a` and
+//! `c` become colliders `a → b ← c`, and Meek's rules complete the
+//! orientation. With a faithful oracle the result is the CPDAG of the true
+//! DAG.
+//!
+//! # References
+//!
+//! P. Spirtes, C. Glymour and R. Scheines (2000). *Causation, Prediction, and
+//! Search*, 2nd ed. MIT Press.
+
+use super::ci::CiTest;
+use super::skeleton::{stable_skeleton, SepSets, Skeleton};
+use crate::edges::Mark;
+use crate::graph::alg::{csr, meek};
+use crate::graph::builder::GraphBuilder;
+use crate::graph::pdag::Pdag;
+use crate::graph::{Cpdag, RegistrySnapshot};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Run PC-stable with `test` and return the learned CPDAG, with edge codes
+/// taken from `registry`.
+///
+/// Conflicting collider orientations, which only an unfaithful test can
+/// produce, keep the first one found. If the orientations do not form a
+/// CPDAG, the CPDAG of a consistent extension is returned. If they admit
+/// none, arrows are kept greedily (in index order) while they stay acyclic,
+/// the remaining edges follow a topological order of the kept arrows, and
+/// the CPDAG of that DAG is returned.
+pub fn pc<T: CiTest + ?Sized>(test: &mut T, registry: &RegistrySnapshot) -> Result<Cpdag, String> {
+    let (adj, sepsets) = stable_skeleton(test);
+    let (mut pa, mut ch, mut und) = orient_colliders(&adj, &sepsets);
+    meek::apply_meek_closure(&mut pa, &mut ch, &mut und, false);
+    if let Ok(pdag) = pdag_from_sets(registry, &ch, &und) {
+        if let Ok(cpdag) = Cpdag::try_new(pdag.clone()) {
+            return Ok(cpdag);
+        }
+        if let Ok(dag) = pdag.consistent_extension() {
+            return dag.to_cpdag();
+        }
+    }
+    let arrows = acyclic_completion(&ch, &und);
+    let none = vec![HashSet::new(); arrows.len()];
+    pdag_from_sets(registry, &arrows, &none)?
+        .consistent_extension()?
+        .to_cpdag()
+}
+
+/// Children of a DAG on the skeleton of `ch ∪ und` that keeps every arrow of
+/// `ch` not closing a cycle with those kept before it.
+fn acyclic_completion(ch: &[HashSet<u32>], und: &[HashSet<u32>]) -> Vec<HashSet<u32>> {
+    let n = ch.len();
+    let mut out = vec![HashSet::new(); n];
+    let reaches = |out: &[HashSet<u32>], from: u32, to: u32| {
+        let mut seen = vec![false; n];
+        let mut stack = vec![from];
+        while let Some(v) = stack.pop() {
+            if v == to {
+                return true;
+            }
+            for &w in &out[v as usize] {
+                if !std::mem::replace(&mut seen[w as usize], true) {
+                    stack.push(w);
+                }
+            }
+        }
+        false
+    };
+    let mut arrows: Vec<(u32, u32)> = (0..n as u32)
+        .flat_map(|u| ch[u as usize].iter().map(move |&v| (u, v)))
+        .collect();
+    arrows.sort_unstable();
+    let mut dropped = Vec::new();
+    for (u, v) in arrows {
+        if reaches(&out, v, u) {
+            dropped.push((u, v));
+        } else {
+            out[u as usize].insert(v);
+        }
+    }
+
+    // Kahn order of the kept arrows, smallest index first.
+    let mut indeg = vec![0usize; n];
+    for c in out.iter().flatten() {
+        indeg[*c as usize] += 1;
+    }
+    let mut ready: std::collections::BTreeSet<u32> =
+        (0..n as u32).filter(|&v| indeg[v as usize] == 0).collect();
+    let mut rank = vec![0usize; n];
+    let mut next = 0;
+    while let Some(v) = ready.pop_first() {
+        rank[v as usize] = next;
+        next += 1;
+        for &c in &out[v as usize] {
+            indeg[c as usize] -= 1;
+            if indeg[c as usize] == 0 {
+                ready.insert(c);
+            }
+        }
+    }
+    let rest = (0..n as u32)
+        .flat_map(|u| und[u as usize].iter().map(move |&v| (u, v)))
+        .filter(|&(u, v)| u < v)
+        .chain(dropped);
+    for (u, v) in rest {
+        if rank[u as usize] < rank[v as usize] {
+            out[u as usize].insert(v);
+        } else {
+            out[v as usize].insert(u);
+        }
+    }
+    out
+}
+
+/// Per-node `(parents, children, undirected)` of a PDAG state.
+type PdagSets = (Vec<HashSet<u32>>, Vec<HashSet<u32>>, Vec<HashSet<u32>>);
+
+/// Orient every unshielded triple `a — b — c` with `b` outside the
+/// separating set of `a` and `c` as `a → b ← c`.
+fn orient_colliders(adj: &Skeleton, sepsets: &SepSets) -> PdagSets {
+    let n = adj.len();
+    let mut pa = vec![HashSet::new(); n];
+    let mut ch = vec![HashSet::new(); n];
+    let mut und: Vec<HashSet<u32>> = adj.iter().map(|a| a.iter().copied().collect()).collect();
+    for b in 0..n as u32 {
+        let nb = &adj[b as usize];
+        for (i, &a) in nb.iter().enumerate() {
+            for &c in &nb[i + 1..] {
+                if adj[a as usize].binary_search(&c).is_ok() || sepsets.contains(a, c, b) {
+                    continue;
+                }
+                for t in [a, c] {
+                    if und[b as usize].contains(&t) {
+                        meek::orient(t, b, &mut und, &mut pa, &mut ch);
+                    }
+                }
+            }
+        }
+    }
+    (pa, ch, und)
+}
+
+/// A PDAG with arrows `ch` and undirected edges `und` (per node), with edge
+/// codes taken from `registry`.
+pub(crate) fn pdag_from_sets(
+    registry: &RegistrySnapshot,
+    ch: &[HashSet<u32>],
+    und: &[HashSet<u32>],
+) -> Result<Pdag, String> {
+    let n = ch.len() as u32;
+    let (dir, dir_rev) = csr::code_for_marks(registry, Mark::Tail, Mark::Arrow)?;
+    let (undc, _) = csr::code_for_marks(registry, Mark::Tail, Mark::Tail)?;
+    let mut b = GraphBuilder::new_from_snapshot(n, true, Arc::new(registry.clone()));
+    for u in 0..n {
+        let mut children: Vec<u32> = ch[u as usize].iter().copied().collect();
+        children.sort_unstable();
+        for v in children {
+            if dir_rev {
+                b.add_edge(v, u, dir)?;
+            } else {
+                b.add_edge(u, v, dir)?;
+            }
+        }
+        let mut nbrs: Vec<u32> = und[u as usize].iter().copied().filter(|&v| u < v).collect();
+        nbrs.sort_unstable();
+        for v in nbrs {
+            b.add_edge(u, v, undc)?;
+        }
+    }
+    Pdag::new(Arc::new(b.finalize()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::dag::Dag;
+    use crate::graph::learn::ci::SeparationOracle;
+    use crate::graph::GraphView;
+
+    fn dag(n: u32, edges: &[(u32, u32)]) -> Dag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(a, c) in edges {
+            b.add_edge(a, c, d).unwrap();
+        }
+        Dag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    fn sets(p: &Pdag) -> (Vec<Vec<u32>>, Vec<Vec<u32>>) {
+        (
+            (0..p.n()).map(|u| p.children_of(u).to_vec()).collect(),
+            (0..p.n()).map(|u| p.undirected_of(u).to_vec()).collect(),
+        )
+    }
+
+    #[test]
+    fn oracle_pc_recovers_the_cpdag() {
+        // 0 -> 2 <- 1, 2 -> 3, 3 -> 4 <- 0, 5 -> 3: the v-structures and
+        // Meek R1 orient everything but the isolated 6 - 7.
+        let cases = [
+            dag(8, &[(0, 2), (1, 2), (2, 3), (3, 4), (0, 4), (5, 3), (6, 7)]),
+            dag(4, &[(0, 1), (1, 2), (2, 3), (0, 2)]),
+            dag(1, &[]),
+        ];
+        for d in cases {
+            let view = GraphView::Dag(Arc::new(d.clone()));
+            let mut oracle = SeparationOracle::new(&view).unwrap();
+            let learned = pc(&mut oracle, &d.core_ref().registry).unwrap();
+            assert_eq!(sets(&learned), sets(&d.to_cpdag().unwrap()));
+            assert!(oracle.n_tests() > 0 || d.n() == 1);
+        }
+        let view = GraphView::Cpdag(Arc::new(dag(2, &[(0, 1)]).to_cpdag().unwrap()));
+        assert!(SeparationOracle::new(&view).is_err());
+    }
+
+    #[test]
+    fn acyclic_completion_breaks_cycles_and_orients_the_rest() {
+        // 0 -> 1 -> 2 -> 0 and 2 - 3: 2 -> 0 closes the cycle and is flipped,
+        // and 2 - 3 follows the order 0, 1, 2, 3.
+        let set = |xs: &[u32]| xs.iter().copied().collect::<HashSet<u32>>();
+        let ch = vec![set(&[1]), set(&[2]), set(&[0]), set(&[])];
+        let und = vec![set(&[]), set(&[]), set(&[3]), set(&[2])];
+        let out = acyclic_completion(&ch, &und);
+        assert_eq!(out, vec![set(&[1, 2]), set(&[2]), set(&[3]), set(&[])]);
+    }
+}
@@ -15,10 +15,14 @@ pub mod alg;
 pub mod builder;
 pub mod cpdag;
 pub mod dag;
+pub mod equivalence;
 pub mod error;
+pub mod generate;
 pub mod mpdag;
 pub mod packed;
+pub mod pag;
 pub mod pdag;
+pub mod sem;
 pub mod session;
 pub mod traits;
 pub mod ug;
@@ -29,6 +33,7 @@ pub use view::GraphView;
 pub use view::NeighborMode;
 pub mod graphml;
 pub mod layout;
+pub mod learn;
 pub mod metrics;
 pub mod serialization;
 pub use session::{EdgeBuffer, GraphClass, GraphSession};
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MIT
+//! Generalized adjustment criterion for MPDAGs (and CPDAGs via `Deref`).
+//!
+//! Amenability and the forbidden set are read off the MPDAG's marks; the
+//! blocking condition is checked by d-separation in the proper backdoor
+//! graph of a consistent DAG extension (Perković et al., 2017).
+
+use super::Mpdag;
+use crate::graph::alg::gac::{self, Amenability, GacSets, MarkedGraph, Semantics};
+use crate::graph::dag::Dag;
+
+impl Mpdag {
+    pub(super) fn marked_graph(&self) -> MarkedGraph {
+        MarkedGraph::new(self.core_ref(), Semantics::Pdag)
+    }
+
+    fn gac_sets(&self, xs: &[u32], ys: &[u32]) -> Result<Amenability<GacSets>, String> {
+        gac::analyse(&self.marked_graph(), xs, ys)
+    }
+
+    fn proper_backdoor_dag(&self, xs: &[u32], ys: &[u32]) -> Result<Dag, String> {
+        self.consistent_extension()?.proper_backdoor_graph(xs, ys)
+    }
+
+    /// Generalized adjustment criterion: whether `z` is a valid adjustment
+    /// set for `Xs → Ys` in every DAG represented by this MPDAG.
+    pub fn is_valid_adjustment_set(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Amenability<bool>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let pbd = self.proper_backdoor_dag(xs, ys)?;
+            gac::is_valid(&sets, ys, z, |z| pbd.d_separated(xs, ys, z))
+        })
+    }
+
+    /// Enumerate valid adjustment sets for `Xs → Ys` up to size `max_size`.
+    /// If `minimal` is true, return only inclusion-minimal sets.
+    pub fn all_adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+        max_size: u32,
+    ) -> Result<Amenability<Vec<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let pbd = self.proper_backdoor_dag(xs, ys)?;
+            Ok(gac::all_sets(&sets, ys, minimal, max_size, |z| {
+                pbd.d_separated(xs, ys, z)
+            }))
+        })
+    }
+
+    /// The canonical adjustment set `PossAn(X ∪ Y) \ (X ∪ Y ∪ Forb)`, or
+    /// `None` if no valid adjustment set exists.
+    pub fn adjustment_set_canonical(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Amenability<Option<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let z = gac::canonical(&self.marked_graph(), &sets, xs, ys);
+            let pbd = self.proper_backdoor_dag(xs, ys)?;
+            Ok(pbd.d_separated(xs, ys, &z).then_some(z))
+        })
+    }
+
+    /// Optimal adjustment set `O(X, Y) = Pa(cn(X, Y)) \ (Forb(X, Y) ∪ Y)`
+    /// (Henckel, Perković & Maathuis, 2022), or `None` if no valid
+    /// adjustment set exists. Errors if one exists but the O-set is not
+    /// valid, which can only happen when `Y ⊄ PossDe(X)`.
+    pub fn adjustment_set_optimal(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Amenability<Option<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let g = self.marked_graph();
+            let pbd = self.proper_backdoor_dag(xs, ys)?;
+            let z = gac::optimal(&g, &sets, ys);
+            if pbd.d_separated(xs, ys, &z) {
+                Ok(Some(z))
+            } else if pbd.d_separated(xs, ys, &gac::canonical(&g, &sets, xs, ys)) {
+                Err(gac::OPTIMAL_UNDEFINED.into())
+            } else {
+                Ok(None)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use crate::graph::pdag::Pdag;
+    use std::sync::Arc;
+
+    fn mpdag(n: u32, edges: &[(u32, u32, &str)]) -> Mpdag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Mpdag::try_new(Pdag::new(Arc::new(b.finalize().unwrap())).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn cpdag_adjustment_when_treatment_edges_are_compelled() {
+        // 2 --- 3, 3 --> 0 <-- 4, 0 --> 1, 3 --> 1: adjust for 3 (or 2 and 3 ...).
+        let g = mpdag(
+            5,
+            &[
+                (2, 3, "---"),
+                (3, 0, "-->"),
+                (4, 0, "-->"),
+                (0, 1, "-->"),
+                (3, 1, "-->"),
+            ],
+        );
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[1], &[3]).unwrap(),
+            Amenability::Amenable(true)
+        );
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[1], &[4]).unwrap(),
+            Amenability::Amenable(false)
+        );
+        assert_eq!(
+            g.all_adjustment_sets(&[0], &[1], true, 3).unwrap(),
+            Amenability::Amenable(vec![vec![3]])
+        );
+        assert_eq!(
+            g.adjustment_set_canonical(&[0], &[1]).unwrap(),
+            Amenability::Amenable(Some(vec![2, 3, 4]))
+        );
+        assert_eq!(
+            g.adjustment_set_optimal(&[0], &[1]).unwrap(),
+            Amenability::Amenable(Some(vec![3]))
+        );
+    }
+
+    #[test]
+    fn cpdag_optimal_set_for_joint_intervention() {
+        // X = {0, 1}, Y = {2}: 0 --> 2 <-- 1, 7 --> 0, 7 --> 2, 3 --- 6 is undetermined.
+        let g = mpdag(
+            8,
+            &[
+                (3, 0, "-->"),
+                (4, 0, "-->"),
+                (4, 1, "-->"),
+                (5, 1, "-->"),
+                (0, 2, "-->"),
+                (1, 2, "-->"),
+                (7, 0, "-->"),
+                (7, 2, "-->"),
+                (6, 3, "---"),
+            ],
+        );
+        assert_eq!(
+            g.adjustment_set_optimal(&[0, 1], &[2]).unwrap(),
+            Amenability::Amenable(Some(vec![7]))
+        );
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0, 1], &[2], &[7]).unwrap(),
+            Amenability::Amenable(true)
+        );
+    }
+
+    #[test]
+    fn cpdag_undirected_treatment_edge_is_not_amenable() {
+        // 0 --- 1 --- 2: the effect of 0 on 2 depends on the orientation.
+        let g = mpdag(3, &[(0, 1, "---"), (1, 2, "---")]);
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[2], &[]).unwrap(),
+            Amenability::NotAmenable { x: 0, first: 1 }
+        );
+        // Towards a non-descendant of every orientation the effect is amenable.
+        let g = mpdag(3, &[(0, 1, "-->"), (2, 1, "-->")]);
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[2], &[]).unwrap(),
+            Amenability::Amenable(true)
+        );
+    }
+
+    #[test]
+    fn optimal_set_undefined_when_y_is_not_a_descendant() {
+        // 5 --> 0 --> 2 --> 4 <-- 1 <-- 3 --> 4, 6 --> 2 with X = {2}, Y = {4, 5}:
+        // {0} is valid, but the O-set {1, 3, 6} leaves 2 <-- 0 <-- 5 open.
+        let edges: Vec<_> = [(5, 0), (0, 2), (2, 4), (1, 4), (3, 1), (3, 4), (6, 2)]
+            .into_iter()
+            .map(|(u, v)| (u, v, "-->"))
+            .collect();
+        let g = mpdag(7, &edges);
+        assert_eq!(
+            g.is_valid_adjustment_set(&[2], &[4, 5], &[0]).unwrap(),
+            Amenability::Amenable(true)
+        );
+        assert_eq!(
+            g.adjustment_set_optimal(&[2], &[4, 5]).unwrap_err(),
+            gac::OPTIMAL_UNDEFINED
+        );
+        assert_eq!(
+            g.adjustment_set_optimal(&[2], &[4]).unwrap(),
+            Amenability::Amenable(Some(vec![1, 3]))
+        );
+    }
+}
@@ -5,6 +5,10 @@
 //! `Mpdag` is composition over a `Pdag` (same `PackedBuckets<3>` storage) plus
 //! a `MeekClosed` marker. Accessors are inherited from `Pdag` via `Deref`.
 
+mod adjustment;
+mod ida;
+mod relations;
+
 use super::pdag::Pdag;
 use super::traits::{Acyclic, MeekClosed, NoBidirected};
 
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MIT
+//! Possible and definite ancestral relations in MPDAGs (and CPDAGs via
+//! `Deref`).
+//!
+//! `a` is a possible ancestor of `b` if some b-possibly causal path runs from
+//! `a` to `b`, i.e. `a` is an ancestor of `b` in some member DAG; it is a
+//! definite ancestor if it is an ancestor of `b` in every member DAG.
+//!
+//! A definite ancestor either reaches `b` by a directed path, or every member
+//! orients some *critical* undirected edge `a - c` out of `a`, where `c`
+//! starts an unshielded possibly causal path to `b` (Fang & He, 2022). The
+//! latter holds iff orienting all critical edges into `a` admits no member,
+//! which is checked directly.
+//!
+//! # References
+//!
+//! E. Perković, M. Kalisch and M. H. Maathuis (2017). Interpreting and using
+//! CPDAGs with background knowledge. *UAI 2017*.
+//!
+//! Z. Fang and Y. He (2022). Representation of causal relations in MPDAGs.
+//! *Journal of Machine Learning Research* 23(71), 1–45.
+
+use super::Mpdag;
+use crate::graph::alg::bitset;
+
+impl Mpdag {
+    /// Possible ancestors of `i` (ancestors in some member DAG), in
+    /// ascending order, excluding `i`.
+    pub fn possible_ancestors_of(&self, i: u32) -> Vec<u32> {
+        let none = vec![false; self.n() as usize];
+        let mut mask = self.marked_graph().possible_ancestors_mask(&[i], &none);
+        mask[i as usize] = false;
+        bitset::collect_from_mask(&mask)
+    }
+
+    /// Possible descendants of `i` (descendants in some member DAG), in
+    /// ascending order, excluding `i`.
+    pub fn possible_descendants_of(&self, i: u32) -> Vec<u32> {
+        let none = vec![false; self.n() as usize];
+        let mut mask = self.marked_graph().possible_descendants_mask(&[i], &none);
+        mask[i as usize] = false;
+        bitset::collect_from_mask(&mask)
+    }
+
+    /// Definite ancestors of `i` (ancestors in every member DAG), in
+    /// ascending order, excluding `i`.
+    pub fn definite_ancestors_of(&self, i: u32) -> Vec<u32> {
+        let g = self.marked_graph();
+        let directed = self.ancestors_of(i);
+        self.possible_ancestors_of(i)
+            .into_iter()
+            .filter(|&a| {
+                if directed.binary_search(&a).is_ok() {
+                    return true;
+                }
+                let into_a: Vec<(u32, u32)> = self
+                    .undirected_of(a)
+                    .iter()
+                    .filter(|&&c| g.possible_descendants_through(a, c)[i as usize])
+                    .map(|&c| (c, a))
+                    .collect();
+                !into_a.is_empty() && !self.admits_member_with(&into_a)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use crate::graph::pdag::Pdag;
+    use std::sync::Arc;
+
+    fn mpdag(n: u32, directed: &[(u32, u32)], undirected: &[(u32, u32)]) -> Mpdag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let u = reg.code_of("---").unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(a, c) in directed {
+            b.add_edge(a, c, d).unwrap();
+        }
+        for &(a, c) in undirected {
+            b.add_edge(a, c, u).unwrap();
+        }
+        Mpdag::try_new(Pdag::new(Arc::new(b.finalize().unwrap())).unwrap()).unwrap()
+    }
+
+    /// Ancestors of `i` in every / some member DAG, by enumeration.
+    fn by_enumeration(g: &Mpdag, i: u32) -> (Vec<u32>, Vec<u32>) {
+        let members: Vec<Vec<u32>> = g
+            .member_dags()
+            .unwrap()
+            .map(|d| d.ancestors_of(i))
+            .collect();
+        let every = (0..g.n())
+            .filter(|v| members.iter().all(|an| an.contains(v)))
+            .collect();
+        let some = (0..g.n())
+            .filter(|v| members.iter().any(|an| an.contains(v)))
+            .collect();
+        (every, some)
+    }
+
+    #[test]
+    fn definite_ancestor_through_a_non_collider() {
+        // u=1 <- a=0 -> v=2 cannot both point into 0 since 1 and 2 are not
+        // adjacent: 1 -> 3 <- 2, 0 - 1, 0 - 2.
+        let g = mpdag(4, &[(1, 3), (2, 3)], &[(0, 1), (0, 2)]);
+        assert_eq!(g.possible_ancestors_of(3), vec![0, 1, 2]);
+        assert_eq!(g.definite_ancestors_of(3), vec![0, 1, 2]);
+        assert_eq!(g.possible_ancestors_of(1), vec![0, 2]);
+        assert_eq!(g.definite_ancestors_of(1), Vec::<u32>::new());
+        assert_eq!(g.possible_descendants_of(0), vec![1, 2, 3]);
+        for i in 0..4 {
+            let (every, some) = by_enumeration(&g, i);
+            assert_eq!(g.definite_ancestors_of(i), every);
+            assert_eq!(g.possible_ancestors_of(i), some);
+        }
+    }
+
+    #[test]
+    fn possible_ancestors_skip_shielded_detours() {
+        // 0 - 1 - 2 with 0 -> 2: 2 is never an ancestor of 0, and 0 -> 2 makes
+        // 0 a definite ancestor of 2.
+        let g = mpdag(3, &[(0, 2)], &[(0, 1), (1, 2)]);
+        assert_eq!(g.possible_ancestors_of(0), vec![1]);
+        assert_eq!(g.definite_ancestors_of(2), vec![0]);
+        for i in 0..3 {
+            let (every, some) = by_enumeration(&g, i);
+            assert_eq!(g.definite_ancestors_of(i), every);
+            assert_eq!(g.possible_ancestors_of(i), some);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+//! Generalized adjustment criterion for PAGs.
+//!
+//! Amenability and the forbidden set are read off the PAG's endpoint marks;
+//! the blocking condition is checked by m-separation in the proper backdoor
+//! graph of a representative MAG (Perković et al., 2018).
+
+use super::Pag;
+use crate::graph::ag::Ag;
+use crate::graph::alg::gac::{self, Amenability, GacSets, MarkedGraph, Semantics};
+
+impl Pag {
+    fn gac_sets(&self, xs: &[u32], ys: &[u32]) -> Result<Amenability<GacSets>, String> {
+        gac::analyse(
+            &MarkedGraph::new(self.core_ref(), Semantics::Ancestral),
+            xs,
+            ys,
+        )
+    }
+
+    fn proper_backdoor_mag(&self, xs: &[u32], ys: &[u32]) -> Result<Ag, String> {
+        self.representative_mag()?.proper_backdoor_mag(xs, ys)
+    }
+
+    /// Generalized adjustment criterion: whether `z` is a valid adjustment
+    /// set for `Xs → Ys` in every MAG represented by this PAG.
+    pub fn is_valid_adjustment_set(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Amenability<bool>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let pbd = self.proper_backdoor_mag(xs, ys)?;
+            gac::is_valid(&sets, ys, z, |z| pbd.m_separated(xs, ys, z))
+        })
+    }
+
+    /// Enumerate valid adjustment sets for `Xs → Ys` up to size `max_size`.
+    /// If `minimal` is true, return only inclusion-minimal sets.
+    pub fn all_adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+        max_size: u32,
+    ) -> Result<Amenability<Vec<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let pbd = self.proper_backdoor_mag(xs, ys)?;
+            Ok(gac::all_sets(&sets, ys, minimal, max_size, |z| {
+                pbd.m_separated(xs, ys, z)
+            }))
+        })
+    }
+
+    /// The canonical adjustment set `PossAn(X ∪ Y) \ (X ∪ Y ∪ Forb)`, or
+    /// `None` if no valid adjustment set exists.
+    pub fn adjustment_set_canonical(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Amenability<Option<Vec<u32>>>, String> {
+        self.gac_sets(xs, ys)?.try_map(|sets| {
+            let g = MarkedGraph::new(self.core_ref(), Semantics::Ancestral);
+            let z = gac::canonical(&g, &sets, xs, ys);
+            let pbd = self.proper_backdoor_mag(xs, ys)?;
+            Ok(pbd.m_separated(xs, ys, &z).then_some(z))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use std::sync::Arc;
+
+    fn pag(n: u32, edges: &[(u32, u32, &str)]) -> Pag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Pag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn pag_adjustment_amenable_and_not() {
+        // 3 o-> 0 <-o 4, 0 --> 1: the edge 0 --> 1 is visible through 3.
+        let g = pag(5, &[(3, 0, "o->"), (4, 0, "o->"), (0, 1, "-->")]);
+        assert_eq!(
+            g.is_valid_adjustment_set(&[0], &[1], &[]).unwrap(),
+            Amenability::Amenable(true)
+        );
+        assert_eq!(
+            g.all_adjustment_sets(&[0], &[1], true, 2).unwrap(),
+            Amenability::Amenable(vec![vec![]])
+        );
+        assert_eq!(
+            g.adjustment_set_canonical(&[0], &[1]).unwrap(),
+            Amenability::Amenable(Some(vec![3, 4]))
+        );
+
+        // 0 o-> 1: the effect is not amenable.
+        let g = pag(2, &[(0, 1, "o->")]);
+        assert_eq!(
+            g.all_adjustment_sets(&[0], &[1], true, 2).unwrap(),
+            Amenability::NotAmenable { x: 0, first: 1 }
+        );
+    }
+}
@@ -0,0 +1,571 @@
+// SPDX-License-Identifier: MIT
+//! Partial Ancestral Graph (PAG) wrapper with O(1) slice queries via packed neighborhoods.
+//!
+//! A PAG represents a Markov equivalence class of MAGs. Next to the edges of an
+//! ancestral graph it contains circle marks (`o`) for endpoints that are not
+//! invariant across the class:
+//! - Directed edges (-->), bidirected edges (<->), and undirected edges (---)
+//! - Partial edges with at least one circle endpoint (o-o, o->, --o)
+//!
+//! Validation enforces the constraints that hold for every PAG:
+//! 1. Every endpoint mark is an arrow, tail, or circle
+//! 2. At most one edge between any pair of nodes
+//! 3. No directed cycles among the definite (`-->`) edges
+//! 4. If there is an arrowhead at v from u, then v is not a definite anterior of u
+//! 5. If v is an endpoint of an undirected edge, v has no edge with arrowhead at v
+
+mod adjustment;
+mod transforms;
+
+use super::error::PagError;
+use super::packed::{PackedBuckets, PackedBucketsBuilder};
+use super::CaugiGraph;
+use crate::edges::Mark;
+use crate::graph::alg::bitset;
+use crate::graph::alg::directed_part_is_acyclic;
+use crate::graph::alg::gac::{MarkedGraph, Semantics};
+use crate::graph::alg::traversal;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Pag {
+    core: Arc<CaugiGraph>,
+    /// packed as [parents | undirected | spouses | children | circle] for each node
+    packed: PackedBuckets<5>,
+}
+
+/// Bucket of the half-edge with marks `(mine, theirs)`, or `None` for invalid marks.
+#[inline]
+fn bucket_of(mine: Mark, theirs: Mark) -> Option<usize> {
+    match (mine, theirs) {
+        (Mark::Other, _) | (_, Mark::Other) => None,
+        (Mark::Circle, _) | (_, Mark::Circle) => Some(4),
+        (Mark::Arrow, Mark::Tail) => Some(0),
+        (Mark::Tail, Mark::Tail) => Some(1),
+        (Mark::Arrow, Mark::Arrow) => Some(2),
+        (Mark::Tail, Mark::Arrow) => Some(3),
+    }
+}
+
+impl Pag {
+    /// Builds a `Pag` view over a class-agnostic CSR graph.
+    ///
+    /// Validates that:
+    /// 1. Only arrow, tail, and circle marks are present
+    /// 2. Every pair of nodes is joined by at most one edge
+    /// 3. The definite directed part is acyclic
+    /// 4. Anterior constraint: if arrowhead at v from u, v is not a definite anterior of u
+    /// 5. Undirected constraint: if v has undirected edge, v has no arrowhead edges
+    ///
+    /// Parents, undirected neighbors, spouses, children, and circle neighbors for
+    /// each node are stored contiguously and sorted.
+    ///
+    /// Returns a `String` error for FFI compatibility. Use `try_new` for typed errors.
+    pub fn new(core: Arc<CaugiGraph>) -> Result<Self, String> {
+        Self::try_new(core).map_err(|e| e.to_string())
+    }
+
+    /// Builds a `Pag` view with typed error handling.
+    ///
+    /// See [`new`](Self::new) for details.
+    pub fn try_new(core: Arc<CaugiGraph>) -> Result<Self, PagError> {
+        let n = core.n() as usize;
+
+        let mut packed_builder: PackedBucketsBuilder<5> = PackedBucketsBuilder::new(n);
+        let mut has_undirected = vec![false; n];
+        let mut has_arrowhead = vec![false; n];
+        let mut last_seen = vec![u32::MAX; n];
+
+        for i in 0..n {
+            for k in core.row_range(i as u32) {
+                let (mine, theirs) = core.marks(k);
+                let Some(bucket) = bucket_of(mine, theirs) else {
+                    return Err(PagError::InvalidEdgeType {
+                        found: core.spec(k).glyph.clone(),
+                    });
+                };
+                let j = core.col_index[k] as usize;
+                if std::mem::replace(&mut last_seen[j], i as u32) == i as u32 {
+                    return Err(PagError::MultipleEdges {
+                        a: i as u32,
+                        b: j as u32,
+                    });
+                }
+                packed_builder.inc_degree(i, bucket);
+                has_undirected[i] |= bucket == 1;
+                has_arrowhead[i] |= mine == Mark::Arrow;
+            }
+        }
+
+        // Check acyclicity of the definite directed part
+        if !directed_part_is_acyclic(&core) {
+            return Err(PagError::DirectedCycle);
+        }
+
+        // Check undirected constraint: if node has undirected edge, it can't have arrowhead
+        for i in 0..n {
+            if has_undirected[i] && has_arrowhead[i] {
+                return Err(PagError::UndirectedConstraintViolation { node: i as u32 });
+            }
+        }
+
+        packed_builder.finalize_degrees();
+
+        for i in 0..n {
+            for k in core.row_range(i as u32) {
+                let (mine, theirs) = core.marks(k);
+                let bucket = bucket_of(mine, theirs).expect("marks validated above");
+                packed_builder.scatter(i, bucket, core.col_index[k]);
+            }
+        }
+
+        packed_builder.sort_all();
+        let packed = packed_builder.build();
+
+        let pag = Self { core, packed };
+        pag.validate_anterior_constraint()?;
+
+        Ok(pag)
+    }
+
+    /// Validate the anterior constraint for every edge with an arrowhead.
+    fn validate_anterior_constraint(&self) -> Result<(), PagError> {
+        for v in 0..self.n() {
+            for k in self.core.row_range(v) {
+                if self.core.my_mark(k) != Mark::Arrow {
+                    continue;
+                }
+                // v has an arrowhead from u, so v must not be a definite anterior of u
+                let u = self.core.col_index[k];
+                if self.anteriors_of(u).binary_search(&v).is_ok() {
+                    return Err(PagError::AnteriorConstraintViolation {
+                        source: u,
+                        target: v,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of nodes.
+    #[inline]
+    pub fn n(&self) -> u32 {
+        self.core.n()
+    }
+
+    /// Sorted slice of parents of `i` (nodes with a definite `-->` edge into `i`).
+    #[inline]
+    pub fn parents_of(&self, i: u32) -> &[u32] {
+        self.packed.bucket_slice(i, 0)
+    }
+
+    /// Sorted slice of children of `i` (nodes with a definite `-->` edge from `i`).
+    #[inline]
+    pub fn children_of(&self, i: u32) -> &[u32] {
+        self.packed.bucket_slice(i, 3)
+    }
+
+    /// Sorted slice of undirected neighbors of `i`.
+    #[inline]
+    pub fn undirected_of(&self, i: u32) -> &[u32] {
+        self.packed.bucket_slice(i, 1)
+    }
+
+    /// Sorted slice of spouses of `i` (nodes connected via bidirected edge).
+    #[inline]
+    pub fn spouses_of(&self, i: u32) -> &[u32] {
+        self.packed.bucket_slice(i, 2)
+    }
+
+    /// Sorted slice of circle neighbors of `i` (edges with a circle at either end).
+    #[inline]
+    pub fn circle_neighbors_of(&self, i: u32) -> &[u32] {
+        self.packed.bucket_slice(i, 4)
+    }
+
+    /// All neighbors of `i`: [parents | undirected | spouses | children | circle].
+    #[inline]
+    pub fn neighbors_of(&self, i: u32) -> &[u32] {
+        self.packed.all_neighbors(i)
+    }
+
+    /// Check if two nodes are adjacent (connected by any edge).
+    pub fn adjacent(&self, a: u32, b: u32) -> bool {
+        (0..5).any(|bucket| {
+            self.packed
+                .bucket_slice(a, bucket)
+                .binary_search(&b)
+                .is_ok()
+        })
+    }
+
+    /// Endpoint marks of the edge between `a` and `b` as `(mark at a, mark at b)`,
+    /// or `None` if the nodes are not adjacent.
+    pub fn marks_between(&self, a: u32, b: u32) -> Option<(Mark, Mark)> {
+        self.core
+            .row_range(a)
+            .find(|&k| self.core.col_index[k] == b)
+            .map(|k| self.core.marks(k))
+    }
+
+    /// All definite ancestors of `i` via `-->` edges, returned in ascending order.
+    #[inline]
+    pub fn ancestors_of(&self, i: u32) -> Vec<u32> {
+        traversal::ancestors_of(self.n(), i, |u| self.parents_of(u))
+    }
+
+    /// All definite descendants of `i` via `-->` edges, returned in ascending order.
+    #[inline]
+    pub fn descendants_of(&self, i: u32) -> Vec<u32> {
+        traversal::descendants_of(self.n(), i, |u| self.children_of(u))
+    }
+
+    /// All definite anteriors of `i` (reachable via `---` or `-->` edges into `i`).
+    #[inline]
+    pub fn anteriors_of(&self, i: u32) -> Vec<u32> {
+        traversal::anteriors_of(
+            self.n(),
+            i,
+            |u| self.parents_of(u),
+            |u| self.undirected_of(u),
+        )
+    }
+
+    /// All definite posteriors of `i` (reachable via `---` or `-->` edges out of `i`).
+    #[inline]
+    pub fn posteriors_of(&self, i: u32) -> Vec<u32> {
+        traversal::posteriors_of(
+            self.n(),
+            i,
+            |u| self.children_of(u),
+            |u| self.undirected_of(u),
+        )
+    }
+
+    /// Possible ancestors of `i`: nodes with a possibly directed path into `i`.
+    ///
+    /// A path `v0 *-* v1 *-* ... *-* vk` is possibly directed when no edge
+    /// `vj *-* vj+1` has an arrowhead at `vj` or a tail at `vj+1` (Zhang, 2008).
+    /// Returned in ascending order, excluding `i`.
+    pub fn possible_ancestors_of(&self, i: u32) -> Vec<u32> {
+        // Walking backwards from c to u: the edge u *-* c must have no arrowhead
+        // at u and no tail at c.
+        self.possibly_directed_reach(i, |mine, theirs| {
+            theirs != Mark::Arrow && mine != Mark::Tail
+        })
+    }
+
+    /// Possible descendants of `i`: nodes reachable from `i` by a possibly directed path.
+    ///
+    /// See [`possible_ancestors_of`](Self::possible_ancestors_of) for the path definition.
+    /// Returned in ascending order, excluding `i`.
+    pub fn possible_descendants_of(&self, i: u32) -> Vec<u32> {
+        self.possibly_directed_reach(i, |mine, theirs| {
+            mine != Mark::Arrow && theirs != Mark::Tail
+        })
+    }
+
+    /// Definite ancestors of `i`: ancestors of `i` in every MAG of the class.
+    ///
+    /// `a` qualifies if a directed path runs from `a` to `i`, or if two
+    /// uncovered possibly directed paths `a, u, ..., i` and `a, v, ..., i`
+    /// start with non-adjacent `u` and `v` (Roumpelaki et al., 2016): `a` is
+    /// then a non-collider between `u` and `v` and must point into one of
+    /// them. With selection bias, `u — a — v` is also a non-collider, so at
+    /// least one of the two edges must have an arrowhead at `u` or `v`.
+    /// Without selection bias the rule needs no such edge and is complete
+    /// (Mooij & Claassen, 2020); here it is sound but may miss ancestors
+    /// whose only witnesses are `a o-o u` and `a o-o v`. Returned in
+    /// ascending order, excluding `i`.
+    pub fn definite_ancestors_of(&self, i: u32) -> Vec<u32> {
+        let g = MarkedGraph::new(&self.core, Semantics::Ancestral);
+        let directed = self.ancestors_of(i);
+        self.possible_ancestors_of(i)
+            .into_iter()
+            .filter(|&a| {
+                if directed.binary_search(&a).is_ok() {
+                    return true;
+                }
+                let starts: Vec<u32> = self
+                    .neighbors_of(a)
+                    .iter()
+                    .copied()
+                    .filter(|&w| g.possible_descendants_through(a, w)[i as usize])
+                    .collect();
+                // An arrowhead at `u` rules out `a — u`, and with it `a` as
+                // the middle of an undirected non-collider.
+                let into = |u: u32| self.marks_between(a, u).map(|(_, m)| m) == Some(Mark::Arrow);
+                starts.iter().enumerate().any(|(k, &u)| {
+                    starts[k + 1..]
+                        .iter()
+                        .any(|&v| !self.adjacent(u, v) && (into(u) || into(v)))
+                })
+            })
+            .collect()
+    }
+
+    /// Nodes reachable from `i` via half-edges accepted by `step(my_mark, nbr_mark)`.
+    fn possibly_directed_reach<F>(&self, i: u32, step: F) -> Vec<u32>
+    where
+        F: Fn(Mark, Mark) -> bool,
+    {
+        let n = self.n() as usize;
+        let mut seen = vec![false; n];
+        seen[i as usize] = true;
+        let mut stack = vec![i];
+        while let Some(u) = stack.pop() {
+            for k in self.core.row_range(u) {
+                let w = self.core.col_index[k];
+                let (mine, theirs) = self.core.marks(k);
+                if step(mine, theirs) && !std::mem::replace(&mut seen[w as usize], true) {
+                    stack.push(w);
+                }
+            }
+        }
+        seen[i as usize] = false;
+        bitset::collect_from_mask(&seen)
+    }
+
+    /// Nodes with no definite parents.
+    #[inline]
+    pub fn exogenous_nodes(&self) -> Vec<u32> {
+        (0..self.n())
+            .filter(|&i| self.parents_of(i).is_empty())
+            .collect()
+    }
+
+    /// Access the underlying CSR.
+    pub fn core_ref(&self) -> &CaugiGraph {
+        &self.core
+    }
+}
+
+impl crate::graph::traits::Acyclic for Pag {}
+impl crate::graph::traits::DirectedNeighbors for Pag {
+    #[inline]
+    fn n(&self) -> u32 {
+        Pag::n(self)
+    }
+    #[inline]
+    fn parents_of(&self, i: u32) -> &[u32] {
+        Pag::parents_of(self, i)
+    }
+    #[inline]
+    fn children_of(&self, i: u32) -> &[u32] {
+        Pag::children_of(self, i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::{EdgeClass, EdgeRegistry, EdgeSpec};
+    use crate::graph::builder::GraphBuilder;
+
+    fn setup() -> EdgeRegistry {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        reg
+    }
+
+    fn build(reg: &EdgeRegistry, n: u32, edges: &[(u32, u32, &str)]) -> Result<Pag, String> {
+        let mut b = GraphBuilder::new_with_registry(n, true, reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Pag::new(Arc::new(b.finalize().unwrap()))
+    }
+
+    #[test]
+    fn pag_buckets_by_marks() {
+        let reg = setup();
+        // 0 o-> 1 <-> 2, 1 --> 3, 3 o-o 4, 5 --- 6, 6 --o 4
+        let pag = build(
+            &reg,
+            7,
+            &[
+                (0, 1, "o->"),
+                (1, 2, "<->"),
+                (1, 3, "-->"),
+                (3, 4, "o-o"),
+                (5, 6, "---"),
+                (6, 4, "--o"),
+            ],
+        )
+        .expect("PAG construction failed");
+
+        assert_eq!(pag.n(), 7);
+        assert_eq!(pag.parents_of(3), &[1]);
+        assert_eq!(pag.children_of(1), &[3]);
+        assert_eq!(pag.spouses_of(1), &[2]);
+        assert_eq!(pag.undirected_of(5), &[6]);
+        assert_eq!(pag.circle_neighbors_of(1), &[0]);
+        assert_eq!(pag.circle_neighbors_of(4), &[3, 6]);
+        assert!(pag.parents_of(1).is_empty());
+        assert_eq!(pag.neighbors_of(1), &[2, 3, 0]);
+        assert!(pag.adjacent(0, 1));
+        assert!(!pag.adjacent(0, 2));
+        assert_eq!(pag.marks_between(0, 1), Some((Mark::Circle, Mark::Arrow)));
+        assert_eq!(pag.marks_between(1, 0), Some((Mark::Arrow, Mark::Circle)));
+        assert_eq!(pag.marks_between(6, 4), Some((Mark::Tail, Mark::Circle)));
+        assert_eq!(pag.marks_between(0, 2), None);
+        assert_eq!(pag.exogenous_nodes(), vec![0, 1, 2, 4, 5, 6]);
+    }
+
+    #[test]
+    fn pag_definite_ancestors_through_a_non_collider() {
+        let reg = setup();
+        // 0 o-o 1 o-> 3 <-o 2 o-o 0: 1 points into 3 or into 0 (and then on
+        // through 2 to 3), and likewise for 2. 0 is not definite: 1 — 0 — 2
+        // with 1 -> 3 <- 2 is a MAG of the class.
+        let pag = build(
+            &reg,
+            4,
+            &[(0, 1, "o-o"), (0, 2, "o-o"), (1, 3, "o->"), (2, 3, "o->")],
+        )
+        .unwrap();
+        assert_eq!(pag.possible_ancestors_of(3), vec![0, 1, 2]);
+        assert_eq!(pag.definite_ancestors_of(3), vec![1, 2]);
+
+        let pag = build(
+            &reg,
+            4,
+            &[(0, 1, "o-o"), (0, 2, "o-o"), (1, 3, "-->"), (2, 3, "-->")],
+        )
+        .unwrap();
+        assert_eq!(pag.definite_ancestors_of(3), vec![1, 2]);
+
+        // 1 <-o 0 o-o 2 -> 3 <- 1: 0 -> 1 or 0 -> 2, and both reach 3.
+        let pag = build(
+            &reg,
+            4,
+            &[(0, 1, "o->"), (0, 2, "o-o"), (1, 3, "-->"), (2, 3, "-->")],
+        )
+        .unwrap();
+        assert_eq!(pag.definite_ancestors_of(3), vec![0, 1, 2]);
+        assert_eq!(pag.possible_ancestors_of(1), vec![0, 2]);
+        assert_eq!(pag.definite_ancestors_of(1), Vec::<u32>::new());
+
+        // 0 o-> 1 <-o 2: 0 <-> 1 is possible, so 0 is only a possible ancestor.
+        let pag = build(&reg, 3, &[(0, 1, "o->"), (2, 1, "o->")]).unwrap();
+        assert_eq!(pag.possible_ancestors_of(1), vec![0, 2]);
+        assert_eq!(pag.definite_ancestors_of(1), Vec::<u32>::new());
+
+        // A directed path is always definite.
+        let pag = build(&reg, 3, &[(0, 1, "-->"), (1, 2, "-->")]).unwrap();
+        assert_eq!(pag.definite_ancestors_of(2), vec![0, 1]);
+    }
+
+    #[test]
+    fn pag_accepts_plain_ancestral_graphs() {
+        let reg = setup();
+        let pag = build(&reg, 3, &[(0, 1, "-->"), (1, 2, "<->")]).unwrap();
+        assert_eq!(pag.ancestors_of(1), vec![0]);
+        assert_eq!(pag.descendants_of(0), vec![1]);
+    }
+
+    #[test]
+    fn pag_rejects_invalid_marks() {
+        let mut reg = setup();
+        reg.register(EdgeSpec {
+            glyph: "-x-".into(),
+            tail: Mark::Other,
+            head: Mark::Other,
+            symmetric: true,
+            class: EdgeClass::Undirected,
+        })
+        .unwrap();
+        let err = build(&reg, 2, &[(0, 1, "-x-")]).unwrap_err();
+        assert!(err.contains("arrow, tail, or circle"));
+        assert!(err.contains("-x-"));
+    }
+
+    #[test]
+    fn pag_rejects_multiple_edges() {
+        let reg = setup();
+        let mut b = GraphBuilder::new_with_registry(2, false, &reg);
+        b.add_edge(0, 1, reg.code_of("o->").unwrap()).unwrap();
+        b.add_edge(0, 1, reg.code_of("<->").unwrap()).unwrap();
+        let err = Pag::new(Arc::new(b.finalize().unwrap())).unwrap_err();
+        assert!(err.contains("at most one edge"));
+    }
+
+    #[test]
+    fn pag_rejects_directed_cycle() {
+        let reg = setup();
+        let err = build(&reg, 3, &[(0, 1, "-->"), (1, 2, "-->"), (2, 0, "-->")]).unwrap_err();
+        assert!(err.contains("cycle"));
+    }
+
+    #[test]
+    fn pag_rejects_arrowhead_at_undirected_endpoint() {
+        let reg = setup();
+        // 1 has an undirected edge and an arrowhead from a partially directed edge
+        let err = build(&reg, 3, &[(0, 1, "---"), (2, 1, "o->")]).unwrap_err();
+        assert!(err.contains("Undirected constraint"));
+        // Circle marks at an undirected endpoint are fine
+        assert!(build(&reg, 3, &[(0, 1, "---"), (1, 2, "--o")]).is_ok());
+    }
+
+    #[test]
+    fn pag_rejects_anterior_violation() {
+        let reg = setup();
+        // 0 --> 1 --> 2 and 2 o-> 0: arrowhead at 0 although 0 is an ancestor of 2
+        let err = build(&reg, 3, &[(0, 1, "-->"), (1, 2, "-->"), (2, 0, "o->")]).unwrap_err();
+        assert!(err.contains("Anterior constraint"));
+        // 0 --> 1 --> 2 and 0 <-> 2 likewise
+        let err = build(&reg, 3, &[(0, 1, "-->"), (1, 2, "-->"), (0, 2, "<->")]).unwrap_err();
+        assert!(err.contains("Anterior constraint"));
+    }
+
+    #[test]
+    fn pag_possible_ancestors_and_descendants() {
+        let reg = setup();
+        // 0 o-o 1 o-> 2 <-> 3, 2 --> 4, 4 o-> 5
+        let pag = build(
+            &reg,
+            6,
+            &[
+                (0, 1, "o-o"),
+                (1, 2, "o->"),
+                (2, 3, "<->"),
+                (2, 4, "-->"),
+                (4, 5, "o->"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(pag.possible_descendants_of(0), vec![1, 2, 4, 5]);
+        assert_eq!(pag.possible_descendants_of(2), vec![4, 5]);
+        assert_eq!(pag.possible_descendants_of(3), Vec::<u32>::new());
+        assert_eq!(pag.possible_ancestors_of(2), vec![0, 1]);
+        assert_eq!(pag.possible_ancestors_of(5), vec![0, 1, 2, 4]);
+        assert_eq!(pag.possible_ancestors_of(0), vec![1]);
+        // Definite relations ignore circle edges
+        assert_eq!(pag.ancestors_of(5), Vec::<u32>::new());
+        assert_eq!(pag.descendants_of(2), vec![4]);
+        assert_eq!(pag.anteriors_of(4), vec![2]);
+        assert_eq!(pag.posteriors_of(2), vec![4]);
+    }
+
+    #[test]
+    fn pag_possible_relations_stop_at_tails_and_arrowheads() {
+        let reg = setup();
+        // 0 --o 1: tail at 0, circle at 1, so 1 is a possible descendant of 0
+        // but 0 is not a possible descendant of 1.
+        let pag = build(&reg, 3, &[(0, 1, "--o"), (1, 2, "---")]).unwrap();
+        assert_eq!(pag.possible_descendants_of(0), vec![1]);
+        assert!(pag.possible_descendants_of(1).is_empty());
+        assert_eq!(pag.possible_ancestors_of(1), vec![0]);
+        assert!(pag.possible_ancestors_of(2).is_empty());
+    }
+
+    #[test]
+    fn pag_empty_graph() {
+        let reg = setup();
+        let pag = build(&reg, 0, &[]).unwrap();
+        assert_eq!(pag.n(), 0);
+        assert_eq!(pag.core_ref().n(), 0);
+    }
+}
@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: MIT
+//! Graph transformations for PAGs.
+
+use super::Pag;
+use crate::edges::Mark;
+use crate::graph::ag::Ag;
+use crate::graph::alg::csr;
+use crate::graph::alg::fci::{self, MarkMatrix};
+use crate::graph::builder::GraphBuilder;
+use crate::graph::RegistrySnapshot;
+use std::sync::Arc;
+
+impl Pag {
+    /// Dense endpoint-mark matrix of this PAG.
+    pub(crate) fn mark_matrix(&self) -> MarkMatrix {
+        MarkMatrix::from_core(self.core_ref())
+    }
+
+    /// Build a PAG from an endpoint-mark matrix, picking edge codes from `registry`.
+    pub(crate) fn from_mark_matrix(
+        registry: &RegistrySnapshot,
+        g: &MarkMatrix,
+    ) -> Result<Pag, String> {
+        let n = g.n() as u32;
+        let mut b = GraphBuilder::new_from_snapshot(n, true, Arc::new(registry.clone()));
+        for u in 0..n {
+            for &v in g.neighbors(u) {
+                if v < u {
+                    continue;
+                }
+                let at_u = g.at(v, u).expect("adjacent nodes have marks");
+                let at_v = g.at(u, v).expect("adjacent nodes have marks");
+                let (code, reversed) = csr::code_for_marks(registry, at_u, at_v)?;
+                if reversed {
+                    b.add_edge(v, u, code)?;
+                } else {
+                    b.add_edge(u, v, code)?;
+                }
+            }
+        }
+        Pag::new(Arc::new(b.finalize()?))
+    }
+
+    /// Apply the FCI orientation rules R1–R10 (Zhang, 2008) and return the
+    /// maximally informative PAG.
+    ///
+    /// The input is expected to be a PAG skeleton with its unshielded colliders
+    /// already oriented, as produced by the orientation phase of FCI or RFCI.
+    /// `in_sepset(theta, gamma, beta)` must report whether `beta` is in the
+    /// separating set recorded for the non-adjacent pair `(theta, gamma)`; it
+    /// is only consulted by R4 on discriminating paths.
+    pub fn fci_closure<F>(&self, in_sepset: F) -> Result<Pag, String>
+    where
+        F: Fn(u32, u32, u32) -> bool,
+    {
+        let mut g = self.mark_matrix();
+        fci::apply_fci_closure(&mut g, in_sepset);
+        Pag::from_mark_matrix(&self.core_ref().registry, &g)
+    }
+
+    /// A MAG in the equivalence class represented by this (complete) PAG.
+    ///
+    /// Partially directed edges `o->` and `--o` become directed, and the
+    /// `o-o` subgraph is oriented acyclically without unshielded colliders
+    /// by following a maximum cardinality search order (Zhang, 2008,
+    /// Theorem 2).
+    ///
+    /// # References
+    ///
+    /// J. Zhang (2008). On the completeness of orientation rules for causal
+    /// discovery in the presence of latent confounders and selection bias.
+    /// *Artificial Intelligence*, 172(16–17), 1873–1896.
+    pub fn representative_mag(&self) -> Result<Ag, String> {
+        let n = self.n();
+        let core = self.core_ref();
+        let mut edges: Vec<(u32, u32, Mark, Mark)> = Vec::new();
+        let mut circle: Vec<Vec<u32>> = vec![Vec::new(); n as usize];
+        for a in 0..n {
+            for k in core.row_range(a) {
+                let b = core.col_index[k];
+                let (at_a, at_b) = core.marks(k);
+                if a > b {
+                    continue;
+                }
+                let (at_a, at_b) = match (at_a, at_b) {
+                    (Mark::Circle, Mark::Circle) => {
+                        circle[a as usize].push(b);
+                        circle[b as usize].push(a);
+                        continue;
+                    }
+                    (Mark::Circle, Mark::Arrow) => (Mark::Tail, Mark::Arrow),
+                    (Mark::Arrow, Mark::Circle) => (Mark::Arrow, Mark::Tail),
+                    (Mark::Circle, Mark::Tail) => (Mark::Arrow, Mark::Tail),
+                    (Mark::Tail, Mark::Circle) => (Mark::Tail, Mark::Arrow),
+                    marks => marks,
+                };
+                edges.push((a, b, at_a, at_b));
+            }
+        }
+
+        // Maximum cardinality search over the o-o subgraph; earlier nodes
+        // in the visit order become parents of later ones.
+        let mut order = vec![u32::MAX; n as usize];
+        let mut weight = vec![0usize; n as usize];
+        for step in 0..n {
+            let v = (0..n)
+                .filter(|&v| order[v as usize] == u32::MAX)
+                .max_by_key(|&v| (weight[v as usize], std::cmp::Reverse(v)))
+                .expect("unvisited node remains");
+            order[v as usize] = step;
+            for &w in &circle[v as usize] {
+                weight[w as usize] += 1;
+            }
+        }
+        for a in 0..n {
+            for &b in &circle[a as usize] {
+                if order[a as usize] < order[b as usize] {
+                    edges.push((a, b, Mark::Tail, Mark::Arrow));
+                }
+            }
+        }
+        Ag::from_marked_edges(&core.registry, n, edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+
+    fn build(reg: &EdgeRegistry, n: u32, edges: &[(u32, u32, &str)]) -> Pag {
+        let mut b = GraphBuilder::new_with_registry(n, true, reg);
+        for &(u, v, g) in edges {
+            b.add_edge(u, v, reg.code_of(g).unwrap()).unwrap();
+        }
+        Pag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    fn setup() -> EdgeRegistry {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        reg
+    }
+
+    #[test]
+    fn mark_matrix_roundtrip_preserves_marks() {
+        let reg = setup();
+        let pag = build(&reg, 4, &[(0, 1, "o->"), (1, 2, "<->"), (3, 2, "--o")]);
+        let g = pag.mark_matrix();
+        let back = Pag::from_mark_matrix(&pag.core_ref().registry, &g).unwrap();
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (1, 0), (0, 3)] {
+            assert_eq!(back.marks_between(a, b), pag.marks_between(a, b));
+        }
+        // `o--` is not a glyph; the edge is stored reversed as `--o`
+        assert_eq!(back.marks_between(2, 3), Some((Mark::Circle, Mark::Tail)));
+    }
+
+    #[test]
+    fn fci_closure_r1_chain() {
+        // 0 o-> 1 o-o 2 o-o 3  =>  0 o-> 1 --> 2 --> 3
+        let reg = setup();
+        let pag = build(&reg, 4, &[(0, 1, "o->"), (1, 2, "o-o"), (2, 3, "o-o")]);
+        let closed = pag.fci_closure(|_, _, _| false).unwrap();
+        assert_eq!(closed.children_of(1), &[2]);
+        assert_eq!(closed.children_of(2), &[3]);
+        assert_eq!(closed.circle_neighbors_of(1), &[0]);
+        assert_eq!(
+            closed.marks_between(0, 1),
+            Some((Mark::Circle, Mark::Arrow))
+        );
+    }
+
+    #[test]
+    fn fci_closure_discriminating_path() {
+        // Discriminating path 0 *-> 1 <-> 2 for 2, with 1 --> 3 and 2 o-o 3.
+        let reg = setup();
+        let pag = build(
+            &reg,
+            4,
+            &[(0, 1, "o->"), (2, 1, "o->"), (1, 3, "-->"), (2, 3, "o-o")],
+        );
+
+        let non_collider = pag.fci_closure(|t, g, b| (t, g, b) == (0, 3, 2)).unwrap();
+        assert_eq!(non_collider.children_of(2), &[3]);
+
+        let collider = pag.fci_closure(|_, _, _| false).unwrap();
+        assert_eq!(collider.spouses_of(2), &[1, 3]);
+    }
+
+    #[test]
+    fn fci_closure_is_idempotent_and_keeps_skeleton() {
+        let reg = setup();
+        // 0 o-o 1 o-o 2 o-o 3 o-o 0: R5 turns the chordless cycle undirected
+        let pag = build(
+            &reg,
+            4,
+            &[(0, 1, "o-o"), (1, 2, "o-o"), (2, 3, "o-o"), (3, 0, "o-o")],
+        );
+        let once = pag.fci_closure(|_, _, _| false).unwrap();
+        assert_eq!(once.undirected_of(0), &[1, 3]);
+        assert!(once.circle_neighbors_of(0).is_empty());
+        let twice = once.fci_closure(|_, _, _| false).unwrap();
+        for a in 0..4 {
+            assert_eq!(once.neighbors_of(a), twice.neighbors_of(a));
+            for b in 0..4 {
+                assert_eq!(once.marks_between(a, b), twice.marks_between(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn representative_mag_orients_circles_without_new_colliders() {
+        // 0 o-> 1 <-o 2, 1 --o 3, 3 o-o 4 o-o 5
+        let reg = setup();
+        let pag = build(
+            &reg,
+            6,
+            &[
+                (0, 1, "o->"),
+                (2, 1, "o->"),
+                (1, 3, "--o"),
+                (3, 4, "o-o"),
+                (4, 5, "o-o"),
+            ],
+        );
+        let mag = pag.representative_mag().unwrap();
+        assert!(mag.is_mag());
+        assert_eq!(mag.parents_of(1), &[0, 2]);
+        assert_eq!(mag.children_of(1), &[3]);
+        // The o-o chain 3 o-o 4 o-o 5 must not gain a collider at 4.
+        assert!(mag.parents_of(4).len() <= 1);
+        assert!(mag.spouses_of(4).is_empty());
+    }
+}
@@ -280,7 +280,23 @@ impl Pdag {
             }
         }
 
-        // R4: u--v and (u ⇒ v or v ⇒ u)
+        // R4: u--v, u--w, w->x->v, w !~ v, u ~ x
+        for v in 0..self.n() {
+            for &u in self.undirected_of(v) {
+                for &x in self.parents_of(v) {
+                    if self.adjacent(u, x)
+                        && self
+                            .parents_of(x)
+                            .iter()
+                            .any(|&w| !self.adjacent(w, v) && self.undirected_of(u).contains(&w))
+                    {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        // Acyclicity: u--v and (u ⇒ v or v ⇒ u)
         for v in 0..self.n() {
             for &u in self.undirected_of(v) {
                 if self.has_dir_path(u, v) || self.has_dir_path(v, u) {
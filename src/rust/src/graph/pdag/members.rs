@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: MIT
+//! Member DAGs of a PDAG: lazy enumeration and uniform sampling.
+//!
+//! The members of a PDAG are its consistent extensions: DAGs with the same
+//! skeleton and unshielded colliders that keep every arrow. For an MPDAG these
+//! are exactly the DAGs it represents; for a CPDAG, its Markov equivalence
+//! class.
+
+use super::transforms::dor_tarsi;
+use super::Pdag;
+use crate::edges::Mark;
+use crate::graph::alg::csr;
+use crate::graph::dag::Dag;
+
+/// Lazy iterator over the member DAGs of a PDAG.
+///
+/// Undirected edges are oriented one at a time (lower index as tail first),
+/// and a branch is only entered if it creates no new unshielded collider and
+/// still admits a consistent extension. Every branch therefore ends in a
+/// member, and the delay between two members is polynomial.
+pub struct MemberDags {
+    pdag: Pdag,
+    /// Arrows of the PDAG, per tail.
+    children: Vec<Vec<u32>>,
+    /// Undirected edges `(u, v)`, `u < v`, in the order they are decided.
+    undirected: Vec<(u32, u32)>,
+    /// Partial assignments still to expand; `true` orients `u → v`.
+    stack: Vec<Vec<bool>>,
+}
+
+impl MemberDags {
+    fn new(pdag: &Pdag) -> Self {
+        let n = pdag.n();
+        let children = (0..n).map(|u| pdag.children_of(u).to_vec()).collect();
+        let undirected = (0..n)
+            .flat_map(|u| {
+                pdag.undirected_of(u)
+                    .iter()
+                    .filter(move |&&v| u < v)
+                    .map(move |&v| (u, v))
+            })
+            .collect();
+        let mut it = Self {
+            pdag: pdag.clone(),
+            children,
+            undirected,
+            stack: Vec::new(),
+        };
+        if it.extendable(&[]) {
+            it.stack.push(Vec::new());
+        }
+        it
+    }
+
+    fn oriented(&self, i: usize, forward: bool) -> (u32, u32) {
+        let (u, v) = self.undirected[i];
+        if forward {
+            (u, v)
+        } else {
+            (v, u)
+        }
+    }
+
+    /// Whether some member orients the first `choice.len()` undirected edges
+    /// as in `choice`.
+    fn extendable(&self, choice: &[bool]) -> bool {
+        let decided: Vec<(u32, u32)> = choice
+            .iter()
+            .enumerate()
+            .map(|(i, &f)| self.oriented(i, f))
+            .collect();
+        self.pdag.admits_member_with(&decided)
+    }
+}
+
+impl Iterator for MemberDags {
+    type Item = Dag;
+
+    fn next(&mut self) -> Option<Dag> {
+        while let Some(choice) = self.stack.pop() {
+            if choice.len() == self.undirected.len() {
+                let arrows = self
+                    .children
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(u, ch)| ch.iter().map(move |&c| (u as u32, c)));
+                let decided = choice.iter().enumerate().map(|(i, &f)| self.oriented(i, f));
+                let dag = self
+                    .pdag
+                    .dag_from_arrows(arrows.chain(decided))
+                    .expect("a member's arrows form a valid DAG");
+                return Some(dag);
+            }
+            // Push `v → u` first so that `u → v` is expanded first.
+            for f in [false, true] {
+                let mut next = choice.clone();
+                next.push(f);
+                if self.extendable(&next) {
+                    self.stack.push(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Pdag {
+    /// Whether some member DAG contains every arrow in `arrows`, each of which
+    /// orients an undirected edge.
+    ///
+    /// The arrows must not form a new unshielded collider, and the PDAG with
+    /// them added must still admit a consistent extension.
+    pub(crate) fn admits_member_with(&self, arrows: &[(u32, u32)]) -> bool {
+        let n = self.n() as usize;
+        let mut children: Vec<Vec<u32>> = (0..n as u32)
+            .map(|u| self.children_of(u).to_vec())
+            .collect();
+        let mut undirected: Vec<Vec<u32>> = (0..n as u32)
+            .map(|u| self.undirected_of(u).to_vec())
+            .collect();
+        for &(t, h) in arrows {
+            children[t as usize].push(h);
+            undirected[t as usize].retain(|&v| v != h);
+            undirected[h as usize].retain(|&v| v != t);
+        }
+        let collider = arrows.iter().any(|&(t, h)| {
+            children
+                .iter()
+                .enumerate()
+                .any(|(p, ch)| p as u32 != t && ch.contains(&h) && !self.adjacent(p as u32, t))
+        });
+        !collider && dor_tarsi(&children, &undirected, |a, b| self.adjacent(a, b)).is_some()
+    }
+
+    /// Lazily enumerate every member DAG (consistent extension).
+    ///
+    /// Yields nothing if the PDAG admits no consistent extension. Errors only
+    /// if the registry has no directed edge type.
+    pub fn member_dags(&self) -> Result<MemberDags, String> {
+        csr::code_for_marks(&self.core_ref().registry, Mark::Tail, Mark::Arrow)?;
+        Ok(MemberDags::new(self))
+    }
+
+    /// Draw `count` member DAGs independently and uniformly at random,
+    /// reproducibly for a given `seed`.
+    ///
+    /// Members are sampled uniformly from the Markov equivalence class of a
+    /// consistent extension (see [`Cpdag::sample_dags`]) and kept if they
+    /// contain every arrow of this PDAG. The expected number of draws per
+    /// member is the class size over the number of members, so heavy
+    /// background knowledge makes sampling slower; a CPDAG never rejects.
+    ///
+    /// [`Cpdag::sample_dags`]: crate::graph::cpdag::Cpdag::sample_dags
+    pub fn sample_dags(&self, count: usize, seed: u64) -> Result<Vec<Dag>, String> {
+        let cpdag = self.consistent_extension()?.to_cpdag()?;
+        let required: Vec<(u32, u32)> = (0..self.n())
+            .flat_map(|u| self.children_of(u).iter().map(move |&c| (u, c)))
+            .collect();
+        cpdag
+            .sample_arrow_sets(count, seed, |arrows| {
+                required.iter().all(|e| arrows.binary_search(e).is_ok())
+            })
+            .into_iter()
+            .map(|arrows| self.dag_from_arrows(arrows))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::builder::GraphBuilder;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    fn pdag(n: u32, directed: &[(u32, u32)], undirected: &[(u32, u32)]) -> Pdag {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let u = reg.code_of("---").unwrap();
+        let mut b = GraphBuilder::new_with_registry(n, true, &reg);
+        for &(a, c) in directed {
+            b.add_edge(a, c, d).unwrap();
+        }
+        for &(a, c) in undirected {
+            b.add_edge(a, c, u).unwrap();
+        }
+        Pdag::new(Arc::new(b.finalize().unwrap())).unwrap()
+    }
+
+    fn arrows(d: &Dag) -> Vec<(u32, u32)> {
+        (0..d.n())
+            .flat_map(|u| d.children_of(u).iter().map(move |&c| (u, c)))
+            .collect()
+    }
+
+    #[test]
+    fn member_dags_of_a_chain_and_a_collider() {
+        // 0 - 1 - 2: three members, 1 never a collider.
+        let p = pdag(3, &[], &[(0, 1), (1, 2)]);
+        let all: Vec<Vec<(u32, u32)>> = p.member_dags().unwrap().map(|d| arrows(&d)).collect();
+        assert_eq!(
+            all,
+            vec![
+                vec![(0, 1), (1, 2)],
+                vec![(1, 0), (1, 2)],
+                vec![(1, 0), (2, 1)],
+            ]
+        );
+        // A lone v-structure is its only member.
+        let p = pdag(3, &[(0, 2), (1, 2)], &[]);
+        assert_eq!(p.member_dags().unwrap().count(), 1);
+        // 0 -> 1 - 2 with 0, 2 non-adjacent forces 1 -> 2 in every member.
+        let p = pdag(3, &[(0, 1)], &[(1, 2)]);
+        let all: Vec<Dag> = p.member_dags().unwrap().collect();
+        assert_eq!(all.len(), 1);
+        assert_eq!(arrows(&all[0]), vec![(0, 1), (1, 2)]);
+        // 0 - 1 - 2 - 3 - 0 is not chordal: no extension, no members.
+        let p = pdag(4, &[], &[(0, 1), (1, 2), (2, 3), (0, 3)]);
+        assert_eq!(p.member_dags().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn member_dags_match_mec_size_and_respect_background_knowledge() {
+        // Fan: 0 joined to the path 1-2-3-4-5, a CPDAG with 18 members.
+        let und = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (0, 5),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+        ];
+        let p = pdag(6, &[], &und);
+        let members: HashSet<Vec<(u32, u32)>> =
+            p.member_dags().unwrap().map(|d| arrows(&d)).collect();
+        assert_eq!(members.len(), 18);
+
+        // Background knowledge 0 -> 1 (closed under Meek) keeps exactly the
+        // members that contain it.
+        let rest: Vec<(u32, u32)> = und[1..].to_vec();
+        let m = pdag(6, &[(0, 1)], &rest).to_mpdag().unwrap();
+        let sub: HashSet<Vec<(u32, u32)>> = m.member_dags().unwrap().map(|d| arrows(&d)).collect();
+        let expected: HashSet<Vec<(u32, u32)>> = members
+            .iter()
+            .filter(|a| a.contains(&(0, 1)))
+            .cloned()
+            .collect();
+        assert_eq!(sub, expected);
+    }
+
+    #[test]
+    fn sample_dags_is_seeded_and_stays_in_the_class() {
+        // 0 -> 1 - 2 - 3 - 1 (triangle 1-2-3): only members with 0 -> 1.
+        let m = pdag(4, &[(0, 1)], &[(1, 2), (2, 3), (1, 3)])
+            .to_mpdag()
+            .unwrap();
+        let members: HashSet<Vec<(u32, u32)>> =
+            m.member_dags().unwrap().map(|d| arrows(&d)).collect();
+        let a = m.sample_dags(200, 3).unwrap();
+        let b = m.sample_dags(200, 3).unwrap();
+        let a: Vec<Vec<(u32, u32)>> = a.iter().map(arrows).collect();
+        let b: Vec<Vec<(u32, u32)>> = b.iter().map(arrows).collect();
+        assert_eq!(a, b);
+        assert!(a.iter().all(|d| members.contains(d)));
+        let seen: HashSet<&Vec<(u32, u32)>> = a.iter().collect();
+        assert_eq!(seen.len(), members.len());
+    }
+}
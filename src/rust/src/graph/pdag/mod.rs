@@ -2,8 +2,11 @@
 //! Pdag wrapper with O(1) slice queries via packed neighborhoods.
 
 mod cpdag;
+mod members;
 mod transforms;
 
+pub use members::MemberDags;
+
 use super::error::PdagError;
 use super::packed::{PackedBuckets, PackedBucketsBuilder};
 use super::CaugiGraph;
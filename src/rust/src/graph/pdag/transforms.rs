@@ -2,8 +2,10 @@
 //! Graph transformations for PDAGs.
 
 use super::Pdag;
-use crate::edges::EdgeClass;
+use crate::edges::{EdgeClass, Mark};
 use crate::graph::alg::{csr, meek};
+use crate::graph::builder::GraphBuilder;
+use crate::graph::dag::Dag;
 use crate::graph::mpdag::Mpdag;
 use crate::graph::ug::Ug;
 use crate::graph::CaugiGraph;
@@ -130,6 +132,92 @@ impl Pdag {
     pub fn meek_closure(&self) -> Result<Mpdag, String> {
         self.to_mpdag()
     }
+
+    /// A consistent DAG extension: orient every undirected edge without
+    /// creating a directed cycle or a new unshielded collider.
+    ///
+    /// Uses the Dor–Tarsi elimination: repeatedly remove a node with no
+    /// remaining children whose undirected neighbours are adjacent to all of
+    /// its other remaining neighbours, orienting its undirected edges into it.
+    /// Every CPDAG and MPDAG admits an extension; other PDAGs may not.
+    ///
+    /// # References
+    ///
+    /// D. Dor and M. Tarsi (1992). A simple algorithm to construct a consistent
+    /// extension of a partially oriented graph. Technical Report R-185, UCLA.
+    pub fn consistent_extension(&self) -> Result<Dag, String> {
+        let n = self.n();
+        let children: Vec<Vec<u32>> = (0..n).map(|u| self.children_of(u).to_vec()).collect();
+        let undirected: Vec<Vec<u32>> = (0..n).map(|u| self.undirected_of(u).to_vec()).collect();
+        let edges = dor_tarsi(&children, &undirected, |a, b| self.adjacent(a, b))
+            .ok_or("PDAG admits no consistent DAG extension")?;
+        self.dag_from_arrows(edges)
+    }
+
+    /// A `Dag` over this graph's nodes and registry with the given arrows.
+    pub(crate) fn dag_from_arrows(
+        &self,
+        arrows: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Dag, String> {
+        let registry = &self.core_ref().registry;
+        let (code, reversed) = csr::code_for_marks(registry, Mark::Tail, Mark::Arrow)?;
+        let mut b = GraphBuilder::new_from_snapshot(self.n(), true, Arc::new(registry.clone()));
+        for (u, v) in arrows {
+            if reversed {
+                b.add_edge(v, u, code)?;
+            } else {
+                b.add_edge(u, v, code)?;
+            }
+        }
+        Dag::new(Arc::new(b.finalize()?))
+    }
+}
+
+/// Dor–Tarsi elimination on the partially directed graph with arrows
+/// `children` and undirected edges `undirected` (both per node).
+///
+/// Returns every arrow of a consistent extension (the given ones first), or
+/// `None` if there is none. `adjacent` must agree with the adjacency lists.
+pub(super) fn dor_tarsi(
+    children: &[Vec<u32>],
+    undirected: &[Vec<u32>],
+    adjacent: impl Fn(u32, u32) -> bool,
+) -> Option<Vec<(u32, u32)>> {
+    let n = children.len();
+    let mut parents = vec![Vec::new(); n];
+    let mut edges = Vec::new();
+    for (u, ch) in children.iter().enumerate() {
+        for &c in ch {
+            parents[c as usize].push(u as u32);
+            edges.push((u as u32, c));
+        }
+    }
+    let mut alive = vec![true; n];
+    for _ in 0..n {
+        let removable = |x: usize| {
+            if children[x].iter().any(|&c| alive[c as usize]) {
+                return false;
+            }
+            let nbrs: Vec<u32> = parents[x]
+                .iter()
+                .chain(&undirected[x])
+                .copied()
+                .filter(|&v| alive[v as usize])
+                .collect();
+            undirected[x]
+                .iter()
+                .filter(|&&y| alive[y as usize])
+                .all(|&y| nbrs.iter().all(|&z| z == y || adjacent(y, z)))
+        };
+        let x = (0..n).find(|&x| alive[x] && removable(x))?;
+        for &y in &undirected[x] {
+            if alive[y as usize] {
+                edges.push((y, x as u32));
+            }
+        }
+        alive[x] = false;
+    }
+    Some(edges)
 }
 
 #[cfg(test)]
@@ -419,6 +507,26 @@ mod tests {
         assert!(m.is_meek_closed());
     }
 
+    #[test]
+    fn meek_closure_orients_meeks_r4() {
+        // 0 -> 2 -> 4, 0 - 3, 2 - 3, 3 - 4, 0 !~ 4: orienting 4 -> 3 would force
+        // 2 -> 3 and 0 -> 3, a new collider 0 -> 3 <- 4, so 3 -> 4 is compelled.
+        let (reg, d, u) = setup();
+        let mut b = GraphBuilder::new_with_registry(5, true, &reg);
+        b.add_edge(0, 2, d).unwrap();
+        b.add_edge(2, 4, d).unwrap();
+        b.add_edge(0, 3, u).unwrap();
+        b.add_edge(2, 3, u).unwrap();
+        b.add_edge(3, 4, u).unwrap();
+        let p = Pdag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert!(!p.is_meek_closed());
+
+        let m = p.meek_closure().unwrap();
+        assert_eq!(m.children_of(3), &[4]);
+        assert_eq!(m.undirected_of(3), &[0, 2]);
+        assert!(m.is_meek_closed());
+    }
+
     #[test]
     fn meek_closure_multi_rule_regression() {
         // causal-learn style: A--B--C, A->D<-C, B--D, D--E, C--E
@@ -563,4 +671,31 @@ mod tests {
         assert!(m.undirected_of(3).contains(&2));
         assert!(m.is_meek_closed());
     }
+
+    #[test]
+    fn pdag_consistent_extension_adds_no_new_collider() {
+        let (reg, d, u) = setup();
+        // 0 --> 1 --- 2 --- 3: extension must orient 1 --> 2 --> 3.
+        let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+        b.add_edge(0, 1, d).unwrap();
+        b.add_edge(1, 2, u).unwrap();
+        b.add_edge(2, 3, u).unwrap();
+        let p = Pdag::new(Arc::new(b.finalize().unwrap())).unwrap();
+
+        let dag = p.consistent_extension().unwrap();
+        assert_eq!(dag.parents_of(1), &[0]);
+        assert_eq!(dag.parents_of(2), &[1]);
+        assert_eq!(dag.parents_of(3), &[2]);
+    }
+
+    #[test]
+    fn pdag_consistent_extension_rejects_chordless_cycle() {
+        let (reg, _, u) = setup();
+        let mut b = GraphBuilder::new_with_registry(4, true, &reg);
+        for (a, c) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            b.add_edge(a, c, u).unwrap();
+        }
+        let p = Pdag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        assert!(p.consistent_extension().is_err());
+    }
 }
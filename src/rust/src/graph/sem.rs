@@ -0,0 +1,502 @@
+// SPDX-License-Identifier: MIT
+//! Linear structural equation models on DAGs and ADMGs.
+//!
+//! Each node is `X_v = Σ_{u → v} β_uv X_u + ε_v`, where the errors have
+//! covariance `Ω`: diagonal entries are error variances and `ω_uv` may be
+//! non-zero only when `u <-> v`. With `B = (β_uv)` the model-implied
+//! covariance is `Σ = (I − B)⁻ᵀ Ω (I − B)⁻¹`, and `(I − B)⁻¹` holds the total
+//! effects. Everything is exact; no sampling is involved.
+//!
+//! # References
+//!
+//! S. Sullivant, K. Talaska and J. Draisma (2010). Trek separation for
+//! Gaussian graphical models. *The Annals of Statistics*, 38(3), 1665–1685.
+
+use super::alg::linalg;
+use super::alg::topological_sort;
+use super::{CaugiGraph, GraphView};
+use crate::edges::EdgeClass;
+
+/// A linear SEM with fixed coefficients and error covariance.
+#[derive(Debug, Clone)]
+pub struct LinearSem {
+    p: usize,
+    /// `(u, β_uv)` for each parent `u` of `v`, sorted by `u`.
+    parents: Vec<Vec<(u32, f64)>>,
+    /// `<->` neighbours of each node, sorted.
+    spouses: Vec<Vec<u32>>,
+    /// Error covariance `Ω`, `p × p` row-major.
+    omega: Vec<f64>,
+    order: Vec<u32>,
+}
+
+/// A trek from `i` to `j`: a directed path down to `i` and one down to `j`,
+/// whose tops coincide or are joined by `<->`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trek {
+    /// Nodes from `i` up to the top of the left side.
+    pub left: Vec<u32>,
+    /// Nodes from `j` up to the top of the right side.
+    pub right: Vec<u32>,
+    /// Product of the coefficients on both sides and the error (co)variance
+    /// at the top.
+    pub weight: f64,
+}
+
+impl LinearSem {
+    /// Linear SEM on a DAG or ADMG.
+    ///
+    /// `weights` is keyed by CSR position, as in
+    /// [`Dag::simulate_linear_sem`](super::dag::Dag::simulate_linear_sem):
+    /// `β_uv` is `weights[k]` for the half-edge `k` of `u → v` in the row of
+    /// `v`, and `ω_uv` for `u <-> v` is read from the half-edge in the row of
+    /// `min(u, v)`. Other entries are ignored. `error_var` holds one variance
+    /// per node, or a single variance shared by all.
+    pub fn new(view: &GraphView, weights: &[f64], error_var: &[f64]) -> Result<Self, String> {
+        Self::build(view, weights, Some(error_var))
+    }
+
+    /// Linear SEM from the coefficients alone, with `Ω = 0`.
+    ///
+    /// Total effects depend only on `B`, so this skips the error covariance
+    /// and its positive semi-definiteness check. `<->` weights are ignored.
+    pub fn from_coefficients(view: &GraphView, weights: &[f64]) -> Result<Self, String> {
+        Self::build(view, weights, None)
+    }
+
+    fn build(view: &GraphView, weights: &[f64], error_var: Option<&[f64]>) -> Result<Self, String> {
+        if !matches!(view, GraphView::Dag(_) | GraphView::Admg(_)) {
+            return Err("a linear SEM needs a DAG or ADMG".into());
+        }
+        let core = view.core();
+        let p = core.n() as usize;
+        if weights.len() != core.col_index.len() {
+            return Err(format!(
+                "weights has length {}, expected one per CSR position ({})",
+                weights.len(),
+                core.col_index.len()
+            ));
+        }
+        if weights.iter().any(|x| !x.is_finite()) {
+            return Err("weights must be finite".into());
+        }
+        if let Some(error_var) = error_var {
+            if error_var.len() != p && error_var.len() != 1 {
+                return Err(format!(
+                    "error_var has length {}, expected 1 or {}",
+                    error_var.len(),
+                    p
+                ));
+            }
+            if error_var.iter().any(|v| !(v.is_finite() && *v >= 0.0)) {
+                return Err("error variances must be finite and non-negative".into());
+            }
+        }
+
+        let mut parents = vec![Vec::new(); p];
+        let mut spouses = vec![Vec::new(); p];
+        let mut omega = vec![0.0; p * p];
+        for v in 0..p {
+            if let Some(error_var) = error_var {
+                omega[v * p + v] = error_var[if error_var.len() == 1 { 0 } else { v }];
+            }
+            for k in core.row_range(v as u32) {
+                let u = core.col_index[k];
+                match core.spec(k).class {
+                    EdgeClass::Directed if core.is_incoming_arrow(k) => {
+                        parents[v].push((u, weights[k]));
+                    }
+                    EdgeClass::Bidirected => {
+                        spouses[v].push(u);
+                        if error_var.is_some() && (v as u32) < u {
+                            omega[v * p + u as usize] = weights[k];
+                            omega[u as usize * p + v] = weights[k];
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            parents[v].sort_unstable_by_key(|&(u, _)| u);
+            spouses[v].sort_unstable();
+        }
+        if error_var.is_some() && linalg::cholesky(&omega, p).is_none() {
+            return Err("error covariance is not positive semi-definite".into());
+        }
+        let order = topological_sort(core);
+        if order.len() != p {
+            return Err("directed part contains a cycle".into());
+        }
+        Ok(Self {
+            p,
+            parents,
+            spouses,
+            omega,
+            order,
+        })
+    }
+
+    /// Number of variables.
+    pub fn n(&self) -> u32 {
+        self.p as u32
+    }
+
+    /// Error covariance `Ω`, `p × p` row-major.
+    pub fn error_covariance(&self) -> &[f64] {
+        &self.omega
+    }
+
+    /// Model-implied covariance `Σ = (I − B)⁻ᵀ Ω (I − B)⁻¹`, `p × p`
+    /// row-major.
+    pub fn implied_covariance(&self) -> Vec<f64> {
+        let p = self.p;
+        // Row k of `t` holds the total effects of k, i.e. row k of (I − B)⁻¹.
+        let t: Vec<f64> = (0..p as u32)
+            .flat_map(|k| self.effects_from(k, &[]))
+            .collect();
+        // Σ = Tᵀ (Ω T).
+        let mut omega_t = vec![0.0; p * p];
+        for k in 0..p {
+            for l in 0..p {
+                let w = self.omega[k * p + l];
+                if w != 0.0 {
+                    for j in 0..p {
+                        omega_t[k * p + j] += w * t[l * p + j];
+                    }
+                }
+            }
+        }
+        let mut sigma = vec![0.0; p * p];
+        for k in 0..p {
+            for i in 0..p {
+                let tki = t[k * p + i];
+                if tki != 0.0 {
+                    for j in 0..p {
+                        sigma[i * p + j] += tki * omega_t[k * p + j];
+                    }
+                }
+            }
+        }
+        sigma
+    }
+
+    /// Total effects of a joint intervention on `xs` on each of `ys`, as an
+    /// `|xs| × |ys|` row-major matrix.
+    ///
+    /// Entry `(a, b)` is `∂ E[Y_b | do(X = x)] / ∂ x_a`: the sum over directed
+    /// paths from `xs[a]` to `ys[b]` that avoid the other members of `xs`, of
+    /// the product of their coefficients. It is `1` on `ys[b] = xs[a]` and
+    /// `0` on the other intervened nodes.
+    pub fn total_effects(&self, xs: &[u32], ys: &[u32]) -> Result<Vec<f64>, String> {
+        if let Some(&v) = xs.iter().chain(ys).find(|&&v| v as usize >= self.p) {
+            return Err(format!("Index {} is out of bounds", v));
+        }
+        let mut out = Vec::with_capacity(xs.len() * ys.len());
+        for &x in xs {
+            let eff = self.effects_from(x, xs);
+            out.extend(ys.iter().map(|&y| eff[y as usize]));
+        }
+        Ok(out)
+    }
+
+    /// Every trek from `i` to `j`; their weights sum to `Σ_ij` (the trek
+    /// rule).
+    ///
+    /// The number of treks grows exponentially with the number of directed
+    /// paths, so this is meant for small graphs and explanations.
+    pub fn treks(&self, i: u32, j: u32) -> Result<Vec<Trek>, String> {
+        if let Some(&v) = [i, j].iter().find(|&&v| v as usize >= self.p) {
+            return Err(format!("Index {} is out of bounds", v));
+        }
+        let left = self.paths_into(i);
+        let right = self.paths_into(j);
+        let mut out = Vec::new();
+        for (lp, lw) in &left {
+            let k = *lp.last().unwrap();
+            for (rp, rw) in &right {
+                let l = *rp.last().unwrap();
+                if k != l && self.spouses[k as usize].binary_search(&l).is_err() {
+                    continue;
+                }
+                out.push(Trek {
+                    left: lp.clone(),
+                    right: rp.clone(),
+                    weight: lw * rw * self.omega[k as usize * self.p + l as usize],
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    /// Total effects of `x` on every node when the incoming edges of
+    /// `blocked` (and of `x`) are cut.
+    fn effects_from(&self, x: u32, blocked: &[u32]) -> Vec<f64> {
+        let mut eff = vec![0.0; self.p];
+        eff[x as usize] = 1.0;
+        for &v in &self.order {
+            if v == x || blocked.contains(&v) {
+                continue;
+            }
+            eff[v as usize] = self.parents[v as usize]
+                .iter()
+                .map(|&(u, b)| b * eff[u as usize])
+                .sum();
+        }
+        eff
+    }
+
+    /// Directed paths ending at `v`, each listed from `v` upwards, with the
+    /// product of their coefficients; the trivial path `[v]` comes first.
+    fn paths_into(&self, v: u32) -> Vec<(Vec<u32>, f64)> {
+        let mut out = Vec::new();
+        let mut stack = vec![(vec![v], 1.0)];
+        while let Some((path, w)) = stack.pop() {
+            let top = *path.last().unwrap();
+            for &(u, b) in self.parents[top as usize].iter().rev() {
+                let mut next = path.clone();
+                next.push(u);
+                stack.push((next, w * b));
+            }
+            out.push((path, w));
+        }
+        out
+    }
+}
+
+/// Spread edge-wise weights over CSR positions, in the keying read by
+/// [`LinearSem::new`] and
+/// [`Dag::simulate_linear_sem`](super::dag::Dag::simulate_linear_sem).
+///
+/// Each `(u, v, x)` is the coefficient of `u → v` or the error covariance of
+/// `u <-> v`. Edges that are not listed get weight 0.
+pub fn edge_weights(core: &CaugiGraph, edges: &[(u32, u32, f64)]) -> Result<Vec<f64>, String> {
+    let n = core.n();
+    let mut w = vec![0.0; core.col_index.len()];
+    for &(u, v, x) in edges {
+        if let Some(&i) = [u, v].iter().find(|&&i| i >= n) {
+            return Err(match n.checked_sub(1) {
+                Some(max) => format!("Node index {} out of bounds (max: {})", i, max),
+                None => format!("Node index {} out of bounds (graph has no nodes)", i),
+            });
+        }
+        let mut found = false;
+        for k in core.row_range(v) {
+            if core.col_index[k] != u {
+                continue;
+            }
+            match core.spec(k).class {
+                EdgeClass::Directed if core.is_incoming_arrow(k) => {
+                    w[k] = x;
+                    found = true;
+                }
+                EdgeClass::Bidirected => {
+                    // Set both half-edges, so either endpoint order reads it.
+                    let back = core
+                        .row_range(u)
+                        .find(|&j| {
+                            core.col_index[j] == v && core.spec(j).class == EdgeClass::Bidirected
+                        })
+                        .expect("bidirected edges are stored in both rows");
+                    w[k] = x;
+                    w[back] = x;
+                    found = true;
+                }
+                _ => {}
+            }
+        }
+        if !found {
+            return Err(format!("No edge {} --> {} or {} <-> {}", u, v, u, v));
+        }
+    }
+    Ok(w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edges::EdgeRegistry;
+    use crate::graph::admg::Admg;
+    use crate::graph::builder::GraphBuilder;
+    use crate::graph::dag::{Dag, NoiseFamily};
+    use crate::graph::generate;
+    use std::sync::Arc;
+
+    fn admg() -> Admg {
+        // 0 -> 1 -> 2 and 0 <-> 2.
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let b = reg.code_of("<->").unwrap();
+        let mut builder = GraphBuilder::new_with_registry(3, true, &reg);
+        builder.add_edge(0, 1, d).unwrap();
+        builder.add_edge(1, 2, d).unwrap();
+        builder.add_edge(0, 2, b).unwrap();
+        Admg::new(Arc::new(builder.finalize().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn edge_weights_key_both_edge_kinds() {
+        let g = admg();
+        let core = g.core_ref();
+        let w = edge_weights(core, &[(2, 0, 0.5), (1, 2, 3.0)]).unwrap();
+        // 0 <-> 2 in either order sets both half-edges; 0 -> 1 defaults to 0.
+        let at = |row: u32, col: u32| {
+            core.row_range(row)
+                .find(|&k| core.col_index[k] == col)
+                .map(|k| w[k])
+                .unwrap()
+        };
+        assert_eq!((at(0, 2), at(2, 0)), (0.5, 0.5));
+        assert_eq!((at(2, 1), at(1, 2)), (3.0, 0.0));
+        assert_eq!(at(1, 0), 0.0);
+
+        assert_eq!(
+            edge_weights(core, &[(2, 1, 1.0)]).unwrap_err(),
+            "No edge 2 --> 1 or 2 <-> 1"
+        );
+        assert_eq!(
+            edge_weights(core, &[(0, 3, 1.0)]).unwrap_err(),
+            "Node index 3 out of bounds (max: 2)"
+        );
+
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let empty = GraphBuilder::new_with_registry(0, true, &reg)
+            .finalize()
+            .unwrap();
+        assert_eq!(
+            edge_weights(&empty, &[(0, 1, 1.0)]).unwrap_err(),
+            "Node index 0 out of bounds (graph has no nodes)"
+        );
+        assert!(edge_weights(&empty, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn implied_covariance_total_effects_and_treks_by_hand() {
+        let g = admg();
+        let w = edge_weights(g.core_ref(), &[(0, 1, 2.0), (1, 2, 3.0), (0, 2, 0.5)]).unwrap();
+        let view = GraphView::Admg(Arc::new(g));
+        let sem = LinearSem::new(&view, &w, &[1.0]).unwrap();
+        // var(X1) = 4 + 1, cov(X0, X2) = 3 · 2 + 0.5, cov(X1, e2) = 1.
+        let expected = [[1.0, 2.0, 6.5], [2.0, 5.0, 16.0], [6.5, 16.0, 52.0]].concat();
+        assert_eq!(sem.implied_covariance(), expected);
+
+        assert_eq!(
+            sem.total_effects(&[0], &[0, 1, 2]).unwrap(),
+            [1.0, 2.0, 6.0]
+        );
+        // Intervening on 1 as well cuts the only path from 0 to 2.
+        assert_eq!(
+            sem.total_effects(&[0, 1], &[1, 2]).unwrap(),
+            [0.0, 0.0, 1.0, 3.0]
+        );
+        assert!(sem.total_effects(&[3], &[0]).is_err());
+
+        let treks = sem.treks(0, 2).unwrap();
+        assert_eq!(
+            treks,
+            vec![
+                Trek {
+                    left: vec![0],
+                    right: vec![2],
+                    weight: 0.5
+                },
+                Trek {
+                    left: vec![0],
+                    right: vec![2, 1, 0],
+                    weight: 6.0
+                },
+            ]
+        );
+        assert!(sem.treks(0, 3).is_err());
+
+        // ω02 = 2 exceeds what unit error variances allow, but total
+        // effects do not depend on Ω.
+        let bad: Vec<f64> = w.iter().map(|&x| if x == 0.5 { 2.0 } else { x }).collect();
+        assert!(LinearSem::new(&view, &bad, &[1.0]).is_err());
+        let b_only = LinearSem::from_coefficients(&view, &bad).unwrap();
+        assert_eq!(
+            b_only.total_effects(&[0], &[0, 1, 2]).unwrap(),
+            [1.0, 2.0, 6.0]
+        );
+        assert_eq!(b_only.implied_covariance(), vec![0.0; 9]);
+        assert!(LinearSem::new(&view, &w[1..], &[1.0]).is_err());
+        assert!(LinearSem::new(&view, &w, &[1.0, 1.0]).is_err());
+        let raw = GraphView::Raw(Arc::new(view.core().clone()));
+        assert!(LinearSem::new(&raw, &w, &[1.0]).is_err());
+    }
+
+    #[test]
+    fn bidirected_covariance_needs_both_error_variances() {
+        // 0 <-> 1 with var(e0) = 0: any ω01 ≠ 0 makes Ω indefinite.
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let mut builder = GraphBuilder::new_with_registry(2, true, &reg);
+        builder.add_edge(0, 1, reg.code_of("<->").unwrap()).unwrap();
+        let g = Admg::new(Arc::new(builder.finalize().unwrap())).unwrap();
+        let w = edge_weights(g.core_ref(), &[(0, 1, 0.5)]).unwrap();
+        let view = GraphView::Admg(Arc::new(g));
+        assert_eq!(
+            LinearSem::new(&view, &w, &[0.0, 1.0]).unwrap_err(),
+            "error covariance is not positive semi-definite"
+        );
+        assert!(LinearSem::new(&view, &[0.0, 0.0], &[0.0, 1.0]).is_ok());
+    }
+
+    #[test]
+    fn trek_rule_holds_on_random_admgs() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        for seed in 0..20 {
+            let mut s = generate::random_admg(&reg, 7, 0.4, 0.2, seed).unwrap();
+            let view = s.view().unwrap();
+            let core = view.core();
+            // Error variances of 7 dominate bidirected covariances below 1,
+            // so Ω is positive definite.
+            let w: Vec<f64> = (0..core.col_index.len())
+                .map(|k| {
+                    let v = core.col_index[k] as f64;
+                    0.9 - 0.3 * ((v + seed as f64) % 5.0)
+                })
+                .collect();
+            let sem = LinearSem::new(&view, &w, &[7.0]).unwrap();
+            let sigma = sem.implied_covariance();
+            for i in 0..7 {
+                for j in 0..7 {
+                    let sum: f64 = sem.treks(i, j).unwrap().iter().map(|t| t.weight).sum();
+                    let s_ij = sigma[i as usize * 7 + j as usize];
+                    assert!((sum - s_ij).abs() < 1e-9 * s_ij.abs().max(1.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn implied_covariance_matches_simulated_data() {
+        let mut reg = EdgeRegistry::new();
+        reg.register_builtins().unwrap();
+        let d = reg.code_of("-->").unwrap();
+        let mut builder = GraphBuilder::new_with_registry(4, true, &reg);
+        for (u, v) in [(0, 2), (1, 2), (2, 3), (0, 3)] {
+            builder.add_edge(u, v, d).unwrap();
+        }
+        let dag = Dag::new(Arc::new(builder.finalize().unwrap())).unwrap();
+        let w = edge_weights(
+            dag.core_ref(),
+            &[(0, 2, 0.8), (1, 2, -0.7), (2, 3, 0.9), (0, 3, 0.4)],
+        )
+        .unwrap();
+        let noise = [1.0, 0.5, 0.25, 2.0];
+        let n = 200_000;
+        let x = dag
+            .simulate_linear_sem(n, Some(&w), &noise, NoiseFamily::Gaussian, 17)
+            .unwrap();
+        let sample = linalg::column_covariance(&x, n, 4);
+        let sem = LinearSem::new(&GraphView::Dag(Arc::new(dag)), &w, &noise).unwrap();
+        for (s, e) in sample.iter().zip(sem.implied_covariance()) {
+            assert!((s - e).abs() < 0.03 * e.abs().max(1.0));
+        }
+        // Total effect of 0 on 3: 0.4 directly plus 0.8 · 0.9 through 2.
+        let te = sem.total_effects(&[0], &[3]).unwrap();
+        assert!((te[0] - 1.12).abs() < 1e-12);
+    }
+}
@@ -6,13 +6,19 @@
 //! - Automatic invalidation on mutation
 //! - On-demand query computation (no caching)
 
-use super::admg::Admg;
+use super::admg::{AdjustmentSets, Admg, Identification};
 use super::ag::Ag;
+use super::alg::do_calculus::DoCalculusRules;
+use super::alg::gac::Amenability;
+use super::alg::implied::ConditionalIndependence;
+use super::alg::open_paths::OpenPath;
 use super::builder::GraphBuilder;
-use super::cpdag::Cpdag;
-use super::dag::Dag;
+use super::cpdag::{Cpdag, MecSize};
+use super::dag::{Dag, NoiseFamily};
 use super::mpdag::Mpdag;
-use super::pdag::Pdag;
+use super::pag::Pag;
+use super::pdag::{MemberDags, Pdag};
+use super::sem::LinearSem;
 use super::ug::Ug;
 use super::view::GraphView;
 use super::CaugiGraph;
@@ -41,6 +47,8 @@ pub enum GraphClass {
     Admg,
     /// Ancestral Graph (`-->`, `<->`, `---`)
     Ag,
+    /// Partial Ancestral Graph (`-->`, `<->`, `---`, `o-o`, `o->`, `--o`)
+    Pag,
     /// Unknown/Raw (no validation)
     Unknown,
     /// Auto - no validation, will be resolved when edges are added
@@ -58,7 +66,8 @@ impl std::str::FromStr for GraphClass {
             "cpdag" => Ok(GraphClass::Cpdag),
             "ug" => Ok(GraphClass::Ug),
             "admg" => Ok(GraphClass::Admg),
-            "ag" | "mag" | "pag" => Ok(GraphClass::Ag),
+            "ag" | "mag" => Ok(GraphClass::Ag),
+            "pag" => Ok(GraphClass::Pag),
             "unknown" | "raw" => Ok(GraphClass::Unknown),
             "auto" => Ok(GraphClass::Auto),
             _ => Err(format!("Unknown graph class: '{}'", s)),
@@ -76,6 +85,7 @@ impl GraphClass {
             GraphClass::Ug => "UG",
             GraphClass::Admg => "ADMG",
             GraphClass::Ag => "AG",
+            GraphClass::Pag => "PAG",
             GraphClass::Unknown => "UNKNOWN",
             GraphClass::Auto => "AUTO",
         }
@@ -516,6 +526,10 @@ impl GraphSession {
                 let ag = Ag::new(core).map_err(|e| self.map_error(e))?;
                 Ok(GraphView::Ag(Arc::new(ag)))
             }
+            GraphClass::Pag => {
+                let pag = Pag::new(core).map_err(|e| self.map_error(e))?;
+                Ok(GraphView::Pag(Arc::new(pag)))
+            }
             GraphClass::Unknown | GraphClass::Auto => Ok(GraphView::Raw(core)),
         }
     }
@@ -679,6 +693,12 @@ impl GraphSession {
         Ok(Ag::new(Arc::new(core.as_ref().clone())).is_ok())
     }
 
+    /// Check if the graph is compatible with PAG.
+    pub fn is_pag_type(&mut self) -> Result<bool, String> {
+        let core = self.core()?;
+        Ok(Pag::new(Arc::new(core.as_ref().clone())).is_ok())
+    }
+
     /// Check if the graph is a CPDAG (PDAG-only).
     pub fn is_cpdag(&mut self) -> Result<bool, String> {
         let core = self.core()?;
@@ -742,6 +762,10 @@ impl GraphSession {
                 Ag::new(Arc::new(core.as_ref().clone())).map_err(|e| self.map_error(e))?;
                 Ok(GraphClass::Ag)
             }
+            GraphClass::Pag => {
+                Pag::new(Arc::new(core.as_ref().clone())).map_err(|e| self.map_error(e))?;
+                Ok(GraphClass::Pag)
+            }
             GraphClass::Unknown => Ok(GraphClass::Unknown),
             GraphClass::Auto => {
                 if Dag::new(Arc::new(core.as_ref().clone())).is_ok() {
@@ -771,6 +795,73 @@ impl GraphSession {
         view.to_cpdag().map_err(|e| self.map_error(e))
     }
 
+    /// Convert MAG (or DAG) to PAG.
+    pub fn to_pag(&mut self) -> Result<GraphView, String> {
+        let view = self.view()?;
+        view.to_pag().map_err(|e| self.map_error(e))
+    }
+
+    /// Sample `n` rows from a linear SEM on a DAG (column-major `n × p`).
+    pub fn simulate_linear_sem(
+        &mut self,
+        n: usize,
+        weights: Option<&[f64]>,
+        noise_var: &[f64],
+        noise: NoiseFamily,
+        seed: u64,
+    ) -> Result<Vec<f64>, String> {
+        let view = self.view()?;
+        view.simulate_linear_sem(n, weights, noise_var, noise, seed)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Edge-wise SEM weights spread over CSR positions (see
+    /// [`sem::edge_weights`](super::sem::edge_weights)).
+    pub fn edge_weights(&mut self, edges: &[(u32, u32, f64)]) -> Result<Vec<f64>, String> {
+        let core = self.core()?;
+        super::sem::edge_weights(&core, edges).map_err(|e| self.map_error(e))
+    }
+
+    /// Linear SEM on this DAG or ADMG, with coefficients and `<->` error
+    /// covariances keyed by CSR position.
+    pub fn linear_sem(&mut self, weights: &[f64], error_var: &[f64]) -> Result<LinearSem, String> {
+        let view = self.view()?;
+        LinearSem::new(&view, weights, error_var).map_err(|e| self.map_error(e))
+    }
+
+    /// Total effects of a joint intervention on `xs` on each of `ys` in the
+    /// linear SEM with coefficients `weights` (row-major `|xs| × |ys|`).
+    pub fn total_effects(
+        &mut self,
+        weights: &[f64],
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Vec<f64>, String> {
+        let view = self.view()?;
+        LinearSem::from_coefficients(&view, weights)
+            .and_then(|sem| sem.total_effects(xs, ys))
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Learn a CPDAG with PC, using this graph as a separation oracle.
+    pub fn oracle_pc(&mut self) -> Result<GraphView, String> {
+        let view = self.view()?;
+        view.oracle_pc().map_err(|e| self.map_error(e))
+    }
+
+    /// Learn a PAG with FCI, using this graph as a separation oracle.
+    pub fn oracle_fci(&mut self) -> Result<GraphView, String> {
+        let view = self.view()?;
+        view.oracle_fci().map_err(|e| self.map_error(e))
+    }
+
+    /// Learn a CPDAG with GES, scoring with a Gaussian BIC on a covariance
+    /// implied by this DAG.
+    pub fn oracle_ges(&mut self) -> Result<GraphView, String> {
+        let view = self.view()?;
+        view.oracle_ges().map_err(|e| self.map_error(e))
+    }
+
     /// Apply Meek closure to a PDAG.
     pub fn meek_closure(&mut self) -> Result<GraphView, String> {
         let core = self.core()?;
@@ -797,6 +888,13 @@ impl GraphSession {
         view.latent_project(latents).map_err(|e| self.map_error(e))
     }
 
+    /// Project onto a MAG (DAG with latents/selection, or ADMG).
+    pub fn to_mag(&mut self, latents: &[u32], selected: &[u32]) -> Result<GraphView, String> {
+        let view = self.view()?;
+        view.to_mag(latents, selected)
+            .map_err(|e| self.map_error(e))
+    }
+
     /// Exogenize a set of nodes (DAG only).
     pub fn exogenize(&mut self, nodes: &[u32]) -> Result<GraphView, String> {
         let view = self.view()?;
@@ -832,6 +930,37 @@ impl GraphSession {
             .map_err(|e| self.map_error(e))
     }
 
+    /// Shortest d-/m-connecting path witness (DAG/ADMG/AG).
+    pub fn connecting_path(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Option<OpenPath>, String> {
+        let view = self.view()?;
+        view.connecting_path(xs, ys, z)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// All d-/m-connecting paths up to `max_len` edges (DAG/ADMG/AG).
+    pub fn connecting_paths(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+        max_len: usize,
+    ) -> Result<Vec<OpenPath>, String> {
+        let view = self.view()?;
+        view.connecting_paths(xs, ys, z, max_len)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Basis of implied conditional independencies (DAG/ADMG/AG).
+    pub fn implied_independencies(&mut self) -> Result<Vec<ConditionalIndependence>, String> {
+        let view = self.view()?;
+        view.implied_independencies().map_err(|e| self.map_error(e))
+    }
+
     /// M-separation query (ADMG/AG/DAG).
     pub fn m_separated(&mut self, xs: &[u32], ys: &[u32], z: &[u32]) -> Result<bool, String> {
         let view = self.view()?;
@@ -852,13 +981,10 @@ impl GraphSession {
             .map_err(|e| self.map_error(e))
     }
 
-    /// Adjustment set: optimal.
+    /// Adjustment set: optimal (O-set), for DAG/CPDAG/MPDAG.
     pub fn adjustment_set_optimal(&mut self, xs: &[u32], ys: &[u32]) -> Result<Vec<u32>, String> {
-        if xs.len() != 1 || ys.len() != 1 {
-            return Err("adjustment_set_optimal expects exactly one X and one Y".into());
-        }
         let view = self.view()?;
-        view.adjustment_set_optimal(xs[0], ys[0])
+        view.adjustment_set_optimal(xs, ys)
             .map_err(|e| self.map_error(e))
     }
 
@@ -893,6 +1019,44 @@ impl GraphSession {
             .map_err(|e| self.map_error(e))
     }
 
+    /// Validate an adjustment set with the generalized adjustment criterion
+    /// (CPDAG/MPDAG/AG/PAG).
+    pub fn is_valid_adjustment_set(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Amenability<bool>, String> {
+        let view = self.view()?;
+        view.is_valid_adjustment_set(xs, ys, z)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Enumerate adjustment sets with the generalized adjustment criterion
+    /// (CPDAG/MPDAG/AG/PAG).
+    pub fn all_adjustment_sets(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+        max_size: u32,
+    ) -> Result<Amenability<Vec<Vec<u32>>>, String> {
+        let view = self.view()?;
+        view.all_adjustment_sets(xs, ys, minimal, max_size)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Canonical adjustment set (CPDAG/MPDAG/AG/PAG).
+    pub fn adjustment_set_canonical(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Amenability<Option<Vec<u32>>>, String> {
+        let view = self.view()?;
+        view.adjustment_set_canonical(xs, ys)
+            .map_err(|e| self.map_error(e))
+    }
+
     /// Validate adjustment set for ADMG/AG.
     pub fn is_valid_adjustment_set_admg(
         &mut self,
@@ -905,6 +1069,88 @@ impl GraphSession {
             .map_err(|e| self.map_error(e))
     }
 
+    /// Identify `P(ys | do(xs))` with the ID algorithm (DAG/ADMG).
+    pub fn identify(&mut self, xs: &[u32], ys: &[u32]) -> Result<Identification, String> {
+        let view = self.view()?;
+        view.identify(xs, ys).map_err(|e| self.map_error(e))
+    }
+
+    /// Identify `P(ys | do(xs), zs)` with the IDC algorithm (DAG/ADMG).
+    pub fn identify_conditional(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+    ) -> Result<Identification, String> {
+        let view = self.view()?;
+        view.identify_conditional(xs, ys, zs)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Check which do-calculus rules apply to `(xs, ys, zs, ws)` (DAG/ADMG).
+    pub fn do_calculus_rules(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+        ws: &[u32],
+    ) -> Result<DoCalculusRules, String> {
+        let view = self.view()?;
+        view.do_calculus_rules(xs, ys, zs, ws)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Markov equivalence class size (CPDAG).
+    pub fn mec_size(&mut self) -> Result<MecSize, String> {
+        let view = self.view()?;
+        view.mec_size().map_err(|e| self.map_error(e))
+    }
+
+    /// Locally valid possible-parent sets of `x` for IDA (CPDAG/MPDAG).
+    pub fn possible_parent_sets(&mut self, x: u32) -> Result<Vec<Vec<u32>>, String> {
+        let view = self.view()?;
+        view.possible_parent_sets(x).map_err(|e| self.map_error(e))
+    }
+
+    /// Possible ancestors of a node (CPDAG/MPDAG/PAG).
+    pub fn possible_ancestors_of(&mut self, node: u32) -> Result<Vec<u32>, String> {
+        let view = self.view()?;
+        view.possible_ancestors_of(node)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Possible descendants of a node (CPDAG/MPDAG/PAG).
+    pub fn possible_descendants_of(&mut self, node: u32) -> Result<Vec<u32>, String> {
+        let view = self.view()?;
+        view.possible_descendants_of(node)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Definite ancestors of a node (CPDAG/MPDAG/PAG).
+    pub fn definite_ancestors_of(&mut self, node: u32) -> Result<Vec<u32>, String> {
+        let view = self.view()?;
+        view.definite_ancestors_of(node)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// A consistent DAG extension (PDAG/MPDAG/CPDAG).
+    pub fn consistent_extension(&mut self) -> Result<GraphView, String> {
+        let view = self.view()?;
+        view.consistent_extension().map_err(|e| self.map_error(e))
+    }
+
+    /// `count` uniformly drawn member DAGs (PDAG/MPDAG/CPDAG), seeded.
+    pub fn sample_dags(&mut self, count: usize, seed: u64) -> Result<Vec<GraphView>, String> {
+        let view = self.view()?;
+        view.sample_dags(count, seed).map_err(|e| self.map_error(e))
+    }
+
+    /// Lazy iterator over all member DAGs (PDAG/MPDAG/CPDAG).
+    pub fn member_dags(&mut self) -> Result<MemberDags, String> {
+        let view = self.view()?;
+        view.member_dags().map_err(|e| self.map_error(e))
+    }
+
     /// Enumerate all adjustment sets for ADMG/AG.
     pub fn all_adjustment_sets_admg(
         &mut self,
@@ -918,6 +1164,55 @@ impl GraphSession {
             .map_err(|e| self.map_error(e))
     }
 
+    /// Lazily enumerate valid (or minimal) adjustment sets (DAG/ADMG).
+    pub fn adjustment_sets(
+        &mut self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+    ) -> Result<AdjustmentSets, String> {
+        let view = self.view()?;
+        view.adjustment_sets(xs, ys, minimal)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Validate a front-door set (DAG/ADMG).
+    pub fn is_valid_front_door_set(&mut self, x: u32, y: u32, m: &[u32]) -> Result<bool, String> {
+        let view = self.view()?;
+        view.is_valid_front_door_set(x, y, m)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Find a front-door set (DAG/ADMG).
+    pub fn front_door_set(&mut self, x: u32, y: u32) -> Result<Option<Vec<u32>>, String> {
+        let view = self.view()?;
+        view.front_door_set(x, y).map_err(|e| self.map_error(e))
+    }
+
+    /// Instrument check for `x → y` given `w` (DAG/ADMG).
+    pub fn is_instrument(&mut self, x: u32, y: u32, z: &[u32], w: &[u32]) -> Result<bool, String> {
+        let view = self.view()?;
+        view.is_instrument(x, y, z, w)
+            .map_err(|e| self.map_error(e))
+    }
+
+    /// Single-node instruments for `x → y` given `w` (DAG/ADMG).
+    pub fn instruments(&mut self, x: u32, y: u32, w: &[u32]) -> Result<Vec<u32>, String> {
+        let view = self.view()?;
+        view.instruments(x, y, w).map_err(|e| self.map_error(e))
+    }
+
+    /// Ancestral instruments with their conditioning sets (DAG/ADMG).
+    pub fn ancestral_instruments(
+        &mut self,
+        x: u32,
+        y: u32,
+    ) -> Result<Vec<(u32, Vec<u32>)>, String> {
+        let view = self.view()?;
+        view.ancestral_instruments(x, y)
+            .map_err(|e| self.map_error(e))
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // INTROSPECTION
     // ═══════════════════════════════════════════════════════════════════════════
@@ -1009,6 +1304,21 @@ impl GraphSession {
             }
         }
 
+        if let Some(rest) = err.strip_prefix("No edge ") {
+            let parts: Vec<&str> = rest.split(" --> ").collect();
+            if parts.len() == 2 {
+                let from: u32 = parts[0].parse().ok()?;
+                let to: u32 = parts[1].split(" or ").next()?.parse().ok()?;
+                return Some(format!(
+                    "No edge {} --> {} or {} <-> {}",
+                    self.format_index(from),
+                    self.format_index(to),
+                    self.format_index(from),
+                    self.format_index(to)
+                ));
+            }
+        }
+
         if let Some(rest) = err.strip_prefix("Self-loops not allowed in simple graphs (node ") {
             let idx: u32 = rest.trim_end_matches(')').parse().ok()?;
             return Some(format!(
@@ -1273,6 +1583,7 @@ mod tests {
         assert_eq!("ug".parse::<GraphClass>().unwrap(), GraphClass::Ug);
         assert_eq!("admg".parse::<GraphClass>().unwrap(), GraphClass::Admg);
         assert_eq!("mag".parse::<GraphClass>().unwrap(), GraphClass::Ag);
+        assert_eq!("pag".parse::<GraphClass>().unwrap(), GraphClass::Pag);
         assert_eq!("raw".parse::<GraphClass>().unwrap(), GraphClass::Unknown);
         assert_eq!("auto".parse::<GraphClass>().unwrap(), GraphClass::Auto);
         assert!("not-a-class".parse::<GraphClass>().is_err());
@@ -1284,6 +1595,7 @@ mod tests {
         assert_eq!(GraphClass::Ug.as_str(), "UG");
         assert_eq!(GraphClass::Admg.as_str(), "ADMG");
         assert_eq!(GraphClass::Ag.as_str(), "AG");
+        assert_eq!(GraphClass::Pag.as_str(), "PAG");
         assert_eq!(GraphClass::Unknown.as_str(), "UNKNOWN");
         assert_eq!(GraphClass::Auto.as_str(), "AUTO");
     }
@@ -1448,6 +1760,9 @@ mod tests {
         let to_cpdag = session.to_cpdag().unwrap();
         assert!(matches!(to_cpdag, GraphView::Cpdag(_)));
 
+        let to_pag = session.to_pag().unwrap();
+        assert!(matches!(to_pag, GraphView::Pag(_)));
+
         let skeleton = session.skeleton().unwrap();
         assert!(matches!(skeleton, GraphView::Ug(_)));
 
@@ -1458,6 +1773,11 @@ mod tests {
         assert!(matches!(projected, GraphView::Admg(_)));
         assert_eq!(projected.n(), 3);
 
+        let mag = session.to_mag(&[1], &[]).unwrap();
+        assert!(matches!(mag, GraphView::Ag(_)));
+        assert_eq!(mag.n(), 3);
+        assert!(session.to_mag(&[1], &[1]).is_err());
+
         assert!(!session.d_separated(&[0], &[3], &[]).unwrap());
         assert!(session.d_separated(&[0], &[3], &[2]).unwrap());
         assert_eq!(
@@ -1467,6 +1787,19 @@ mod tests {
             Some(vec![2])
         );
         assert!(session.m_separated(&[0], &[3], &[2]).unwrap());
+        let implied = session.implied_independencies().unwrap();
+        assert!(!implied.is_empty());
+        assert!(implied
+            .iter()
+            .all(|c| session.d_separated(&c.x, &c.y, &c.z).unwrap()));
+        assert_eq!(session.connecting_path(&[0], &[3], &[2]).unwrap(), None);
+        let path = session.connecting_path(&[0], &[3], &[]).unwrap().unwrap();
+        assert_eq!(path.nodes.first(), Some(&0));
+        assert_eq!(path.nodes.last(), Some(&3));
+        assert!(!session
+            .connecting_paths(&[0], &[3], &[], 3)
+            .unwrap()
+            .is_empty());
     }
 
     #[test]
@@ -1485,8 +1818,8 @@ mod tests {
         let backdoor = session.adjustment_set_backdoor(&[0], &[1]).unwrap();
         assert!(backdoor.iter().all(|&z| z < 3 && z != 0 && z != 1));
 
-        let optimal = session.adjustment_set_optimal(&[0], &[1]).unwrap();
-        assert!(optimal.iter().all(|&z| z < 3 && z != 0 && z != 1));
+        // 1 is not a descendant of 0, so the (empty) O-set is not valid.
+        assert!(session.adjustment_set_optimal(&[0], &[1]).is_err());
 
         assert!(session
             .is_valid_backdoor_set(&[0], &[1], &backdoor)
@@ -1496,7 +1829,10 @@ mod tests {
             .iter()
             .all(|set| set.iter().all(|&z| z < 3 && z != 0 && z != 1)));
 
-        assert!(session.adjustment_set_optimal(&[0, 2], &[1]).is_err());
+        assert_eq!(
+            session.adjustment_set_optimal(&[0, 2], &[1]).unwrap(),
+            Vec::<u32>::new()
+        );
         assert!(session.is_valid_backdoor_set(&[0, 2], &[1], &[2]).is_err());
         assert!(session.all_backdoor_sets(&[0, 2], &[1], true, 3).is_err());
     }
@@ -1520,6 +1856,20 @@ mod tests {
         assert_eq!(admg.spouses_of(1).unwrap(), vec![2]);
         let _ = admg.is_valid_adjustment_set_admg(&[0], &[2], &[1]).unwrap();
         let _ = admg.all_adjustment_sets_admg(&[0], &[2], true, 2).unwrap();
+        assert_eq!(
+            admg.adjustment_sets(&[0], &[2], true)
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![Vec::<u32>::new()]
+        );
+        assert!(!admg.is_valid_front_door_set(0, 2, &[1]).unwrap());
+        assert_eq!(admg.front_door_set(0, 1).unwrap(), None);
+        assert!(!admg.is_instrument(0, 2, &[1], &[]).unwrap());
+        assert_eq!(admg.instruments(0, 1, &[]).unwrap(), Vec::<u32>::new());
+        assert!(admg.ancestral_instruments(0, 1).unwrap().is_empty());
+        assert!(admg.identify(&[0], &[2]).is_ok());
+        assert!(admg.identify_conditional(&[0], &[2], &[1]).is_ok());
+        assert!(admg.do_calculus_rules(&[], &[2], &[0], &[]).is_ok());
         assert_eq!(
             admg.resolve_class(GraphClass::Auto).unwrap(),
             GraphClass::Admg
@@ -1694,6 +2044,10 @@ mod tests {
                 .unwrap(),
             "Undirected constraint violated: node B has both undirected and arrowhead edges"
         );
+        assert_eq!(
+            session.map_error_str("No edge 2 --> 0 or 2 <-> 0").unwrap(),
+            "No edge C --> A or C <-> A"
+        );
         assert_eq!(
             session.map_error_str("Index 1 is out of bounds").unwrap(),
             "Node B is out of bounds"
@@ -1979,6 +2333,45 @@ mod tests {
         assert!(pdag.all_backdoor_sets(&[0], &[2], true, 2).is_err());
         assert!(pdag.is_valid_adjustment_set_admg(&[0], &[2], &[1]).is_err());
         assert!(pdag.all_adjustment_sets_admg(&[0], &[2], true, 2).is_err());
+        assert!(pdag.is_valid_adjustment_set(&[0], &[2], &[]).is_err());
+        assert!(pdag.adjustment_set_canonical(&[0], &[2]).is_err());
+        assert!(pdag.mec_size().is_err());
+
+        // The MPDAG 0 --> 1 --> 2 supports the generalized criterion.
+        let GraphView::Mpdag(mpdag) = pdag.meek_closure().unwrap() else {
+            panic!("expected MPDAG");
+        };
+        assert_eq!(
+            mpdag.is_valid_adjustment_set(&[0], &[2], &[]).unwrap(),
+            Amenability::Amenable(true)
+        );
+
+        // The CPDAG 0 --- 1 --- 2 stands for three DAGs.
+        let mut cpdag = GraphSession::new(&reg, 3, true, GraphClass::Cpdag);
+        let mut e = EdgeBuffer::new();
+        e.push(0, 1, u);
+        e.push(1, 2, u);
+        cpdag.set_edges(e);
+        assert_eq!(cpdag.mec_size().unwrap(), MecSize::Exact(3));
+        assert_eq!(cpdag.member_dags().unwrap().count(), 3);
+        assert_eq!(
+            cpdag.possible_parent_sets(1).unwrap(),
+            vec![vec![], vec![0], vec![2]]
+        );
+        assert!(pdag.possible_parent_sets(1).is_err());
+        assert_eq!(cpdag.possible_ancestors_of(1).unwrap(), vec![0, 2]);
+        assert_eq!(cpdag.possible_descendants_of(0).unwrap(), vec![1, 2]);
+        assert!(cpdag.definite_ancestors_of(1).unwrap().is_empty());
+        assert!(pdag.definite_ancestors_of(1).is_err());
+        let draws = cpdag.sample_dags(5, 1).unwrap();
+        assert_eq!(draws.len(), 5);
+        assert!(draws.iter().all(|g| matches!(g, GraphView::Dag(_))));
+        assert!(matches!(
+            cpdag.consistent_extension().unwrap(),
+            GraphView::Dag(_)
+        ));
+        // The PDAG 0 --> 1 --- 2 has the single member 0 -> 1 -> 2.
+        assert_eq!(pdag.member_dags().unwrap().count(), 1);
     }
 
     #[test]
@@ -1,9 +1,17 @@
-use super::admg::Admg;
+use super::admg::{AdjustmentSets, Admg, Identification};
 use super::ag::Ag;
-use super::cpdag::Cpdag;
-use super::dag::Dag;
+use super::alg::do_calculus::DoCalculusRules;
+use super::alg::gac::Amenability;
+use super::alg::implied::ConditionalIndependence;
+use super::alg::open_paths::OpenPath;
+use super::alg::rng::SplitMix64;
+use super::cpdag::{Cpdag, MecSize};
+use super::dag::{Dag, NoiseFamily};
+use super::learn::{self, GaussianBic, SeparationOracle};
 use super::mpdag::Mpdag;
-use super::pdag::Pdag;
+use super::pag::Pag;
+use super::pdag::{MemberDags, Pdag};
+use super::sem::LinearSem;
 use super::ug::Ug;
 use super::CaugiGraph;
 use std::sync::Arc;
@@ -49,6 +57,7 @@ pub enum GraphView {
     Ug(Arc<Ug>),
     Admg(Arc<Admg>),
     Ag(Arc<Ag>),
+    Pag(Arc<Pag>),
     Raw(Arc<CaugiGraph>),
 }
 
@@ -63,6 +72,7 @@ impl GraphView {
             GraphView::Ug(u) => u.core_ref(),
             GraphView::Admg(a) => a.core_ref(),
             GraphView::Ag(g) => g.core_ref(),
+            GraphView::Pag(g) => g.core_ref(),
             GraphView::Raw(c) => c,
         }
     }
@@ -77,6 +87,7 @@ impl GraphView {
             GraphView::Ug(g) => g.n(),
             GraphView::Admg(g) => g.n(),
             GraphView::Ag(g) => g.n(),
+            GraphView::Pag(g) => g.n(),
             GraphView::Raw(core) => core.n(),
         }
     }
@@ -130,6 +141,7 @@ impl GraphView {
     /// - UG: undirected, all (no directed or partial edges exist)
     /// - ADMG: in, out, undirected (bidirected/spouses), all (no partial edges exist)
     /// - AG: in, out, undirected, bidirected, all (no partial edges exist)
+    /// - PAG: all modes allowed (partial = edges with a circle at either end)
     /// - UNKNOWN (Raw): all modes allowed
     pub fn neighbors_of(&self, i: u32, mode: NeighborMode) -> Result<Vec<u32>, String> {
         // Validate mode for graph type and dispatch to optimized methods
@@ -222,6 +234,14 @@ impl GraphView {
                 Err("mode 'partial' not valid for AG (no partial edges)".into())
             }
 
+            // PAG: all modes; in/out/undirected/bidirected are definite edges only
+            (GraphView::Pag(g), NeighborMode::All) => Ok(g.neighbors_of(i).to_vec()),
+            (GraphView::Pag(g), NeighborMode::In) => Ok(g.parents_of(i).to_vec()),
+            (GraphView::Pag(g), NeighborMode::Out) => Ok(g.children_of(i).to_vec()),
+            (GraphView::Pag(g), NeighborMode::Undirected) => Ok(g.undirected_of(i).to_vec()),
+            (GraphView::Pag(g), NeighborMode::Bidirected) => Ok(g.spouses_of(i).to_vec()),
+            (GraphView::Pag(g), NeighborMode::Partial) => Ok(g.circle_neighbors_of(i).to_vec()),
+
             // Raw (UNKNOWN): all modes allowed, iterate CSR
             (GraphView::Raw(_), _) => self.neighbors_mode_of_raw(i, mode),
         }
@@ -296,6 +316,7 @@ impl GraphView {
             GraphView::Cpdag(c) => Ok(c.as_pdag().ancestors_of(i)),
             GraphView::Admg(g) => Ok(g.ancestors_of(i)),
             GraphView::Ag(g) => Ok(g.ancestors_of(i)),
+            GraphView::Pag(g) => Ok(g.ancestors_of(i)),
             GraphView::Ug(_) => Err("ancestors_of not defined for UG".into()),
             GraphView::Raw(_) => Err("ancestors_of not implemented for UNKNOWN class".into()),
         }
@@ -308,6 +329,7 @@ impl GraphView {
             GraphView::Cpdag(c) => Ok(c.as_pdag().descendants_of(i)),
             GraphView::Admg(g) => Ok(g.descendants_of(i)),
             GraphView::Ag(g) => Ok(g.descendants_of(i)),
+            GraphView::Pag(g) => Ok(g.descendants_of(i)),
             GraphView::Ug(_) => Err("descendants_of not defined for UG".into()),
             GraphView::Raw(_) => Err("descendants_of not implemented for UNKNOWN class".into()),
         }
@@ -320,6 +342,7 @@ impl GraphView {
             GraphView::Cpdag(c) => Ok(c.as_pdag().anteriors_of(i)),
             GraphView::Admg(_) => Err("anteriors_of not defined for ADMG".into()),
             GraphView::Ag(g) => Ok(g.anteriors_of(i)),
+            GraphView::Pag(g) => Ok(g.anteriors_of(i)),
             GraphView::Ug(_) => Err("anteriors_of not defined for UG".into()),
             GraphView::Raw(_) => Err("anteriors_of not implemented for UNKNOWN class".into()),
         }
@@ -332,6 +355,7 @@ impl GraphView {
             GraphView::Cpdag(c) => Ok(c.as_pdag().posteriors_of(i)),
             GraphView::Admg(_) => Err("posteriors_of not defined for ADMG".into()),
             GraphView::Ag(g) => Ok(g.posteriors_of(i)),
+            GraphView::Pag(g) => Ok(g.posteriors_of(i)),
             GraphView::Ug(_) => Err("posteriors_of not defined for UG".into()),
             GraphView::Raw(_) => Err("posteriors_of not implemented for UNKNOWN class".into()),
         }
@@ -345,6 +369,7 @@ impl GraphView {
             GraphView::Ug(g) => Ok(g.markov_blanket_of(i)),
             GraphView::Admg(g) => Ok(g.markov_blanket_of(i)),
             GraphView::Ag(g) => Ok(g.markov_blanket_of(i)),
+            GraphView::Pag(_) => Err("markov_blanket_of not defined for PAG".into()),
             GraphView::Raw(_) => Err("markov_blanket_of not implemented for UNKNOWN class".into()),
         }
     }
@@ -365,6 +390,15 @@ impl GraphView {
                     Ok(g.exogenous_nodes())
                 }
             }
+            GraphView::Pag(g) => {
+                if undirected_as_parents {
+                    Ok((0..g.n())
+                        .filter(|&i| g.parents_of(i).is_empty() && g.undirected_of(i).is_empty())
+                        .collect())
+                } else {
+                    Ok(g.exogenous_nodes())
+                }
+            }
             GraphView::Raw(_) => Err("exogenous_nodes not implemented for UNKNOWN class".into()),
         }
     }
@@ -382,6 +416,7 @@ impl GraphView {
             GraphView::Cpdag(_) => Err("topological_sort is only defined for DAGs".into()),
             GraphView::Ug(_) => Err("topological_sort is only defined for DAGs".into()),
             GraphView::Ag(_) => Err("topological_sort is only defined for DAGs".into()),
+            GraphView::Pag(_) => Err("topological_sort is only defined for DAGs".into()),
             GraphView::Raw(_) => Err("topological_sort is only defined for DAGs".into()),
         }
     }
@@ -421,6 +456,38 @@ impl GraphView {
         }
     }
 
+    /// A shortest d- or m-connecting path between `xs` and `ys` given `z`
+    /// (DAG, ADMG or AG), or `None` if they are separated.
+    pub fn connecting_path(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Option<OpenPath>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.d_connecting_path(xs, ys, z)),
+            GraphView::Admg(g) => Ok(g.m_connecting_path(xs, ys, z)),
+            GraphView::Ag(g) => Ok(g.m_connecting_path(xs, ys, z)),
+            _ => Err("connecting_path is only defined for DAGs, ADMGs, and AGs".into()),
+        }
+    }
+
+    /// Every d- or m-connecting path with at most `max_len` edges.
+    pub fn connecting_paths(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+        max_len: usize,
+    ) -> Result<Vec<OpenPath>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.d_connecting_paths(xs, ys, z, max_len)),
+            GraphView::Admg(g) => Ok(g.m_connecting_paths(xs, ys, z, max_len)),
+            GraphView::Ag(g) => Ok(g.m_connecting_paths(xs, ys, z, max_len)),
+            _ => Err("connecting_paths is only defined for DAGs, ADMGs, and AGs".into()),
+        }
+    }
+
     // ---- DAG-only methods ----
     pub fn d_separated(&self, xs: &[u32], ys: &[u32], z: &[u32]) -> Result<bool, String> {
         match self {
@@ -444,6 +511,16 @@ impl GraphView {
         }
     }
 
+    /// A basis of the conditional independencies implied by a DAG, ADMG or AG.
+    pub fn implied_independencies(&self) -> Result<Vec<ConditionalIndependence>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.implied_independencies()),
+            GraphView::Admg(a) => Ok(a.implied_independencies()),
+            GraphView::Ag(g) => Ok(g.implied_independencies()),
+            _ => Err("implied_independencies is only defined for DAGs, ADMGs, and AGs".into()),
+        }
+    }
+
     pub fn adjustment_set_parents(&self, xs: &[u32], ys: &[u32]) -> Result<Vec<u32>, String> {
         match self {
             GraphView::Dag(d) => Ok(d.adjustment_set_parents(xs, ys)),
@@ -456,10 +533,24 @@ impl GraphView {
             _ => Err("adjustment_set_backdoor is only defined for DAGs".into()),
         }
     }
-    pub fn adjustment_set_optimal(&self, x: u32, y: u32) -> Result<Vec<u32>, String> {
-        match self {
-            GraphView::Dag(d) => Ok(d.adjustment_set_optimal(x, y)),
-            _ => Err("adjustment_set_optimal is only defined for DAGs".into()),
+    pub fn adjustment_set_optimal(&self, xs: &[u32], ys: &[u32]) -> Result<Vec<u32>, String> {
+        let gac = match self {
+            GraphView::Dag(d) => Amenability::Amenable(d.adjustment_set_optimal(xs, ys)?),
+            GraphView::Mpdag(g) => g.adjustment_set_optimal(xs, ys)?,
+            GraphView::Cpdag(g) => g.adjustment_set_optimal(xs, ys)?,
+            _ => {
+                return Err(
+                    "adjustment_set_optimal is only defined for DAGs, CPDAGs, and MPDAGs".into(),
+                )
+            }
+        };
+        match gac {
+            Amenability::Amenable(Some(z)) => Ok(z),
+            Amenability::Amenable(None) => Err("no valid adjustment set exists".into()),
+            Amenability::NotAmenable { x, first } => Err(format!(
+                "effect is not amenable: edge {} - {} is not directed",
+                x, first
+            )),
         }
     }
     pub fn is_valid_backdoor_set(&self, x: u32, y: u32, z: &[u32]) -> Result<bool, String> {
@@ -481,6 +572,66 @@ impl GraphView {
         }
     }
 
+    // ---- Generalized adjustment criterion (DAG/CPDAG/MPDAG/AG/PAG) ----
+    pub fn is_valid_adjustment_set(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        z: &[u32],
+    ) -> Result<Amenability<bool>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(Amenability::Amenable(d.is_valid_adjustment_set(xs, ys, z))),
+            GraphView::Mpdag(g) => g.is_valid_adjustment_set(xs, ys, z),
+            GraphView::Cpdag(g) => g.is_valid_adjustment_set(xs, ys, z),
+            GraphView::Ag(g) => g.is_valid_adjustment_set(xs, ys, z),
+            GraphView::Pag(g) => g.is_valid_adjustment_set(xs, ys, z),
+            _ => Err(
+                "is_valid_adjustment_set is only defined for DAGs, CPDAGs, MPDAGs, AGs, and PAGs"
+                    .into(),
+            ),
+        }
+    }
+
+    pub fn all_adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+        max_size: u32,
+    ) -> Result<Amenability<Vec<Vec<u32>>>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(Amenability::Amenable(
+                d.all_adjustment_sets(xs, ys, minimal, max_size),
+            )),
+            GraphView::Mpdag(g) => g.all_adjustment_sets(xs, ys, minimal, max_size),
+            GraphView::Cpdag(g) => g.all_adjustment_sets(xs, ys, minimal, max_size),
+            GraphView::Ag(g) => g.all_adjustment_sets(xs, ys, minimal, max_size),
+            GraphView::Pag(g) => g.all_adjustment_sets(xs, ys, minimal, max_size),
+            _ => Err(
+                "all_adjustment_sets is only defined for DAGs, CPDAGs, MPDAGs, AGs, and PAGs"
+                    .into(),
+            ),
+        }
+    }
+
+    pub fn adjustment_set_canonical(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+    ) -> Result<Amenability<Option<Vec<u32>>>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(Amenability::Amenable(d.adjustment_set_canonical(xs, ys))),
+            GraphView::Mpdag(g) => g.adjustment_set_canonical(xs, ys),
+            GraphView::Cpdag(g) => g.adjustment_set_canonical(xs, ys),
+            GraphView::Ag(g) => g.adjustment_set_canonical(xs, ys),
+            GraphView::Pag(g) => g.adjustment_set_canonical(xs, ys),
+            _ => Err(
+                "adjustment_set_canonical is only defined for DAGs, CPDAGs, MPDAGs, AGs, and PAGs"
+                    .into(),
+            ),
+        }
+    }
+
     // ---- ADMG adjustment methods ----
     pub fn is_valid_adjustment_set_admg(
         &self,
@@ -507,6 +658,186 @@ impl GraphView {
         }
     }
 
+    /// Lazy enumeration of valid (or minimal) adjustment sets for DAGs and
+    /// ADMGs; see [`Admg::adjustment_sets`].
+    pub fn adjustment_sets(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        minimal: bool,
+    ) -> Result<AdjustmentSets, String> {
+        match self {
+            GraphView::Dag(g) => g.adjustment_sets(xs, ys, minimal),
+            GraphView::Admg(g) => g.adjustment_sets(xs, ys, minimal),
+            _ => Err("adjustment_sets is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    pub fn is_valid_front_door_set(&self, x: u32, y: u32, m: &[u32]) -> Result<bool, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.is_valid_front_door_set(x, y, m)),
+            GraphView::Admg(g) => Ok(g.is_valid_front_door_set(x, y, m)),
+            _ => Err("is_valid_front_door_set is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    pub fn front_door_set(&self, x: u32, y: u32) -> Result<Option<Vec<u32>>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.front_door_set(x, y)),
+            GraphView::Admg(g) => Ok(g.front_door_set(x, y)),
+            _ => Err("front_door_set is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    pub fn is_instrument(&self, x: u32, y: u32, z: &[u32], w: &[u32]) -> Result<bool, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.is_instrument(x, y, z, w)),
+            GraphView::Admg(g) => Ok(g.is_instrument(x, y, z, w)),
+            _ => Err("is_instrument is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    pub fn instruments(&self, x: u32, y: u32, w: &[u32]) -> Result<Vec<u32>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.instruments(x, y, w)),
+            GraphView::Admg(g) => Ok(g.instruments(x, y, w)),
+            _ => Err("instruments is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    pub fn ancestral_instruments(&self, x: u32, y: u32) -> Result<Vec<(u32, Vec<u32>)>, String> {
+        match self {
+            GraphView::Dag(d) => Ok(d.ancestral_instruments(x, y)),
+            GraphView::Admg(g) => Ok(g.ancestral_instruments(x, y)),
+            _ => Err("ancestral_instruments is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    /// Identify `P(ys | do(xs))` with the ID algorithm; see [`Admg::identify`].
+    ///
+    /// Defined for ADMGs and DAGs (every DAG is an ADMG without bidirected edges).
+    pub fn identify(&self, xs: &[u32], ys: &[u32]) -> Result<Identification, String> {
+        match self {
+            GraphView::Admg(g) => g.identify(xs, ys),
+            GraphView::Dag(d) => Admg::new(Arc::new(d.core_ref().clone()))?.identify(xs, ys),
+            _ => Err("identify is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    /// Identify `P(ys | do(xs), zs)` with the IDC algorithm; see
+    /// [`Admg::identify_conditional`].
+    pub fn identify_conditional(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+    ) -> Result<Identification, String> {
+        match self {
+            GraphView::Admg(g) => g.identify_conditional(xs, ys, zs),
+            GraphView::Dag(d) => {
+                Admg::new(Arc::new(d.core_ref().clone()))?.identify_conditional(xs, ys, zs)
+            }
+            _ => Err("identify_conditional is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    /// Check which do-calculus rules apply to the query `(xs, ys, zs, ws)`.
+    pub fn do_calculus_rules(
+        &self,
+        xs: &[u32],
+        ys: &[u32],
+        zs: &[u32],
+        ws: &[u32],
+    ) -> Result<DoCalculusRules, String> {
+        match self {
+            GraphView::Dag(d) => d.do_calculus_rules(xs, ys, zs, ws),
+            GraphView::Admg(g) => g.do_calculus_rules(xs, ys, zs, ws),
+            _ => Err("do_calculus_rules is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
+    /// Number of DAGs in the Markov equivalence class of a CPDAG.
+    pub fn mec_size(&self) -> Result<MecSize, String> {
+        match self {
+            GraphView::Cpdag(g) => Ok(g.mec_size()),
+            _ => Err("mec_size is only defined for CPDAGs".into()),
+        }
+    }
+
+    /// Locally valid possible-parent sets of `x` (IDA) in a CPDAG or MPDAG.
+    pub fn possible_parent_sets(&self, x: u32) -> Result<Vec<Vec<u32>>, String> {
+        match self {
+            GraphView::Mpdag(m) => m.possible_parent_sets(x),
+            GraphView::Cpdag(c) => c.possible_parent_sets(x),
+            _ => Err("possible_parent_sets is only defined for CPDAGs and MPDAGs".into()),
+        }
+    }
+
+    /// Possible ancestors of `i` in a CPDAG, MPDAG or PAG.
+    pub fn possible_ancestors_of(&self, i: u32) -> Result<Vec<u32>, String> {
+        match self {
+            GraphView::Mpdag(m) => Ok(m.possible_ancestors_of(i)),
+            GraphView::Cpdag(c) => Ok(c.possible_ancestors_of(i)),
+            GraphView::Pag(g) => Ok(g.possible_ancestors_of(i)),
+            _ => Err("possible_ancestors_of is only defined for CPDAGs, MPDAGs, and PAGs".into()),
+        }
+    }
+
+    /// Possible descendants of `i` in a CPDAG, MPDAG or PAG.
+    pub fn possible_descendants_of(&self, i: u32) -> Result<Vec<u32>, String> {
+        match self {
+            GraphView::Mpdag(m) => Ok(m.possible_descendants_of(i)),
+            GraphView::Cpdag(c) => Ok(c.possible_descendants_of(i)),
+            GraphView::Pag(g) => Ok(g.possible_descendants_of(i)),
+            _ => Err("possible_descendants_of is only defined for CPDAGs, MPDAGs, and PAGs".into()),
+        }
+    }
+
+    /// Definite ancestors of `i` (ancestors in every represented graph).
+    pub fn definite_ancestors_of(&self, i: u32) -> Result<Vec<u32>, String> {
+        match self {
+            GraphView::Mpdag(m) => Ok(m.definite_ancestors_of(i)),
+            GraphView::Cpdag(c) => Ok(c.definite_ancestors_of(i)),
+            GraphView::Pag(g) => Ok(g.definite_ancestors_of(i)),
+            _ => Err("definite_ancestors_of is only defined for CPDAGs, MPDAGs, and PAGs".into()),
+        }
+    }
+
+    /// A consistent DAG extension of a PDAG, MPDAG or CPDAG.
+    pub fn consistent_extension(&self) -> Result<GraphView, String> {
+        let dag = match self {
+            GraphView::Pdag(p) => p.consistent_extension()?,
+            GraphView::Mpdag(m) => m.consistent_extension()?,
+            GraphView::Cpdag(c) => c.consistent_extension()?,
+            _ => return Err("consistent_extension is only defined for PDAGs".into()),
+        };
+        Ok(GraphView::Dag(Arc::new(dag)))
+    }
+
+    /// `count` member DAGs of a PDAG, MPDAG or CPDAG, drawn uniformly with `seed`.
+    pub fn sample_dags(&self, count: usize, seed: u64) -> Result<Vec<GraphView>, String> {
+        let dags = match self {
+            GraphView::Pdag(p) => p.sample_dags(count, seed)?,
+            GraphView::Mpdag(m) => m.sample_dags(count, seed)?,
+            GraphView::Cpdag(c) => c.sample_dags(count, seed)?,
+            _ => return Err("sample_dags is only defined for PDAGs".into()),
+        };
+        Ok(dags
+            .into_iter()
+            .map(|d| GraphView::Dag(Arc::new(d)))
+            .collect())
+    }
+
+    /// Lazy iterator over the member DAGs of a PDAG, MPDAG or CPDAG.
+    pub fn member_dags(&self) -> Result<MemberDags, String> {
+        match self {
+            GraphView::Pdag(p) => p.member_dags(),
+            GraphView::Mpdag(m) => m.member_dags(),
+            GraphView::Cpdag(c) => c.member_dags(),
+            _ => Err("member_dags is only defined for PDAGs".into()),
+        }
+    }
+
     pub fn induced_subgraph(&self, keep: &[u32]) -> Result<GraphView, String> {
         let (core2, _new_to_old, _old_to_new) = self.core().induced_subgraph(keep)?;
         let gv = match self {
@@ -555,6 +886,10 @@ impl GraphView {
                 let g = super::ag::Ag::new(std::sync::Arc::new(core2))?;
                 GraphView::Ag(std::sync::Arc::new(g))
             }
+            GraphView::Pag(_) => {
+                let g = super::pag::Pag::new(std::sync::Arc::new(core2))?;
+                GraphView::Pag(std::sync::Arc::new(g))
+            }
             GraphView::Raw(_) => GraphView::Raw(std::sync::Arc::new(core2)),
         };
         Ok(gv)
@@ -579,6 +914,76 @@ impl GraphView {
         }
     }
 
+    /// Convert to a PAG (essential graph of the MAG's Markov equivalence class).
+    ///
+    /// Defined for ancestral graphs (assumed maximal), DAGs (every DAG is a
+    /// MAG), ADMGs (via their MAG), and PAGs (identity).
+    pub fn to_pag(&self) -> Result<GraphView, String> {
+        match self {
+            GraphView::Dag(d) => {
+                let ag = Ag::new(Arc::new(d.core_ref().clone()))?;
+                Ok(GraphView::Pag(Arc::new(ag.to_pag()?)))
+            }
+            GraphView::Admg(a) => Ok(GraphView::Pag(Arc::new(a.to_mag()?.to_pag()?))),
+            GraphView::Ag(g) => Ok(GraphView::Pag(Arc::new(g.to_pag()?))),
+            GraphView::Pag(p) => Ok(GraphView::Pag(Arc::clone(p))),
+            _ => Err("to_pag is only defined for DAGs, ADMGs, AGs, and PAGs".into()),
+        }
+    }
+
+    /// Sample `n` rows from a linear SEM on a DAG (column-major `n × p`).
+    pub fn simulate_linear_sem(
+        &self,
+        n: usize,
+        weights: Option<&[f64]>,
+        noise_var: &[f64],
+        noise: NoiseFamily,
+        seed: u64,
+    ) -> Result<Vec<f64>, String> {
+        match self {
+            GraphView::Dag(d) => d.simulate_linear_sem(n, weights, noise_var, noise, seed),
+            _ => Err("simulate_linear_sem is only defined for DAGs".into()),
+        }
+    }
+
+    /// Run PC with this graph (DAG, ADMG or AG) as the independence oracle.
+    pub fn oracle_pc(&self) -> Result<GraphView, String> {
+        let mut oracle = SeparationOracle::new(self)?;
+        let cpdag = learn::pc(&mut oracle, &self.core().registry)?;
+        Ok(GraphView::Cpdag(Arc::new(cpdag)))
+    }
+
+    /// Run FCI with this graph (DAG, ADMG or AG) as the independence oracle.
+    pub fn oracle_fci(&self) -> Result<GraphView, String> {
+        let mut oracle = SeparationOracle::new(self)?;
+        let pag = learn::fci(&mut oracle, &self.core().registry)?;
+        Ok(GraphView::Pag(Arc::new(pag)))
+    }
+
+    /// Run GES with a Gaussian BIC on the covariance of a linear SEM on this
+    /// DAG, which makes the score an oracle.
+    ///
+    /// The SEM has unit error variances and coefficients drawn from
+    /// `[0.5, 1]` with a fixed seed. The score treats that covariance as
+    /// estimated from `10^12` samples, so a dependence only goes undetected
+    /// if its partial correlation is below about `5e-6`, e.g. when paths
+    /// nearly cancel; such a near-violation of faithfulness can still make
+    /// GES stop short of the CPDAG.
+    pub fn oracle_ges(&self) -> Result<GraphView, String> {
+        if !matches!(self, GraphView::Dag(_)) {
+            return Err("oracle_ges is only defined for DAGs".into());
+        }
+        let core = self.core();
+        let mut rng = SplitMix64::new(0x5eed);
+        let weights: Vec<f64> = (0..core.col_index.len())
+            .map(|_| 0.5 + 0.5 * rng.next_f64())
+            .collect();
+        let cov = LinearSem::new(self, &weights, &[1.0])?.implied_covariance();
+        let mut score = GaussianBic::from_covariance(cov, core.n() as usize, 1_000_000_000_000)?;
+        let cpdag = learn::ges(&mut score, core.n(), &core.registry)?;
+        Ok(GraphView::Cpdag(Arc::new(cpdag)))
+    }
+
     /// Apply Meek closure and return an MPDAG.
     ///
     /// Defined for DAGs (delegates to `to_cpdag` since a CPDAG is an MPDAG),
@@ -656,6 +1061,29 @@ impl GraphView {
         }
     }
 
+    /// Project onto a MAG.
+    ///
+    /// For DAGs, adjacencies come from inducing paths relative to
+    /// `⟨latents, selected⟩` and orientations from ancestral relations; see
+    /// [`Dag::to_mag`]. ADMGs (e.g. `latent_project` output) are converted to
+    /// the MAG with the same m-separation model and take no latent or selected
+    /// nodes; see [`Admg::to_mag`].
+    pub fn to_mag(&self, latents: &[u32], selected: &[u32]) -> Result<GraphView, String> {
+        match self {
+            GraphView::Dag(d) => {
+                let ag = d.to_mag(latents, selected)?;
+                Ok(GraphView::Ag(Arc::new(ag)))
+            }
+            GraphView::Admg(a) => {
+                if !latents.is_empty() || !selected.is_empty() {
+                    return Err("to_mag for ADMGs does not take latent or selected nodes".into());
+                }
+                Ok(GraphView::Ag(Arc::new(a.to_mag()?)))
+            }
+            _ => Err("to_mag is only defined for DAGs and ADMGs".into()),
+        }
+    }
+
     pub fn exogenize(&self, nodes: &[u32]) -> Result<GraphView, String> {
         match self {
             GraphView::Dag(d) => {
@@ -911,7 +1339,18 @@ mod tests {
         // adjustment sets (parents/backdoor/optimal) all return {0}
         assert_eq!(v.adjustment_set_parents(&[1], &[2]).unwrap(), vec![0]);
         assert_eq!(v.adjustment_set_backdoor(&[1], &[2]).unwrap(), vec![0]);
-        assert_eq!(v.adjustment_set_optimal(1, 2).unwrap(), vec![0]);
+        assert_eq!(v.adjustment_set_optimal(&[1], &[2]).unwrap(), vec![0]);
+
+        // 0 -> 1 -> 2 -> 3, 1 -> 3: no valid set for X = {0, 2}, Y = {3}.
+        let mut b = GraphBuilder::new_with_registry(4, true, &r);
+        for (u, w) in [(0, 1), (1, 2), (2, 3), (1, 3)] {
+            b.add_edge(u, w, d).unwrap();
+        }
+        let w = GraphView::Dag(Arc::new(Dag::new(Arc::new(b.finalize().unwrap())).unwrap()));
+        assert_eq!(
+            w.adjustment_set_optimal(&[0, 2], &[3]).unwrap_err(),
+            "no valid adjustment set exists"
+        );
 
         // backdoor validity + enumeration
         assert_eq!(v.is_valid_backdoor_set(1, 2, &[0]).unwrap(), true);
@@ -919,6 +1358,95 @@ mod tests {
         assert_eq!(v.all_backdoor_sets(1, 2, true, 5).unwrap(), vec![vec![0]]);
     }
 
+    #[test]
+    fn graphview_gac_dispatch_for_cpdag_and_pag() {
+        let mut r = EdgeRegistry::new();
+        r.register_builtins().unwrap();
+        let d = r.code_of("-->").unwrap();
+        let u = r.code_of("---").unwrap();
+        let po = r.code_of("o->").unwrap();
+
+        // CPDAG: 2 --> 0 <-- 3, 0 --> 1, 2 --> 1
+        let mut b = GraphBuilder::new_with_registry(4, true, &r);
+        b.add_edge(2, 0, d).unwrap();
+        b.add_edge(3, 0, d).unwrap();
+        b.add_edge(0, 1, d).unwrap();
+        b.add_edge(2, 1, d).unwrap();
+        let pdag = Pdag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        let v = GraphView::Cpdag(Arc::new(Cpdag::try_new(pdag).unwrap()));
+        assert_eq!(
+            v.is_valid_adjustment_set(&[0], &[1], &[2]).unwrap(),
+            Amenability::Amenable(true)
+        );
+        assert_eq!(
+            v.all_adjustment_sets(&[0], &[1], true, 3).unwrap(),
+            Amenability::Amenable(vec![vec![2]])
+        );
+        assert_eq!(
+            v.adjustment_set_canonical(&[0], &[1]).unwrap(),
+            Amenability::Amenable(Some(vec![2, 3]))
+        );
+        assert_eq!(v.adjustment_set_optimal(&[0], &[1]).unwrap(), vec![2]);
+
+        // CPDAG 0 --- 1: not amenable.
+        let mut b = GraphBuilder::new_with_registry(2, true, &r);
+        b.add_edge(0, 1, u).unwrap();
+        let pdag = Pdag::new(Arc::new(b.finalize().unwrap())).unwrap();
+        let v = GraphView::Cpdag(Arc::new(Cpdag::try_new(pdag).unwrap()));
+        assert_eq!(
+            v.is_valid_adjustment_set(&[0], &[1], &[]).unwrap(),
+            Amenability::NotAmenable { x: 0, first: 1 }
+        );
+        assert!(v
+            .adjustment_set_optimal(&[0], &[1])
+            .unwrap_err()
+            .contains("not amenable"));
+
+        // PAG 0 o-> 1: not amenable either.
+        let mut b = GraphBuilder::new_with_registry(2, true, &r);
+        b.add_edge(0, 1, po).unwrap();
+        let v = GraphView::Pag(Arc::new(Pag::new(Arc::new(b.finalize().unwrap())).unwrap()));
+        assert_eq!(
+            v.adjustment_set_canonical(&[0], &[1]).unwrap(),
+            Amenability::NotAmenable { x: 0, first: 1 }
+        );
+
+        // DAGs are always amenable: 2 -> 0 -> 1, 2 -> 1, 0 -> 3.
+        let mut b = GraphBuilder::new_with_registry(4, true, &r);
+        for (u, w) in [(2, 0), (0, 1), (2, 1), (0, 3)] {
+            b.add_edge(u, w, d).unwrap();
+        }
+        let v = GraphView::Dag(Arc::new(Dag::new(Arc::new(b.finalize().unwrap())).unwrap()));
+        assert_eq!(
+            v.is_valid_adjustment_set(&[0], &[1], &[2]).unwrap(),
+            Amenability::Amenable(true)
+        );
+        assert_eq!(
+            v.is_valid_adjustment_set(&[0], &[1], &[]).unwrap(),
+            Amenability::Amenable(false)
+        );
+        assert_eq!(
+            v.all_adjustment_sets(&[0], &[1], true, 2).unwrap(),
+            Amenability::Amenable(vec![vec![2]])
+        );
+        assert_eq!(
+            v.adjustment_set_canonical(&[0], &[1]).unwrap(),
+            Amenability::Amenable(Some(vec![2]))
+        );
+        let u = GraphView::Ug(Arc::new(
+            Ug::new(Arc::new(
+                GraphBuilder::new_with_registry(2, true, &r)
+                    .finalize()
+                    .unwrap(),
+            ))
+            .unwrap(),
+        ));
+        assert_eq!(
+            u.is_valid_adjustment_set(&[0], &[1], &[]).unwrap_err(),
+            "is_valid_adjustment_set is only defined for DAGs, CPDAGs, MPDAGs, AGs, and PAGs"
+        );
+    }
+
     // --- d-sep simple chain to hit both true/false via GraphView::Dag ---
     #[test]
     fn graphview_dag_dsep_chain_true_false() {
@@ -986,12 +1514,12 @@ mod tests {
 
         // adjustment_set_optimal
         assert_eq!(
-            vp.adjustment_set_optimal(0, 1).unwrap_err(),
-            "adjustment_set_optimal is only defined for DAGs"
+            vp.adjustment_set_optimal(&[0], &[1]).unwrap_err(),
+            "adjustment_set_optimal is only defined for DAGs, CPDAGs, and MPDAGs"
         );
         assert_eq!(
-            vr.adjustment_set_optimal(0, 1).unwrap_err(),
-            "adjustment_set_optimal is only defined for DAGs"
+            vr.adjustment_set_optimal(&[0], &[1]).unwrap_err(),
+            "adjustment_set_optimal is only defined for DAGs, CPDAGs, and MPDAGs"
         );
 
         // is_valid_backdoor_set
@@ -1236,6 +1764,34 @@ mod tests {
             v_pdag.to_cpdag().unwrap_err(),
             "to_cpdag is only defined for DAGs"
         );
+        // to_pag is defined for DAGs, ADMGs, AGs, and PAGs
+        assert!(matches!(v_ag.to_pag().unwrap(), GraphView::Pag(_)));
+        assert!(matches!(v_admg.to_pag().unwrap(), GraphView::Pag(_)));
+        assert_eq!(
+            v_pdag.to_pag().unwrap_err(),
+            "to_pag is only defined for DAGs, ADMGs, AGs, and PAGs"
+        );
+        // to_mag is defined for DAGs and ADMGs; ADMGs take no latents
+        assert!(matches!(v_admg.to_mag(&[], &[]).unwrap(), GraphView::Ag(_)));
+        assert_eq!(
+            v_admg.to_mag(&[0], &[]).unwrap_err(),
+            "to_mag for ADMGs does not take latent or selected nodes"
+        );
+        assert_eq!(
+            v_ag.to_mag(&[], &[]).unwrap_err(),
+            "to_mag is only defined for DAGs and ADMGs"
+        );
+        assert!(v_admg.identify(&[0], &[2]).is_ok());
+        assert!(v_admg.identify_conditional(&[0], &[2], &[1]).is_ok());
+        assert!(v_admg.do_calculus_rules(&[], &[2], &[0], &[]).is_ok());
+        assert_eq!(
+            v_ag.do_calculus_rules(&[], &[1], &[0], &[]).unwrap_err(),
+            "do_calculus_rules is only defined for DAGs and ADMGs"
+        );
+        assert_eq!(
+            v_ag.identify(&[0], &[1]).unwrap_err(),
+            "identify is only defined for DAGs and ADMGs"
+        );
 
         // DAG-only transform helpers error branches on non-DAG
         assert_eq!(
@@ -2184,4 +2740,89 @@ mod tests {
             vec![1]
         );
     }
+
+    #[test]
+    fn neighbors_of_pag_all_modes() {
+        let mut r = EdgeRegistry::new();
+        r.register_builtins().unwrap();
+        let d = r.code_of("-->").unwrap();
+        let bi = r.code_of("<->").unwrap();
+        let pd = r.code_of("o->").unwrap();
+        let cc = r.code_of("o-o").unwrap();
+
+        // Graph: 0 o-> 1 --> 2 <-> 3, 3 o-o 4
+        let mut b = GraphBuilder::new_with_registry(5, true, &r);
+        b.add_edge(0, 1, pd).unwrap();
+        b.add_edge(1, 2, d).unwrap();
+        b.add_edge(2, 3, bi).unwrap();
+        b.add_edge(3, 4, cc).unwrap();
+        let v = GraphView::Pag(Arc::new(Pag::new(Arc::new(b.finalize().unwrap())).unwrap()));
+
+        assert_eq!(v.n(), 5);
+        assert_eq!(v.parents_of(2).unwrap(), vec![1]);
+        assert!(v.parents_of(1).unwrap().is_empty());
+        assert_eq!(v.children_of(1).unwrap(), vec![2]);
+        assert_eq!(v.spouses_of(3).unwrap(), vec![2]);
+        assert_eq!(v.neighbors_of(1, NeighborMode::Partial).unwrap(), vec![0]);
+        assert_eq!(v.neighbors_of(3, NeighborMode::All).unwrap(), vec![2, 4]);
+        assert_eq!(v.ancestors_of(2).unwrap(), vec![1]);
+        assert!(v.markov_blanket_of(0).is_err());
+        assert!(v.topological_sort().is_err());
+
+        let sub = v.induced_subgraph(&[0, 1, 2]).unwrap();
+        assert!(matches!(sub, GraphView::Pag(_)));
+        assert_eq!(sub.neighbors_of(0, NeighborMode::Partial).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn oracle_ges_recovers_the_cpdag_of_random_dags() {
+        let mut r = EdgeRegistry::new();
+        r.register_builtins().unwrap();
+        let pattern = |v: GraphView| match v {
+            GraphView::Cpdag(c) => (0..c.n())
+                .map(|u| (c.children_of(u).to_vec(), c.undirected_of(u).to_vec()))
+                .collect::<Vec<_>>(),
+            _ => panic!("expected a CPDAG"),
+        };
+        for seed in 0..300 {
+            let n = 4 + (seed % 4) as u32;
+            let p = 0.3 + 0.1 * (seed % 4) as f64;
+            let mut s = crate::graph::generate::erdos_renyi_dag(&r, n, p, seed).unwrap();
+            let v = s.view().unwrap();
+            assert_eq!(
+                pattern(v.oracle_ges().unwrap()),
+                pattern(v.to_cpdag().unwrap()),
+                "seed {seed}"
+            );
+        }
+
+        // With 10^9 samples, 0 and 1 looked independent given 6 here
+        // (partial correlation 5e-5), and GES missed 0 --> 1.
+        let d = r.code_of("-->").unwrap();
+        let mut b = GraphBuilder::new_with_registry(7, true, &r);
+        for (u, w) in [
+            (0, 1),
+            (0, 2),
+            (0, 6),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (1, 5),
+            (1, 6),
+            (2, 3),
+            (2, 4),
+            (5, 6),
+        ] {
+            b.add_edge(u, w, d).unwrap();
+        }
+        let v = GraphView::Dag(Arc::new(Dag::new(Arc::new(b.finalize().unwrap())).unwrap()));
+        assert_eq!(
+            pattern(v.oracle_ges().unwrap()),
+            pattern(v.to_cpdag().unwrap())
+        );
+        assert_eq!(
+            v.to_cpdag().unwrap().oracle_ges().unwrap_err(),
+            "oracle_ges is only defined for DAGs"
+        );
+    }
 }
@@ -9,13 +9,19 @@ pub mod graph;
 use edges::{EdgeClass, EdgeRegistry, EdgeSpec, Mark};
 use graph::builder::GraphBuilder;
 
+use graph::alg::implied::ConditionalIndependence;
+use graph::alg::open_paths::{OpenPath, PathEdge};
+use graph::dag::NoiseFamily;
+use graph::equivalence::{markov_equivalent_with_perm, EquivalenceWitness};
 #[cfg(feature = "gadjid")]
 use graph::metrics::aid;
 use graph::metrics::{hd_with_perm, shd_with_perm};
+use graph::sem::Trek;
 
 use graph::view::GraphView;
 use graph::{
-    admg::Admg, ag::Ag, cpdag::Cpdag, dag::Dag, mpdag::Mpdag, pdag::Pdag, ug::Ug, CaugiGraph,
+    admg::Admg, ag::Ag, cpdag::Cpdag, dag::Dag, mpdag::Mpdag, pag::Pag, pdag::Pdag, ug::Ug,
+    CaugiGraph,
 };
 use std::sync::Arc;
 
@@ -366,6 +372,7 @@ fn graph_class_from_view(view: &GraphView) -> GraphClass {
         GraphView::Ug(_) => GraphClass::Ug,
         GraphView::Admg(_) => GraphClass::Admg,
         GraphView::Ag(_) => GraphClass::Ag,
+        GraphView::Pag(_) => GraphClass::Pag,
         GraphView::Raw(_) => GraphClass::Unknown,
     }
 }
@@ -379,6 +386,7 @@ fn graph_class_label_from_view(view: &GraphView) -> &'static str {
         GraphView::Ug(_) => "UG",
         GraphView::Admg(_) => "ADMG",
         GraphView::Ag(_) => "AG",
+        GraphView::Pag(_) => "PAG",
         GraphView::Raw(_) => "UNKNOWN",
     }
 }
@@ -586,6 +594,10 @@ fn graphview_new(core: ExternalPtr<CaugiGraph>, class: &str) -> ExternalPtr<Grap
             let ag = Ag::new(Arc::clone(&core_arc)).unwrap_or_else(|e| throw_r_error(e));
             ExternalPtr::new(GraphView::Ag(Arc::new(ag)))
         }
+        "PAG" => {
+            let pag = Pag::new(Arc::clone(&core_arc)).unwrap_or_else(|e| throw_r_error(e));
+            ExternalPtr::new(GraphView::Pag(Arc::new(pag)))
+        }
         "AUTO" => {
             // Try each class in order: DAG → UG → PDAG → ADMG → AG → Raw
             if let Ok(dag) = Dag::new(Arc::clone(&core_arc)) {
@@ -657,6 +669,42 @@ fn rs_hd(mut s1: ExternalPtr<GraphSession>, mut s2: ExternalPtr<GraphSession>) -
     list!(normalized = norm, count = count as i32).into_robj()
 }
 
+#[extendr]
+fn rs_markov_equivalent(
+    mut s1: ExternalPtr<GraphSession>,
+    mut s2: ExternalPtr<GraphSession>,
+) -> Robj {
+    let v1 = s1.as_mut().view().unwrap_or_else(|e| throw_r_error(e));
+    let v2 = s2.as_mut().view().unwrap_or_else(|e| throw_r_error(e));
+    if v1.n() != v2.n() {
+        throw_r_error("graph size mismatch");
+    }
+    let names1 = s1.as_ref().names();
+    let perm = build_perm_from_string_slices(names1, s2.as_ref().names())
+        .unwrap_or_else(|e| throw_r_error(e));
+    let witness = markov_equivalent_with_perm(v1.as_ref(), v2.as_ref(), &perm)
+        .unwrap_or_else(|e| throw_r_error(e));
+    let named =
+        |ix: &[u32]| -> Vec<String> { ix.iter().map(|&i| names1[i as usize].clone()).collect() };
+    match witness {
+        None => list!(equivalent = true, witness = NULL).into_robj(),
+        Some(EquivalenceWitness::Adjacency { u, v, in_first }) => list!(
+            equivalent = false,
+            witness = list!(
+                kind = "adjacency",
+                nodes = named(&[u, v]),
+                in_first = in_first
+            )
+        )
+        .into_robj(),
+        Some(EquivalenceWitness::Collider { path, in_first }) => list!(
+            equivalent = false,
+            witness = list!(kind = "collider", nodes = named(&path), in_first = in_first)
+        )
+        .into_robj(),
+    }
+}
+
 #[cfg(feature = "gadjid")]
 fn to_aid_input(view: &GraphView) -> std::result::Result<aid::AidInput<'_>, String> {
     match view {
@@ -1341,6 +1389,66 @@ fn posteriors(cg: Robj, nodes: Robj, index: Robj, open: Robj) -> Robj {
     })
 }
 
+#[extendr]
+fn possible_ancestors(cg: Robj, nodes: Robj, index: Robj, open: Robj) -> Robj {
+    let mut session = session_ptr_from_cg(&cg);
+    let idx0 = resolve_query_idx0(
+        &session,
+        nodes,
+        index,
+        "Must supply either `nodes` or `index`.",
+    );
+    let open_flag = parse_open_arg(open);
+
+    run_relation_query(&mut session, idx0, "node", "node", move |s, i| {
+        let mut v = s.as_mut().possible_ancestors_of(i)?;
+        if !open_flag {
+            v.insert(0, i);
+        }
+        Ok(v)
+    })
+}
+
+#[extendr]
+fn possible_descendants(cg: Robj, nodes: Robj, index: Robj, open: Robj) -> Robj {
+    let mut session = session_ptr_from_cg(&cg);
+    let idx0 = resolve_query_idx0(
+        &session,
+        nodes,
+        index,
+        "Must supply either `nodes` or `index`.",
+    );
+    let open_flag = parse_open_arg(open);
+
+    run_relation_query(&mut session, idx0, "node", "node", move |s, i| {
+        let mut v = s.as_mut().possible_descendants_of(i)?;
+        if !open_flag {
+            v.insert(0, i);
+        }
+        Ok(v)
+    })
+}
+
+#[extendr]
+fn definite_ancestors(cg: Robj, nodes: Robj, index: Robj, open: Robj) -> Robj {
+    let mut session = session_ptr_from_cg(&cg);
+    let idx0 = resolve_query_idx0(
+        &session,
+        nodes,
+        index,
+        "Must supply either `nodes` or `index`.",
+    );
+    let open_flag = parse_open_arg(open);
+
+    run_relation_query(&mut session, idx0, "node", "node", move |s, i| {
+        let mut v = s.as_mut().definite_ancestors_of(i)?;
+        if !open_flag {
+            v.insert(0, i);
+        }
+        Ok(v)
+    })
+}
+
 #[extendr]
 fn markov_blanket(cg: Robj, nodes: Robj, index: Robj) -> Robj {
     let mut session = session_ptr_from_cg(&cg);
@@ -1597,6 +1705,210 @@ fn rs_to_cpdag(mut session: ExternalPtr<GraphSession>) -> ExternalPtr<GraphSessi
     ExternalPtr::new(session_from_view(view, names))
 }
 
+#[extendr]
+fn rs_oracle_pc(mut session: ExternalPtr<GraphSession>) -> ExternalPtr<GraphSession> {
+    let view = session
+        .as_mut()
+        .oracle_pc()
+        .unwrap_or_else(|e| throw_r_error(e));
+    let names: Vec<String> = session.as_ref().names().to_vec();
+    ExternalPtr::new(session_from_view(view, names))
+}
+
+#[extendr]
+fn rs_oracle_fci(mut session: ExternalPtr<GraphSession>) -> ExternalPtr<GraphSession> {
+    let view = session
+        .as_mut()
+        .oracle_fci()
+        .unwrap_or_else(|e| throw_r_error(e));
+    let names: Vec<String> = session.as_ref().names().to_vec();
+    ExternalPtr::new(session_from_view(view, names))
+}
+
+#[extendr]
+fn rs_oracle_ges(mut session: ExternalPtr<GraphSession>) -> ExternalPtr<GraphSession> {
+    let view = session
+        .as_mut()
+        .oracle_ges()
+        .unwrap_or_else(|e| throw_r_error(e));
+    let names: Vec<String> = session.as_ref().names().to_vec();
+    ExternalPtr::new(session_from_view(view, names))
+}
+
+#[extendr]
+fn rs_pc_fisher_z(
+    reg: ExternalPtr<EdgeRegistry>,
+    data: &[f64],
+    n: i32,
+    names: Strings,
+    alpha: f64,
+    is_covariance: Rbool,
+) -> ExternalPtr<GraphSession> {
+    if n < 0 {
+        throw_r_error("n must be >= 0");
+    }
+    let node_names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+    let p = node_names.len();
+    let mut test = if is_covariance.is_true() {
+        graph::learn::FisherZ::from_covariance(data.to_vec(), p, n as usize, alpha)
+    } else {
+        graph::learn::FisherZ::from_data(data, n as usize, p, alpha)
+    }
+    .unwrap_or_else(|e| throw_r_error(e));
+    let template = GraphSession::new(reg.as_ref(), p as u32, true, GraphClass::Cpdag);
+    let cpdag =
+        graph::learn::pc(&mut test, template.registry()).unwrap_or_else(|e| throw_r_error(e));
+    ExternalPtr::new(session_from_view(
+        GraphView::Cpdag(Arc::new(cpdag)),
+        node_names,
+    ))
+}
+
+#[extendr]
+fn rs_edge_weights(
+    mut session: ExternalPtr<GraphSession>,
+    from: Integers,
+    to: Integers,
+    weight: &[f64],
+) -> Vec<f64> {
+    if from.len() != to.len() || from.len() != weight.len() {
+        throw_r_error("from, to and weight must have the same length");
+    }
+    let edges: Vec<(u32, u32, f64)> = from
+        .iter()
+        .zip(to.iter())
+        .zip(weight)
+        .map(|((u, v), &w)| (rint_to_u32(u, "from"), rint_to_u32(v, "to"), w))
+        .collect();
+    session
+        .as_mut()
+        .edge_weights(&edges)
+        .unwrap_or_else(|e| throw_r_error(e))
+}
+
+#[extendr]
+fn rs_simulate_sem(
+    mut session: ExternalPtr<GraphSession>,
+    n: i32,
+    weights: Nullable<&[f64]>,
+    noise_var: &[f64],
+    noise: &str,
+    df: f64,
+    seed: i32,
+) -> Vec<f64> {
+    if n < 0 {
+        throw_r_error("n must be >= 0");
+    }
+    let family = match noise {
+        "gaussian" => NoiseFamily::Gaussian,
+        "uniform" => NoiseFamily::Uniform,
+        "laplace" => NoiseFamily::Laplace,
+        "t" => NoiseFamily::StudentT { df },
+        other => throw_r_error(format!("Unknown noise family '{}'", other)),
+    };
+    let weights = match weights {
+        Nullable::NotNull(w) => Some(w),
+        Nullable::Null => None,
+    };
+    session
+        .as_mut()
+        .simulate_linear_sem(n as usize, weights, noise_var, family, seed as u32 as u64)
+        .unwrap_or_else(|e| throw_r_error(e))
+}
+
+#[extendr]
+fn rs_implied_covariance(
+    mut session: ExternalPtr<GraphSession>,
+    weights: &[f64],
+    error_var: &[f64],
+) -> Vec<f64> {
+    session
+        .as_mut()
+        .linear_sem(weights, error_var)
+        .unwrap_or_else(|e| throw_r_error(e))
+        .implied_covariance()
+}
+
+#[extendr]
+fn rs_total_effects(
+    mut session: ExternalPtr<GraphSession>,
+    weights: &[f64],
+    xs: Integers,
+    ys: Integers,
+) -> Vec<f64> {
+    let xs_u: Vec<u32> = xs.iter().map(|ri| rint_to_u32(ri, "xs")).collect();
+    let ys_u: Vec<u32> = ys.iter().map(|ri| rint_to_u32(ri, "ys")).collect();
+    let effects = session
+        .as_mut()
+        .total_effects(weights, &xs_u, &ys_u)
+        .unwrap_or_else(|e| throw_r_error(e));
+    // Row-major |xs| x |ys| to column-major for R.
+    let (r, c) = (xs_u.len(), ys_u.len());
+    (0..r * c).map(|k| effects[(k % r) * c + k / r]).collect()
+}
+
+#[extendr]
+fn rs_treks(
+    mut session: ExternalPtr<GraphSession>,
+    weights: &[f64],
+    error_var: &[f64],
+    i: i32,
+    j: i32,
+) -> Robj {
+    let i = rint_to_u32(Rint::from(i), "i");
+    let j = rint_to_u32(Rint::from(j), "j");
+    let treks = session
+        .as_mut()
+        .linear_sem(weights, error_var)
+        .and_then(|sem| sem.treks(i, j))
+        .unwrap_or_else(|e| throw_r_error(e));
+    let column = |f: fn(&Trek) -> &Vec<u32>| -> Robj {
+        let paths: Vec<Robj> = treks
+            .iter()
+            .map(|t| f(t).iter().map(|&u| u as i32).collect_robj())
+            .collect();
+        extendr_api::prelude::List::from_values(paths).into_robj()
+    };
+    list!(
+        left = column(|t| &t.left),
+        right = column(|t| &t.right),
+        weight = treks.iter().map(|t| t.weight).collect_robj()
+    )
+    .into_robj()
+}
+
+#[extendr]
+#[allow(clippy::too_many_arguments)]
+fn rs_random_graph(
+    reg: ExternalPtr<EdgeRegistry>,
+    model: &str,
+    n: i32,
+    p: f64,
+    degree: f64,
+    m: i32,
+    p_bidirected: f64,
+    n_latent: i32,
+    seed: i32,
+) -> ExternalPtr<GraphSession> {
+    use graph::generate;
+    if n < 0 || m < 0 || n_latent < 0 {
+        throw_r_error("n, m and n_latent must be >= 0");
+    }
+    let (reg, n, seed) = (reg.as_ref(), n as u32, seed as u32 as u64);
+    let session = match model {
+        "er_dag" => generate::erdos_renyi_dag(reg, n, p, seed),
+        "degree_dag" => generate::expected_degree_dag(reg, n, degree, seed),
+        "ba_dag" => generate::barabasi_albert_dag(reg, n, m as u32, seed),
+        "admg" => generate::random_admg(reg, n, p, p_bidirected, seed),
+        "mag" => generate::random_mag(reg, n, n_latent as u32, p, seed),
+        "cpdag" => generate::random_cpdag(reg, n, p, seed),
+        "chordal_ug" => generate::random_chordal_ug(reg, n, p, seed),
+        other => throw_r_error(format!("Unknown random graph model '{}'", other)),
+    }
+    .unwrap_or_else(|e| throw_r_error(e));
+    ExternalPtr::new(session)
+}
+
 #[extendr]
 fn rs_meek_closure(mut session: ExternalPtr<GraphSession>) -> ExternalPtr<GraphSession> {
     let view = session
@@ -1887,6 +2199,95 @@ fn rs_d_separated(
         .unwrap_or_else(|e| throw_r_error(e))
 }
 
+fn open_path_to_robj(p: &OpenPath) -> Robj {
+    let edges: Vec<&str> = p
+        .edges
+        .iter()
+        .map(|e| match e {
+            PathEdge::Forward => "-->",
+            PathEdge::Backward => "<--",
+            PathEdge::Bidirected => "<->",
+            PathEdge::Undirected => "---",
+        })
+        .collect();
+    list!(
+        nodes = p.nodes.iter().map(|&v| v as i32).collect_robj(),
+        edges = edges,
+        colliders = p
+            .opened_colliders
+            .iter()
+            .map(|&(c, _)| c as i32)
+            .collect_robj(),
+        opened_by = p
+            .opened_colliders
+            .iter()
+            .map(|&(_, z)| z as i32)
+            .collect_robj()
+    )
+    .into_robj()
+}
+
+#[extendr]
+fn rs_connecting_path(
+    mut session: ExternalPtr<GraphSession>,
+    xs: Integers,
+    ys: Integers,
+    z: Integers,
+) -> Robj {
+    let xs_u: Vec<u32> = xs.iter().map(|ri| rint_to_u32(ri, "xs")).collect();
+    let ys_u: Vec<u32> = ys.iter().map(|ri| rint_to_u32(ri, "ys")).collect();
+    let z_u: Vec<u32> = z.iter().map(|ri| rint_to_u32(ri, "z")).collect();
+    match session
+        .as_mut()
+        .connecting_path(&xs_u, &ys_u, &z_u)
+        .unwrap_or_else(|e| throw_r_error(e))
+    {
+        Some(p) => open_path_to_robj(&p),
+        None => ().into_robj(),
+    }
+}
+
+#[extendr]
+fn rs_connecting_paths(
+    mut session: ExternalPtr<GraphSession>,
+    xs: Integers,
+    ys: Integers,
+    z: Integers,
+    max_len: i32,
+) -> Robj {
+    let xs_u: Vec<u32> = xs.iter().map(|ri| rint_to_u32(ri, "xs")).collect();
+    let ys_u: Vec<u32> = ys.iter().map(|ri| rint_to_u32(ri, "ys")).collect();
+    let z_u: Vec<u32> = z.iter().map(|ri| rint_to_u32(ri, "z")).collect();
+    let max_len = rint_to_u32(Rint::from(max_len), "max_len");
+    let paths = session
+        .as_mut()
+        .connecting_paths(&xs_u, &ys_u, &z_u, max_len as usize)
+        .unwrap_or_else(|e| throw_r_error(e));
+    let robjs: Vec<Robj> = paths.iter().map(open_path_to_robj).collect();
+    extendr_api::prelude::List::from_values(robjs).into_robj()
+}
+
+#[extendr]
+fn rs_implied_independencies(mut session: ExternalPtr<GraphSession>) -> Robj {
+    let cis = session
+        .as_mut()
+        .implied_independencies()
+        .unwrap_or_else(|e| throw_r_error(e));
+    let column = |f: fn(&ConditionalIndependence) -> &Vec<u32>| -> Robj {
+        let sets: Vec<Robj> = cis
+            .iter()
+            .map(|c| f(c).iter().map(|&u| u as i32).collect_robj())
+            .collect();
+        extendr_api::prelude::List::from_values(sets).into_robj()
+    };
+    list!(
+        x = column(|c| &c.x),
+        y = column(|c| &c.y),
+        z = column(|c| &c.z)
+    )
+    .into_robj()
+}
+
 #[extendr]
 fn rs_minimal_separator(
     mut session: ExternalPtr<GraphSession>,
@@ -2183,6 +2584,9 @@ extendr_module! {
     fn descendants;
     fn anteriors;
     fn posteriors;
+    fn possible_ancestors;
+    fn possible_descendants;
+    fn definite_ancestors;
     fn markov_blanket;
     fn exogenous;
     fn topological_sort;
@@ -2199,6 +2603,16 @@ extendr_module! {
     fn rs_is_cpdag;
     fn rs_is_mpdag;
     fn rs_to_cpdag;
+    fn rs_oracle_pc;
+    fn rs_oracle_fci;
+    fn rs_oracle_ges;
+    fn rs_pc_fisher_z;
+    fn rs_edge_weights;
+    fn rs_simulate_sem;
+    fn rs_implied_covariance;
+    fn rs_total_effects;
+    fn rs_treks;
+    fn rs_random_graph;
     fn rs_meek_closure;
     fn rs_skeleton;
     fn rs_moralize;
@@ -2209,6 +2623,9 @@ extendr_module! {
     fn subgraph;
     fn rs_d_separated;
     fn rs_minimal_separator;
+    fn rs_implied_independencies;
+    fn rs_connecting_path;
+    fn rs_connecting_paths;
     fn rs_m_separated;
     fn rs_not_m_separated_for_all_subsets;
     fn rs_adjustment_set_parents;
@@ -2220,6 +2637,7 @@ extendr_module! {
     fn rs_all_adjustment_sets_admg;
     fn rs_shd;
     fn rs_hd;
+    fn rs_markov_equivalent;
     fn rs_ancestor_aid;
     fn rs_oset_aid;
     fn rs_parent_aid;